@@ -32,8 +32,10 @@ impl CameraObject {
 
 impl GameObject for CameraObject {
 
-    fn update(&mut self, ctx: &Context, _scene: &Scene) -> GameResult {
-        self.camera.update_projection(ctx.window.width() as f32, ctx.window.height() as f32);
+    fn update(&mut self, ctx: &Context, scene: &Scene) -> GameResult {
+        self.camera.update_viewport_projection(ctx.window.width() as f32, ctx.window.height() as f32);
+        scene.set_view_position(*self.camera.transform.position());
+        ctx.renderer.add_camera(&self.camera);
         Ok(())
     }
 
@@ -2,42 +2,41 @@ mod mesh;
 use mesh::CubeMesh;
 use microengine::prelude::*;
 use microengine::components::transform::*;
-use super::camera::{CAMERA_NAME, CameraObject};
 
-pub fn add_cube(scene: &mut Scene) {
-    let cube = Cube::default();
-    _ = scene.add_gameobject(cube, 1).unwrap()
+pub fn add_cube(scene: &mut Scene) -> GameResult {
+    let cube = Cube {
+        transform: Transform::default(),
+        mesh: CubeMesh::new()?,
+    };
+    scene.add_gameobject(cube, 1)?;
+    Ok(())
 }
 
-#[derive(Default)]
 pub struct Cube {
     transform: Transform,
     mesh: CubeMesh,
-    camera_id: Option<GameObjectId>,
 }
 
 impl GameObject for Cube {
-    fn start(&mut self, _ctx: &Context, scene: &Scene) -> GameResult {
-        self.camera_id = scene.get_gameobject_id(CAMERA_NAME);
-        Ok(())
-    }
-
     fn update(&mut self, ctx: &Context, _scene: &Scene) -> GameResult {
         self.transform.rotate(glm::Vec3::x(), ctx.time.delta_time() as f32, Space::Local);
         self.transform.rotate(glm::Vec3::z(), ctx.time.delta_time() as f32, Space::Local);
         Ok(())
     }
 
-    fn draw(&mut self, _ctx: &Context, scene: &Scene) -> GameResult {
-        let camera = scene.gameobject_by_id::<CameraObject>(self.camera_id.as_ref().unwrap()).unwrap();
-        self.mesh.draw(
-            camera.camera.transform.position(),
-            &camera.camera.world_to_projection_matrix(),
-            &self.transform.local_to_world(), 
-        );
+    fn draw(&mut self, ctx: &Context, _scene: &Scene) -> GameResult {
+        ctx.renderer.submit(self.mesh.draw_command(self.transform.local_to_world()));
         Ok(())
     }
 
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.transform)
+    }
+
+    fn transform_mut(&mut self) -> Option<&mut Transform> {
+        Some(&mut self.transform)
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
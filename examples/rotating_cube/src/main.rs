@@ -5,7 +5,7 @@ extern crate gl;
 extern crate nalgebra_glm as glm;
 
 use compose::compose;
-use microengine::{Backend::GLFW, GameConfig, Game};
+use microengine::{Backend::GLFW, ErrorPolicy, GameConfig, Game};
 use std::process;
 
 const MAIN_SCENE: &str = "Rotating Cube";
@@ -16,11 +16,15 @@ pub fn game_config() -> GameConfig {
         window_cfg: Default::default(),
         fixed_fps: 50,
         starting_scene_name: MAIN_SCENE.into(),
+        error_policy: ErrorPolicy::Abort,
+        log_cfg: Default::default(),
     }
 }
 
 fn main() {
-    let result = compose(Game::from(game_config())).run();
+    let result = Game::try_from(game_config())
+        .and_then(compose)
+        .and_then(|mut game| game.run());
     match result {
         Ok(_) => (),
         Err(e) => {
@@ -2,12 +2,26 @@
 /// Provides a thin abstraction over Shader Programs (compiling, binding etc.)
 mod program;
 
+use crate::error::{check_gl_error, clear_gl_errors, GlError, GlResult};
 use gl::types::*;
 use program::{Program, Shader};
 use std::fs::File;
 use std::io::read_to_string;
 
 
+/// Uniform value which can be set with CompiledProgram::set_uniform
+#[derive(Debug, Clone, PartialEq)]
+pub enum Uniform {
+    Int(i32),
+    Float(f32),
+    Vec2(glm::Vec2),
+    Vec3(glm::Vec3),
+    Vec4(glm::Vec4),
+    Mat4(glm::Mat4),
+    /// uniform array, e.g. joint matrices of a skinned mesh
+    Mat4Array(Vec<glm::Mat4>),
+}
+
 pub struct CompiledProgram {
     program: Program,
     vao_id: GLuint,
@@ -16,33 +30,61 @@ pub struct CompiledProgram {
 }
 
 impl CompiledProgram {
-    pub fn new(vs_path: &str, fs_path: &str) -> CompiledProgram {
-        let program = CompiledProgram::compile_shaders(vs_path, fs_path);
+    /// Compiles program from vertex and fragment shader files
+    pub fn new(vs_path: &str, fs_path: &str) -> GlResult<CompiledProgram> {
+        let vs = CompiledProgram::read_source(vs_path)?;
+        let fs = CompiledProgram::read_source(fs_path)?;
+        let vs = CompiledProgram::compile_shader(&vs, gl::VERTEX_SHADER, vs_path)?;
+        let fs = CompiledProgram::compile_shader(&fs, gl::FRAGMENT_SHADER, fs_path)?;
+        CompiledProgram::from_shaders(&[vs, fs])
+    }
+
+    /// Compiles program from vertex and fragment shader source code
+    pub fn from_sources(vs_source: &str, fs_source: &str) -> GlResult<CompiledProgram> {
+        let vs = CompiledProgram::compile_shader(vs_source, gl::VERTEX_SHADER, "vertex shader")?;
+        let fs =
+            CompiledProgram::compile_shader(fs_source, gl::FRAGMENT_SHADER, "fragment shader")?;
+        CompiledProgram::from_shaders(&[vs, fs])
+    }
+
+    fn from_shaders(shaders: &[Shader]) -> GlResult<CompiledProgram> {
+        let program =
+            Program::from_shaders(shaders).map_err(|log| GlError::ProgramLink { log })?;
         let mut vao_id: GLuint = 0;
         let mut vbo_id: GLuint = 0;
         let mut ebo_id: GLuint = 0;
+        clear_gl_errors();
         unsafe {
             gl::GenVertexArrays(1, &mut vao_id);
             gl::BindVertexArray(vao_id);
             gl::GenBuffers(1, &mut ebo_id);
             gl::GenBuffers(1, &mut vbo_id);
         }
-        CompiledProgram {
+        let program = CompiledProgram {
             program,
             vao_id,
             vbo_id,
             ebo_id,
-        }
+        };
+        check_gl_error()?;
+        Ok(program)
     }
 
-    fn compile_shaders(vs_path: &str, fs_path: &str) -> Program {
-        let vs = File::open(vs_path).unwrap_or_else(|_| panic!("Missing vertex shader file: {}", vs_path));
-        let fs = File::open(fs_path).unwrap_or_else(|_| panic!("Missing fragment shader file: {}", fs_path));
-        let vs = read_to_string(vs).unwrap();
-        let fs = read_to_string(fs).unwrap();
-        let vs = Shader::from_source(&vs, gl::VERTEX_SHADER).unwrap();
-        let fs = Shader::from_source(&fs, gl::FRAGMENT_SHADER).unwrap();
-        Program::from_shaders(&[vs, fs]).unwrap()
+    fn read_source(path: &str) -> GlResult<String> {
+        File::open(path)
+            .and_then(read_to_string)
+            .map_err(|source| GlError::Io {
+                path: path.into(),
+                source,
+            })
+    }
+
+    /// path is only used to describe the shader in case of compilation error
+    fn compile_shader(source: &str, kind: GLenum, path: &str) -> GlResult<Shader> {
+        Shader::from_source(source, kind).map_err(|log| GlError::ShaderCompile {
+            path: path.into(),
+            log,
+        })
     }
 
     pub fn bind_program(&self) {
@@ -61,6 +103,27 @@ impl CompiledProgram {
         self.program.get_uniform_location(name)
     }
     
+    /// Sets uniform of this program, it has to be bound first
+    pub fn set_uniform(&self, name: &str, value: &Uniform) {
+        let location = self.get_uniform_location(name);
+        unsafe {
+            match *value {
+                Uniform::Int(v) => gl::Uniform1i(location, v),
+                Uniform::Float(v) => gl::Uniform1f(location, v),
+                Uniform::Vec2(ref v) => gl::Uniform2f(location, v.x, v.y),
+                Uniform::Vec3(ref v) => gl::Uniform3f(location, v.x, v.y, v.z),
+                Uniform::Vec4(ref v) => gl::Uniform4f(location, v.x, v.y, v.z, v.w),
+                Uniform::Mat4(ref m) => gl::UniformMatrix4fv(location, 1, gl::FALSE, m.as_ptr()),
+                Uniform::Mat4Array(ref m) => gl::UniformMatrix4fv(
+                    location,
+                    m.len() as GLsizei,
+                    gl::FALSE,
+                    m.as_ptr() as *const GLfloat,
+                ),
+            }
+        }
+    }
+
     pub fn bind_uniform_to_block_idx(&self, name: &str, block_idx: u32) {
         self.program.bind_uniform_to_block_idx(name, block_idx);
     }
@@ -8,7 +8,7 @@ pub struct Shader {
 
 impl Shader {
     pub fn from_source(source: &str, kind: GLenum) -> Result<Shader, String> {
-        let source = &CString::new(source).unwrap();
+        let source = &CString::new(source).map_err(|e| e.to_string())?;
         let id = shader_from_source(source, kind)?;
         Ok(Shader { id })
     }
@@ -0,0 +1,60 @@
+//! Error type returned by gl_utils and Result<T, GlError> wrapper
+use gl::types::GLenum;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum GlError {
+    /// Shader or texture file couldn't be read
+    Io { path: String, source: std::io::Error },
+    /// Shader compilation failed, contains shader info log
+    ShaderCompile { path: String, log: String },
+    /// Program linking failed, contains program info log
+    ProgramLink { log: String },
+    /// Image couldn't be decoded
+    Image { path: String, source: image::ImageError },
+    /// Error reported by glGetError
+    Gl(GLenum),
+}
+
+impl fmt::Display for GlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            GlError::Io { ref path, ref source } => write!(f, "Couldn't read '{path}': {source}"),
+            GlError::ShaderCompile { ref path, ref log } => {
+                write!(f, "Couldn't compile shader '{path}':\n{log}")
+            }
+            GlError::ProgramLink { ref log } => write!(f, "Couldn't link program:\n{log}"),
+            GlError::Image { ref path, ref source } => {
+                write!(f, "Couldn't decode '{path}' image file: {source}")
+            }
+            GlError::Gl(code) => write!(f, "OpenGL error: 0x{code:X}"),
+        }
+    }
+}
+
+impl Error for GlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            GlError::Io { ref source, .. } => Some(source),
+            GlError::Image { ref source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub type GlResult<T = ()> = Result<T, GlError>;
+
+/// Discards errors reported by OpenGL so far, call it before calls checked
+/// with check_gl_error so earlier unrelated errors aren't attributed to them
+pub fn clear_gl_errors() {
+    while unsafe { gl::GetError() } != gl::NO_ERROR {}
+}
+
+/// Returns the oldest error reported by OpenGL, if there is any
+pub fn check_gl_error() -> GlResult {
+    match unsafe { gl::GetError() } {
+        gl::NO_ERROR => Ok(()),
+        code => Err(GlError::Gl(code)),
+    }
+}
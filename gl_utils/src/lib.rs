@@ -1,10 +1,17 @@
 /// This package contains some basic, safe abstractions over OpenGL API
 mod compiled_program;
+pub mod error;
+mod mesh;
 mod texture;
 pub mod primitives;
+pub mod skinning;
+mod text;
 extern crate gl;
 extern crate image;
 extern crate nalgebra_glm as glm;
 
-pub use compiled_program::CompiledProgram;
+pub use compiled_program::{CompiledProgram, Uniform};
+pub use error::{GlError, GlResult};
+pub use mesh::Mesh;
+pub use text::TextRenderer;
 pub use texture::Texture;
@@ -0,0 +1,190 @@
+/// Provides Mesh which owns vertex and index buffers of a single model
+/// Vertex attributes layout: location 0 - position, 1 - normal, 2 - texture coordinates,
+/// skinned meshes add 3 - joint indices and 4 - joint weights
+use crate::error::{check_gl_error, clear_gl_errors, GlResult};
+use crate::primitives::{Bounds, Cube, Plane, Sphere};
+use gl::types::*;
+
+/// floats per vertex: position (3), normal (3), texture coordinates (2)
+const VERTEX_SIZE: usize = 8;
+/// floats per skinned vertex: VERTEX_SIZE + joint indices (4) + joint weights (4)
+const SKINNED_VERTEX_SIZE: usize = VERTEX_SIZE + 8;
+
+pub struct Mesh {
+    vao_id: GLuint,
+    vbo_id: GLuint,
+    ebo_id: GLuint,
+    indices: usize,
+    bounds: Bounds,
+}
+
+impl Mesh {
+    /// Uploads given vertex data to GPU, normals and texture_coordinates
+    /// missing for some vertices are filled with zeros
+    pub fn new(
+        verts: &[glm::Vec3],
+        normals: &[glm::Vec3],
+        texture_coordinates: &[glm::Vec2],
+        indices: &[u32],
+    ) -> GlResult<Self> {
+        let buffer = verts
+            .iter()
+            .enumerate()
+            .flat_map(|(i, v)| {
+                let n = normals.get(i).copied().unwrap_or_default();
+                let uv = texture_coordinates.get(i).copied().unwrap_or_default();
+                [v.x, v.y, v.z, n.x, n.y, n.z, uv.x, uv.y]
+            })
+            .collect::<Vec<f32>>();
+        Mesh::upload(&buffer, VERTEX_SIZE, verts, indices)
+    }
+
+    /// Uploads mesh with skinning data, each vertex is influenced by up to 4 joints.
+    /// Joint indices are stored at location 3 (as floats) and their weights at location 4,
+    /// see skinning module for a vertex shader using them
+    pub fn new_skinned(
+        verts: &[glm::Vec3],
+        normals: &[glm::Vec3],
+        texture_coordinates: &[glm::Vec2],
+        joints: &[[u32; 4]],
+        weights: &[glm::Vec4],
+        indices: &[u32],
+    ) -> GlResult<Self> {
+        let buffer = verts
+            .iter()
+            .enumerate()
+            .flat_map(|(i, v)| {
+                let n = normals.get(i).copied().unwrap_or_default();
+                let uv = texture_coordinates.get(i).copied().unwrap_or_default();
+                let j = joints.get(i).copied().unwrap_or_default().map(|j| j as f32);
+                let w = weights.get(i).copied().unwrap_or_default();
+                [
+                    v.x, v.y, v.z, n.x, n.y, n.z, uv.x, uv.y, j[0], j[1], j[2], j[3], w.x, w.y,
+                    w.z, w.w,
+                ]
+            })
+            .collect::<Vec<f32>>();
+        Mesh::upload(&buffer, SKINNED_VERTEX_SIZE, verts, indices)
+    }
+
+    fn upload(buffer: &[f32], vertex_size: usize, verts: &[glm::Vec3], indices: &[u32]) -> GlResult<Self> {
+        let mut mesh = Mesh {
+            vao_id: 0,
+            vbo_id: 0,
+            ebo_id: 0,
+            indices: indices.len(),
+            bounds: Bounds::from_points(verts),
+        };
+        let stride = (vertex_size * std::mem::size_of::<f32>()) as GLint;
+        let attributes: &[(GLuint, GLint, usize)] = match vertex_size {
+            SKINNED_VERTEX_SIZE => &[(0, 3, 0), (1, 3, 3), (2, 2, 6), (3, 4, 8), (4, 4, 12)],
+            _ => &[(0, 3, 0), (1, 3, 3), (2, 2, 6)],
+        };
+        clear_gl_errors();
+        unsafe {
+            gl::GenVertexArrays(1, &mut mesh.vao_id);
+            gl::GenBuffers(1, &mut mesh.vbo_id);
+            gl::GenBuffers(1, &mut mesh.ebo_id);
+            gl::BindVertexArray(mesh.vao_id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, mesh.vbo_id);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(buffer) as GLsizeiptr,
+                buffer.as_ptr() as *const GLvoid,
+                gl::STATIC_DRAW,
+            );
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, mesh.ebo_id);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(indices) as GLsizeiptr,
+                indices.as_ptr() as *const GLvoid,
+                gl::STATIC_DRAW,
+            );
+            for &(location, size, offset) in attributes {
+                gl::EnableVertexAttribArray(location);
+                gl::VertexAttribPointer(
+                    location,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    (offset * std::mem::size_of::<f32>()) as *const GLvoid,
+                );
+            }
+            gl::BindVertexArray(0);
+        }
+        check_gl_error()?;
+        Ok(mesh)
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vao_id);
+        }
+    }
+
+    /// Draws the whole mesh, it has to be bound first
+    pub fn draw(&self) {
+        unsafe {
+            gl::DrawElements(
+                gl::TRIANGLES,
+                self.indices as GLsizei,
+                gl::UNSIGNED_INT,
+                std::ptr::null::<GLvoid>(),
+            );
+        }
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.indices
+    }
+
+    /// Returns model space bounding box of mesh's vertices, used for frustum culling
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+}
+
+impl Drop for Mesh {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo_id);
+            gl::DeleteBuffers(1, &self.ebo_id);
+            gl::DeleteVertexArrays(1, &self.vao_id);
+        }
+    }
+}
+
+impl TryFrom<&Cube> for Mesh {
+    type Error = crate::GlError;
+
+    fn try_from(value: &Cube) -> GlResult<Self> {
+        Mesh::new(
+            &value.verts,
+            &value.normals,
+            &value.texture_coordinates,
+            &value.indices,
+        )
+    }
+}
+
+impl TryFrom<&Plane> for Mesh {
+    type Error = crate::GlError;
+
+    fn try_from(value: &Plane) -> GlResult<Self> {
+        Mesh::new(
+            &value.verts,
+            &value.normals,
+            &value.texture_coordinates,
+            &value.indices,
+        )
+    }
+}
+
+impl TryFrom<&Sphere> for Mesh {
+    type Error = crate::GlError;
+
+    fn try_from(value: &Sphere) -> GlResult<Self> {
+        Mesh::new(&value.verts, &value.normals, &[], &value.indices)
+    }
+}
@@ -1,8 +1,10 @@
 /// This module provides VBOs for primitive 3D shapes
+pub mod bounds;
 pub mod cube;
 pub mod sphere;
 pub mod plane;
 
+pub use bounds::Bounds;
 pub use cube::Cube;
 pub use plane::Plane;
 pub use sphere::Sphere;
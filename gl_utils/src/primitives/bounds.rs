@@ -0,0 +1,42 @@
+/// Axis aligned box enclosing all vertices of a model, in model space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl Bounds {
+    /// Returns box enclosing given points, empty slice gives a box at origin
+    pub fn from_points(points: &[glm::Vec3]) -> Self {
+        let Some(first) = points.first() else {
+            return Bounds {
+                min: glm::Vec3::zeros(),
+                max: glm::Vec3::zeros(),
+            };
+        };
+        points.iter().fold(
+            Bounds {
+                min: *first,
+                max: *first,
+            },
+            |b, p| Bounds {
+                min: glm::min2(&b.min, p),
+                max: glm::max2(&b.max, p),
+            },
+        )
+    }
+
+    pub fn center(&self) -> glm::Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Returns half of the box size
+    pub fn extents(&self) -> glm::Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    /// Returns radius of a sphere placed at center() enclosing the box
+    pub fn radius(&self) -> f32 {
+        self.extents().magnitude()
+    }
+}
@@ -1,3 +1,4 @@
+use super::Bounds;
 use std::iter;
 
 pub struct Cube {
@@ -14,6 +15,11 @@ impl Default for Cube {
 }
 
 impl Cube {
+    /// Returns model space bounding box of the shape
+    pub fn bounds(&self) -> Bounds {
+        Bounds::from_points(&self.verts)
+    }
+
     pub fn new() -> Self {
         let mut verts = vec![
             // #1
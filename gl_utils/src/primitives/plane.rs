@@ -1,3 +1,4 @@
+use super::Bounds;
 use std::iter::{self, repeat};
 
 pub struct Plane {
@@ -8,6 +9,11 @@ pub struct Plane {
 }
 
 impl Plane {
+    /// Returns model space bounding box of the shape
+    pub fn bounds(&self) -> Bounds {
+        Bounds::from_points(&self.verts)
+    }
+
     
     fn get_idx(row: usize, column: usize, size: usize) -> u32 {
         (column + size * row) as u32
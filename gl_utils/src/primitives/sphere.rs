@@ -1,3 +1,4 @@
+use super::Bounds;
 use std::iter::{self, repeat};
 
 /// Helping structure
@@ -55,6 +56,11 @@ pub struct Sphere {
 }
 
 impl Sphere {
+    /// Returns model space bounding box of the shape
+    pub fn bounds(&self) -> Bounds {
+        Bounds::from_points(&self.verts)
+    }
+
     pub fn new(segments: usize, rings: usize) -> Self {
         let s = SolidOfRevolution::new(2.0, segments, rings + 2, true, |x| (1.0f32 - x.powi(2)).sqrt());
         Self {
@@ -73,6 +79,11 @@ pub struct SolidOfRevolution {
 } 
 
 impl SolidOfRevolution {
+    /// Returns model space bounding box of the shape
+    pub fn bounds(&self) -> Bounds {
+        Bounds::from_points(&self.verts)
+    }
+
 
     // TODO: Return GameResult<Self> instead of 'fixing' errors
     pub fn new<T>(height: f32, mut segments: usize, mut rings: usize, closed: bool, mut f: T) -> Self
@@ -0,0 +1,49 @@
+/// Provides vertex shader for GPU skinning of meshes created with Mesh::new_skinned
+/// Each vertex is moved by up to 4 joint matrices (joint's current global transform
+/// multiplied by its inverse bind matrix) weighted by its joint weights.
+use crate::compiled_program::CompiledProgram;
+use crate::error::GlResult;
+
+/// Maximal number of joints a skinned mesh drawn with SKINNING_VERTEX_SHADER can use
+pub const MAX_JOINTS: usize = 64;
+
+/// Name of mat4 array uniform holding joint matrices
+pub const JOINTS_UNIFORM: &str = "joints";
+
+/// Skinning vertex shader (GLSL 3.30, requires OpenGL 3.3), uses uniforms `mat4 projection`,
+/// `mat4 model`, `mat4 joints[MAX_JOINTS]` and outputs `frag_pos`, `frag_normal` and `tex_coords`
+pub const SKINNING_VERTEX_SHADER: &str = r#"#version 330
+layout(location = 0) in vec3 vert;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+layout(location = 3) in vec4 joint_ids;
+layout(location = 4) in vec4 joint_weights;
+
+const int MAX_JOINTS = 64;
+
+uniform mat4 model;
+uniform mat4 projection;
+uniform mat4 joints[MAX_JOINTS];
+
+out vec3 frag_pos;
+out vec3 frag_normal;
+out vec2 tex_coords;
+
+void main(void) {
+    mat4 skin = joint_weights.x * joints[int(joint_ids.x)]
+        + joint_weights.y * joints[int(joint_ids.y)]
+        + joint_weights.z * joints[int(joint_ids.z)]
+        + joint_weights.w * joints[int(joint_ids.w)];
+    mat4 skinned_model = model * skin;
+    vec4 world_pos = skinned_model * vec4(vert, 1.0);
+    gl_Position = projection * world_pos;
+    frag_pos = vec3(world_pos);
+    frag_normal = mat3(transpose(inverse(skinned_model))) * normal;
+    tex_coords = uv;
+}
+"#;
+
+/// Compiles SKINNING_VERTEX_SHADER with given fragment shader source
+pub fn skinning_program(fs_source: &str) -> GlResult<CompiledProgram> {
+    CompiledProgram::from_sources(SKINNING_VERTEX_SHADER, fs_source)
+}
@@ -0,0 +1,199 @@
+//! Provides TextRenderer which draws monospace bitmap font text and solid rectangles
+//! on top of the screen (useful for debug overlays, consoles, simple UI)
+//! Its shaders require OpenGL 3.3 or newer
+use crate::compiled_program::CompiledProgram;
+use crate::error::GlResult;
+use crate::texture::Texture;
+use gl::types::*;
+
+const VERT_SHADER: &str = r#"
+#version 330 core
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec2 uv;
+
+uniform vec2 screen;
+
+out vec2 tex_coords;
+
+void main() {
+    vec2 ndc = position / screen * 2.0 - 1.0;
+    gl_Position = vec4(ndc.x, -ndc.y, 0.0, 1.0);
+    tex_coords = uv;
+}
+"#;
+
+const FRAG_SHADER: &str = r#"
+#version 330 core
+in vec2 tex_coords;
+
+uniform sampler2D atlas;
+uniform vec4 color;
+uniform bool textured;
+
+out vec4 frag_color;
+
+void main() {
+    float alpha = 1.0;
+    if (textured) {
+        vec4 texel = texture(atlas, tex_coords);
+        alpha = texel.a * max(max(texel.r, texel.g), texel.b);
+    }
+    frag_color = vec4(color.rgb, color.a * alpha);
+}
+"#;
+
+pub struct TextRenderer {
+    program: CompiledProgram,
+    atlas: Texture,
+    columns: u32,
+    rows: u32,
+    first_char: u32,
+    /// glyph width / glyph height
+    glyph_aspect: f32,
+}
+
+impl TextRenderer {
+    /// Creates renderer using font atlas image: a grid of columns x rows equally sized glyphs
+    /// in ASCII order, starting with first_char (usually ' ')
+    pub fn new(atlas_path: &str, columns: u32, rows: u32, first_char: char) -> GlResult<Self> {
+        let program = CompiledProgram::from_sources(VERT_SHADER, FRAG_SHADER)?;
+        let atlas = Texture::try_from(atlas_path)?;
+        let (w, h) = atlas.size();
+        program.bind_buffers();
+        unsafe {
+            let stride = (4 * std::mem::size_of::<f32>()) as GLint;
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(
+                1,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (2 * std::mem::size_of::<f32>()) as *const GLvoid,
+            );
+        }
+        Ok(Self {
+            program,
+            atlas,
+            columns,
+            rows,
+            first_char: first_char as u32,
+            glyph_aspect: (w as f32 / columns as f32) / (h as f32 / rows as f32),
+        })
+    }
+
+    /// Returns width in pixels of the longest line of text drawn with given glyph height
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let longest = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        longest as f32 * size * self.glyph_aspect
+    }
+
+    /// Draws text with its top left corner at pos (in pixels, origin in the top left corner
+    /// of the screen), size is the glyph height in pixels. Characters missing in atlas are skipped.
+    pub fn draw_text(
+        &self,
+        text: &str,
+        pos: (f32, f32),
+        size: f32,
+        color: &glm::Vec4,
+        screen: (f32, f32),
+    ) {
+        let glyph_w = size * self.glyph_aspect;
+        let glyph_count = self.columns * self.rows;
+        let mut verts: Vec<f32> = Vec::with_capacity(text.len() * 24);
+        for (line_idx, line) in text.lines().enumerate() {
+            let y = pos.1 + line_idx as f32 * size;
+            for (i, c) in line.chars().enumerate() {
+                let idx = match (c as u32).checked_sub(self.first_char) {
+                    Some(idx) if idx < glyph_count => idx,
+                    _ => continue,
+                };
+                let x = pos.0 + i as f32 * glyph_w;
+                let u = (idx % self.columns) as f32 / self.columns as f32;
+                let v = (idx / self.columns) as f32 / self.rows as f32;
+                let (du, dv) = (1.0 / self.columns as f32, 1.0 / self.rows as f32);
+                Self::push_quad(&mut verts, (x, y, glyph_w, size), (u, v, du, dv));
+            }
+        }
+        self.draw(&verts, true, color, screen);
+    }
+
+    /// Draws a solid rectangle with its top left corner at pos (in pixels)
+    pub fn draw_rect(
+        &self,
+        pos: (f32, f32),
+        dim: (f32, f32),
+        color: &glm::Vec4,
+        screen: (f32, f32),
+    ) {
+        let mut verts = Vec::with_capacity(24);
+        Self::push_quad(
+            &mut verts,
+            (pos.0, pos.1, dim.0, dim.1),
+            (0.0, 0.0, 0.0, 0.0),
+        );
+        self.draw(&verts, false, color, screen);
+    }
+
+    #[rustfmt::skip]
+    fn push_quad(verts: &mut Vec<f32>, rect: (f32, f32, f32, f32), uv: (f32, f32, f32, f32)) {
+        let (x, y, w, h) = rect;
+        let (u, v, du, dv) = uv;
+        verts.extend_from_slice(&[
+            x, y, u, v,
+            x, y + h, u, v + dv,
+            x + w, y + h, u + du, v + dv,
+            x, y, u, v,
+            x + w, y + h, u + du, v + dv,
+            x + w, y, u + du, v,
+        ]);
+    }
+
+    fn draw(&self, verts: &[f32], textured: bool, color: &glm::Vec4, screen: (f32, f32)) {
+        if verts.is_empty() {
+            return;
+        }
+        self.program.bind_program();
+        self.program.bind_buffers();
+        self.atlas.bind_texture();
+        unsafe {
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            let blend = gl::IsEnabled(gl::BLEND) == gl::TRUE;
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(verts) as GLsizeiptr,
+                verts.as_ptr() as *const GLvoid,
+                gl::DYNAMIC_DRAW,
+            );
+            gl::Uniform2f(
+                self.program.get_uniform_location("screen"),
+                screen.0,
+                screen.1,
+            );
+            gl::Uniform4f(
+                self.program.get_uniform_location("color"),
+                color.x,
+                color.y,
+                color.z,
+                color.w,
+            );
+            gl::Uniform1i(
+                self.program.get_uniform_location("textured"),
+                textured as GLint,
+            );
+            gl::Uniform1i(self.program.get_uniform_location("atlas"), 0);
+            gl::DrawArrays(gl::TRIANGLES, 0, (verts.len() / 4) as GLsizei);
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if !blend {
+                gl::Disable(gl::BLEND);
+            }
+        }
+    }
+}
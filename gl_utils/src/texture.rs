@@ -1,3 +1,4 @@
+use crate::error::{check_gl_error, clear_gl_errors, GlError, GlResult};
 use gl::types::*;
 use image::io::Reader as ImageReader;
 use image::{EncodableLayout};
@@ -7,17 +8,21 @@ pub struct Texture {
 }
 
 impl Texture {
-    pub fn load_image(&self, img_src: &str) {
-        let img = ImageReader::open(img_src)
-            .unwrap_or_else(|_| panic!("Couldn't load '{}' texture file!", img_src));
-        let img = img
-            .decode()
-            .unwrap_or_else(|_| panic!("Couldn't decode '{}' texture file!", img_src));
+    pub fn load_image(&self, img_src: &str) -> GlResult {
+        let img = ImageReader::open(img_src).map_err(|source| GlError::Io {
+            path: img_src.into(),
+            source,
+        })?;
+        let img = img.decode().map_err(|source| GlError::Image {
+            path: img_src.into(),
+            source,
+        })?;
         //let img = img.into_rgba8();
         let img = img.into_rgba8();
         let (w, h) = (img.width() as i32, img.height() as i32);
         let img: Vec<u8> = Vec::from(img.as_bytes());
 
+        clear_gl_errors();
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
             gl::TexImage2D(
@@ -33,6 +38,19 @@ impl Texture {
             );
             gl::GenerateMipmap(gl::TEXTURE_2D);
         }
+        check_gl_error()
+    }
+
+    /// Returns (width, height) of the loaded image
+    pub fn size(&self) -> (u32, u32) {
+        let mut width: GLint = 0;
+        let mut height: GLint = 0;
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_WIDTH, &mut width);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_HEIGHT, &mut height);
+        }
+        (width as u32, height as u32)
     }
 
     pub fn bind_texture(&self) {
@@ -40,6 +58,14 @@ impl Texture {
             gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
         }
     }
+
+    /// Binds texture to given texture unit (GL_TEXTURE0 + unit)
+    pub fn bind_texture_unit(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
+        }
+    }
 }
 
 impl Default for Texture {
@@ -69,10 +95,12 @@ impl Default for Texture {
     }
 }
 
-impl From<&str> for Texture {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for Texture {
+    type Error = GlError;
+
+    fn try_from(value: &str) -> GlResult<Self> {
         let t = Texture::default();
-        t.load_image(value);
-        t
+        t.load_image(value)?;
+        Ok(t)
     }
 }
@@ -0,0 +1,515 @@
+//! Ready-made gameobjects moving a Camera: free-fly, orbit, third-person follow and FPS look.
+//! Each controller owns its camera, keeps its projection in sync with the window
+//! and adds it to ctx.renderer every update, so adding one to a scene is enough
+//! to get a working view. Rotation is stored as yaw (around world y axis) and pitch
+//! (around local x axis) in radians, camera looks along its local -z axis.
+
+use crate::{
+    components::{camera::Camera, transform::Transform},
+    context::Context,
+    error::GameResult,
+    gameobject::{GameObject, GameObjectId},
+    input::{keyboard::KeyBoard, mouse::Mouse, Input},
+    scene::Scene,
+    KeyCode, MouseButton,
+};
+use std::any::Any;
+use std::f32::consts::FRAC_PI_2;
+
+/// Pitch limit keeping camera from flipping over when looking straight up or down
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Returns rotation of a camera with given yaw and pitch
+pub fn yaw_pitch_rotation(yaw: f32, pitch: f32) -> glm::Quat {
+    glm::quat_angle_axis(yaw, &glm::Vec3::y()) * glm::quat_angle_axis(pitch, &glm::Vec3::x())
+}
+
+/// Returns (yaw, pitch) of a camera looking in given direction
+pub fn look_yaw_pitch(direction: &glm::Vec3) -> (f32, f32) {
+    let d = direction.normalize();
+    (f32::atan2(-d.x, -d.z), d.y.clamp(-1.0, 1.0).asin())
+}
+
+/// Returns interpolation factor moving value towards its target in delta_time,
+/// damping is roughly time in seconds needed to catch up, 0 means no smoothing
+pub fn damping_factor(damping: f32, delta_time: f32) -> f32 {
+    if damping <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-delta_time / damping).exp()
+    }
+}
+
+fn key_held(kb: &KeyBoard, key: KeyCode) -> bool {
+    kb.get_key(key) || kb.get_key_down(key)
+}
+
+fn button_held(mouse: &Mouse, button: Option<MouseButton>) -> bool {
+    button.is_none_or(|b| mouse.get_key(b) || mouse.get_key_down(b))
+}
+
+/// Returns movement direction in camera's local space (x right, y up, z backwards)
+/// from WASD keys and E/Q for up/down, not normalized
+fn movement_input(kb: &KeyBoard) -> glm::Vec3 {
+    let axis = |positive: KeyCode, negative: KeyCode| {
+        key_held(kb, positive) as i32 as f32 - key_held(kb, negative) as i32 as f32
+    };
+    glm::vec3(
+        axis(KeyCode::KeyD, KeyCode::KeyA),
+        axis(KeyCode::KeyE, KeyCode::KeyQ),
+        axis(KeyCode::KeyS, KeyCode::KeyW),
+    )
+}
+
+/// Updates camera's projection and submits it to the renderer
+fn present(camera: &mut Camera, ctx: &Context, scene: &Scene) {
+    camera.update_viewport_projection(ctx.window.width() as f32, ctx.window.height() as f32);
+    scene.set_view_position(*camera.transform.position());
+    ctx.renderer.add_camera(camera);
+}
+
+/// Yaw and pitch changed by mouse movement
+#[derive(Debug, Clone, Copy)]
+struct Look {
+    yaw: f32,
+    pitch: f32,
+}
+
+impl Look {
+    fn from_camera(camera: &Camera) -> Self {
+        let (yaw, pitch) = look_yaw_pitch(&camera.front());
+        Look { yaw, pitch }
+    }
+
+    fn apply(&mut self, mouse: &Mouse, sensitivity: f32, pitch_limit: f32) {
+        self.yaw -= mouse.position_delta.0 * sensitivity;
+        self.pitch -= mouse.position_delta.1 * sensitivity;
+        self.pitch = self.pitch.clamp(-pitch_limit, pitch_limit);
+    }
+
+    fn rotation(&self) -> glm::Quat {
+        yaw_pitch_rotation(self.yaw, self.pitch)
+    }
+}
+
+/// Free-fly camera: WASD moves along camera axes, E/Q up and down, left shift speeds up,
+/// mouse looks around while look_button is held
+pub struct FlyCamera {
+    pub camera: Camera,
+    /// units per second
+    pub speed: f32,
+    /// speed multiplier used while left shift is held
+    pub fast_multiplier: f32,
+    /// radians per pixel of mouse movement
+    pub sensitivity: f32,
+    /// None means mouse always rotates the camera
+    pub look_button: Option<MouseButton>,
+    look: Look,
+    name: String,
+}
+
+impl FlyCamera {
+    pub fn new(name: &str, camera: Camera) -> Self {
+        FlyCamera {
+            look: Look::from_camera(&camera),
+            camera,
+            speed: 5.0,
+            fast_multiplier: 3.0,
+            sensitivity: 0.003,
+            look_button: Some(MouseButton::Right),
+            name: name.into(),
+        }
+    }
+
+    fn apply_input(&mut self, input: &Input, delta_time: f32) {
+        if button_held(&input.mouse, self.look_button) {
+            self.look.apply(&input.mouse, self.sensitivity, MAX_PITCH);
+        }
+        *self.camera.transform.rotation_mut() = self.look.rotation();
+        let direction = movement_input(&input.kb);
+        if direction == glm::Vec3::zeros() {
+            return;
+        }
+        let mut speed = self.speed;
+        if key_held(&input.kb, KeyCode::KeyLeftShift) {
+            speed *= self.fast_multiplier;
+        }
+        let step = self
+            .camera
+            .transform
+            .vector_to_world(&direction.normalize());
+        *self.camera.transform.position_mut() += step * speed * delta_time;
+    }
+}
+
+/// FPS camera: mouse always looks around with pitch clamped to pitch_limit,
+/// WASD walks on horizontal plane so looking up or down doesn't change height
+pub struct FpsCamera {
+    pub camera: Camera,
+    /// units per second, 0 disables walking (e.g. when camera is attached to a character)
+    pub speed: f32,
+    /// radians per pixel of mouse movement
+    pub sensitivity: f32,
+    /// maximal angle in radians camera can look up or down
+    pub pitch_limit: f32,
+    /// hide cursor when the camera starts
+    pub hide_cursor: bool,
+    look: Look,
+    name: String,
+}
+
+impl FpsCamera {
+    pub fn new(name: &str, camera: Camera) -> Self {
+        FpsCamera {
+            look: Look::from_camera(&camera),
+            camera,
+            speed: 4.0,
+            sensitivity: 0.003,
+            pitch_limit: f32::to_radians(85.0),
+            hide_cursor: true,
+            name: name.into(),
+        }
+    }
+
+    fn apply_input(&mut self, input: &Input, delta_time: f32) {
+        self.look.apply(
+            &input.mouse,
+            self.sensitivity,
+            self.pitch_limit.min(MAX_PITCH),
+        );
+        *self.camera.transform.rotation_mut() = self.look.rotation();
+        let mut direction = movement_input(&input.kb);
+        direction.y = 0.0;
+        if direction == glm::Vec3::zeros() {
+            return;
+        }
+        let heading = glm::quat_angle_axis(self.look.yaw, &glm::Vec3::y());
+        let step = glm::quat_rotate_vec3(&heading, &direction.normalize());
+        *self.camera.transform.position_mut() += step * self.speed * delta_time;
+    }
+}
+
+/// Camera orbiting around target point, dragging with rotate_button rotates it
+/// and scrolling zooms in and out
+pub struct OrbitCamera {
+    pub camera: Camera,
+    pub target: glm::Vec3,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// fraction of distance changed by one scroll step
+    pub zoom_speed: f32,
+    /// radians per pixel of mouse movement
+    pub sensitivity: f32,
+    /// None means mouse always rotates the camera
+    pub rotate_button: Option<MouseButton>,
+    look: Look,
+    name: String,
+}
+
+impl OrbitCamera {
+    pub fn new(name: &str, camera: Camera, target: glm::Vec3) -> Self {
+        let offset = camera.transform.position() - target;
+        let distance = offset.magnitude().max(0.1);
+        let (yaw, pitch) = look_yaw_pitch(&-offset);
+        let mut orbit = OrbitCamera {
+            camera,
+            target,
+            distance,
+            min_distance: 0.5,
+            max_distance: 100.0,
+            zoom_speed: 0.1,
+            sensitivity: 0.005,
+            rotate_button: Some(MouseButton::Left),
+            look: Look { yaw, pitch },
+            name: name.into(),
+        };
+        orbit.update_transform();
+        orbit
+    }
+
+    /// Sets camera rotation, yaw 0 and pitch 0 place camera on +z side of target
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        self.look = Look {
+            yaw,
+            pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+        };
+        self.update_transform();
+    }
+
+    fn update_transform(&mut self) {
+        let rotation = self.look.rotation();
+        let offset = glm::quat_rotate_vec3(&rotation, &glm::vec3(0.0, 0.0, self.distance));
+        *self.camera.transform.position_mut() = self.target + offset;
+        *self.camera.transform.rotation_mut() = rotation;
+    }
+
+    fn apply_input(&mut self, input: &Input) {
+        if button_held(&input.mouse, self.rotate_button) {
+            self.look.apply(&input.mouse, self.sensitivity, MAX_PITCH);
+        }
+        let scroll = input.mouse.scroll_delta.1;
+        self.distance = (self.distance * (1.0 - self.zoom_speed).powf(scroll))
+            .clamp(self.min_distance, self.max_distance);
+        self.update_transform();
+    }
+}
+
+/// Third-person camera following gameobject with given name on a spring arm
+/// attached to target's pivot. Target has to expose its transform (see GameObject::transform),
+/// add this camera to a later layer than the target so it sees the target's final position.
+/// The arm doesn't collide with anything.
+pub struct FollowCamera {
+    pub camera: Camera,
+    pub target_name: String,
+    /// offset from target's position the arm is attached to, in target's local space
+    pub pivot_offset: glm::Vec3,
+    pub arm_length: f32,
+    /// angle in radians the arm is raised above target's back
+    pub arm_pitch: f32,
+    /// rotate the arm together with target so camera stays behind it
+    pub follow_rotation: bool,
+    /// time in seconds camera needs to catch up with target, 0 snaps immediately
+    pub position_damping: f32,
+    pub rotation_damping: f32,
+    target: Option<GameObjectId>,
+    name: String,
+}
+
+impl FollowCamera {
+    pub fn new(name: &str, camera: Camera, target_name: &str) -> Self {
+        FollowCamera {
+            camera,
+            target_name: target_name.into(),
+            pivot_offset: glm::vec3(0.0, 1.0, 0.0),
+            arm_length: 5.0,
+            arm_pitch: f32::to_radians(20.0),
+            follow_rotation: true,
+            position_damping: 0.2,
+            rotation_damping: 0.1,
+            target: None,
+            name: name.into(),
+        }
+    }
+
+    /// Returns pivot point and camera position the arm holds for target with given transform
+    fn arm(&self, target: &Transform) -> (glm::Vec3, glm::Vec3) {
+        let rotation = match self.follow_rotation {
+            true => {
+                let (yaw, _) = look_yaw_pitch(&target.vector_to_world(&-glm::Vec3::z()));
+                glm::quat_angle_axis(yaw, &glm::Vec3::y())
+            }
+            false => glm::quat_identity(),
+        };
+        let pivot = target.position() + target.vector_to_world(&self.pivot_offset);
+        let arm = rotation * glm::quat_angle_axis(-self.arm_pitch, &glm::Vec3::x());
+        let position = pivot + glm::quat_rotate_vec3(&arm, &glm::vec3(0.0, 0.0, self.arm_length));
+        (pivot, position)
+    }
+
+    fn follow(&mut self, target: &Transform, delta_time: f32) {
+        let (pivot, desired) = self.arm(target);
+        let t = damping_factor(self.position_damping, delta_time);
+        let position = glm::lerp(self.camera.transform.position(), &desired, t);
+        *self.camera.transform.position_mut() = position;
+        if pivot == position {
+            return;
+        }
+        let (yaw, pitch) = look_yaw_pitch(&(pivot - position));
+        let desired = yaw_pitch_rotation(yaw, pitch);
+        let t = damping_factor(self.rotation_damping, delta_time);
+        *self.camera.transform.rotation_mut() =
+            glm::quat_slerp(&self.camera.transform.rotation, &desired, t);
+    }
+}
+
+impl GameObject for FlyCamera {
+    fn update(&mut self, ctx: &Context, scene: &Scene) -> GameResult {
+        self.apply_input(&ctx.input, ctx.time.delta_time() as f32);
+        present(&mut self.camera, ctx, scene);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.camera.transform)
+    }
+
+    fn transform_mut(&mut self) -> Option<&mut Transform> {
+        Some(&mut self.camera.transform)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl GameObject for FpsCamera {
+    fn start(&mut self, ctx: &Context, _scene: &Scene) -> GameResult {
+        if self.hide_cursor {
+            ctx.input.mouse.set_cursor_visibility(false);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &Context, scene: &Scene) -> GameResult {
+        self.apply_input(&ctx.input, ctx.time.delta_time() as f32);
+        present(&mut self.camera, ctx, scene);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.camera.transform)
+    }
+
+    fn transform_mut(&mut self) -> Option<&mut Transform> {
+        Some(&mut self.camera.transform)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl GameObject for OrbitCamera {
+    fn update(&mut self, ctx: &Context, scene: &Scene) -> GameResult {
+        self.apply_input(&ctx.input);
+        present(&mut self.camera, ctx, scene);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.camera.transform)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl GameObject for FollowCamera {
+    fn update(&mut self, ctx: &Context, scene: &Scene) -> GameResult {
+        if self.target.is_none_or(|id| scene.transform(&id).is_none()) {
+            self.target = scene.get_gameobject_id(&self.target_name);
+        }
+        if let Some(target) = self.target.and_then(|id| scene.transform(&id)) {
+            self.follow(target, ctx.time.delta_time() as f32);
+        }
+        present(&mut self.camera, ctx, scene);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.camera.transform)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::camera::ProjectionType;
+    use crate::test_utils::assert_close;
+
+    fn camera() -> Camera {
+        Camera::new(
+            ProjectionType::Perspective { fov: 60.0 },
+            0.1,
+            100.0,
+            800.0,
+            600.0,
+        )
+    }
+
+    #[test]
+    fn yaw_and_pitch_round_trip() {
+        let direction = glm::vec3(1.0, 0.5, -2.0).normalize();
+        let (yaw, pitch) = look_yaw_pitch(&direction);
+        let front = glm::quat_rotate_vec3(&yaw_pitch_rotation(yaw, pitch), &-glm::Vec3::z());
+        assert_close(&front, &direction);
+    }
+
+    #[test]
+    fn fly_camera_moves_forward_and_clamps_pitch() {
+        let mut fly = FlyCamera::new("fly", camera());
+        fly.look_button = None;
+        let mut input = Input::new();
+        input.kb.press_key(KeyCode::KeyW);
+        input.mouse.position_delta = (0.0, -10000.0);
+        fly.apply_input(&input, 1.0);
+        assert!(fly.look.pitch <= MAX_PITCH);
+        assert!(fly.camera.transform.position().y > 4.9);
+    }
+
+    #[test]
+    fn fps_camera_walks_on_plane() {
+        let mut fps = FpsCamera::new("fps", camera());
+        let mut input = Input::new();
+        input.kb.press_key(KeyCode::KeyW);
+        input.mouse.position_delta = (0.0, -200.0);
+        fps.apply_input(&input, 1.0);
+        assert!(fps.look.pitch > 0.0);
+        assert_close(fps.camera.transform.position(), &glm::vec3(0.0, 0.0, -4.0));
+    }
+
+    #[test]
+    fn orbit_camera_zooms_and_looks_at_target() {
+        let target = glm::vec3(1.0, 0.0, 0.0);
+        let mut c = camera();
+        *c.transform.position_mut() = glm::vec3(1.0, 0.0, 10.0);
+        let mut orbit = OrbitCamera::new("orbit", c, target);
+        let mut input = Input::new();
+        input.mouse.scroll_delta = (0.0, 1.0);
+        orbit.apply_input(&input);
+        assert!((orbit.distance - 9.0).abs() < 1e-4);
+        orbit.set_angles(FRAC_PI_2, 0.0);
+        assert_close(
+            orbit.camera.transform.position(),
+            &glm::vec3(10.0, 0.0, 0.0),
+        );
+        assert_close(&orbit.camera.front(), &-glm::Vec3::x());
+    }
+
+    #[test]
+    fn follow_camera_stays_behind_target() {
+        let mut follow = FollowCamera::new("follow", camera(), "player");
+        follow.position_damping = 0.0;
+        follow.rotation_damping = 0.0;
+        follow.arm_pitch = 0.0;
+        let mut target = Transform::default();
+        target.rotate(
+            glm::Vec3::y(),
+            FRAC_PI_2,
+            crate::components::transform::Space::World,
+        );
+        follow.follow(&target, 0.016);
+        assert_close(
+            follow.camera.transform.position(),
+            &glm::vec3(5.0, 1.0, 0.0),
+        );
+        assert_close(&follow.camera.front(), &-glm::Vec3::x());
+        follow.position_damping = 1.0;
+        *target.position_mut() = glm::vec3(0.0, 0.0, 10.0);
+        follow.follow(&target, 0.1);
+        let z = follow.camera.transform.position().z;
+        assert!(z > 0.0 && z < 10.0);
+    }
+}
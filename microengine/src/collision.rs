@@ -0,0 +1,481 @@
+//! Colliders attached to gameobjects, collision events and scene queries.
+//! Gameobjects take part in collisions by returning a collider from GameObject::collider,
+//! scene detects collisions before each fixed_update and reports them with
+//! on_collision_* and on_trigger_* methods. Colliders of gameobjects without
+//! a transform are placed at the origin.
+
+pub mod broadphase;
+pub mod narrowphase;
+pub mod shapes;
+
+use crate::{
+    collision2d::{self, Body2D},
+    error::{GameError, GameResult},
+    gameobject::GameObjectId,
+    geometry::Ray,
+};
+use broadphase::UniformGrid;
+use shapes::WorldShape;
+use std::{collections::HashMap, rc::Rc};
+
+/// Mask matching colliders on every layer
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+/// Default size of broadphase grid cell, see scene.set_collision_cell_size
+pub const DEFAULT_CELL_SIZE: f32 = 4.0;
+
+/// Triangle soup used by mesh colliders, vertices are in gameobject's local space
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleMesh {
+    vertices: Vec<glm::Vec3>,
+    indices: Vec<u32>,
+}
+
+impl TriangleMesh {
+    /// Every three indices form a triangle, like in gl_utils primitives
+    pub fn new(vertices: Vec<glm::Vec3>, indices: Vec<u32>) -> GameResult<Self> {
+        if !indices.len().is_multiple_of(3) {
+            return Err(GameError::GameLogicError(format!(
+                "Mesh collider index count {} is not a multiple of 3",
+                indices.len()
+            )));
+        }
+        if let Some(i) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+            return Err(GameError::GameLogicError(format!(
+                "Mesh collider index {i} is out of bounds ({} vertices)",
+                vertices.len()
+            )));
+        }
+        Ok(TriangleMesh { vertices, indices })
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [glm::Vec3; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| {
+            [
+                self.vertices[t[0] as usize],
+                self.vertices[t[1] as usize],
+                self.vertices[t[2] as usize],
+            ]
+        })
+    }
+}
+
+/// Shape of a collider in gameobject's local space, scaled by its transform
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Sphere {
+        radius: f32,
+    },
+    /// box which ignores gameobject's rotation
+    Aabb {
+        half_extents: glm::Vec3,
+    },
+    /// box rotated with the gameobject
+    Obb {
+        half_extents: glm::Vec3,
+    },
+    /// capsule along gameobject's up axis, half_height doesn't include the rounded caps
+    Capsule {
+        radius: f32,
+        half_height: f32,
+    },
+    /// mesh colliders are hollow surfaces and don't collide with other meshes
+    Mesh(Rc<TriangleMesh>),
+}
+
+/// Collider returned by GameObject::collider
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collider {
+    pub shape: Shape,
+    /// position of shape's center in gameobject's local space
+    pub offset: glm::Vec3,
+    /// triggers report overlaps with on_trigger_* methods and are ignored by scene queries
+    pub is_trigger: bool,
+    /// collision layer (0-31) of the collider
+    pub layer: u8,
+    /// bitmask of layers this collider collides with,
+    /// both colliders have to include each other's layer
+    pub mask: u32,
+}
+
+impl Collider {
+    pub fn new(shape: Shape) -> Self {
+        Collider {
+            shape,
+            offset: glm::Vec3::zeros(),
+            is_trigger: false,
+            layer: 0,
+            mask: ALL_LAYERS,
+        }
+    }
+
+    pub fn offset(mut self, offset: glm::Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn trigger(mut self, is_trigger: bool) -> Self {
+        self.is_trigger = is_trigger;
+        self
+    }
+
+    pub fn layer(mut self, layer: u8) -> Self {
+        self.layer = layer.min(31);
+        self
+    }
+
+    pub fn mask(mut self, mask: u32) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Returns bit of collider's layer, as used in masks
+    pub fn layer_bit(&self) -> u32 {
+        1 << self.layer
+    }
+
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.mask & other.layer_bit() != 0 && other.mask & self.layer_bit() != 0
+    }
+}
+
+/// Single point of contact between two colliders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// point in world space halfway between the surfaces
+    pub point: glm::Vec3,
+    /// normalized direction from the first collider to the second
+    pub normal: glm::Vec3,
+    /// penetration depth, moving the second collider by normal * depth separates them
+    pub depth: f32,
+}
+
+impl Contact {
+    /// Returns contact seen from the other collider
+    pub fn flipped(self) -> Self {
+        Contact {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+/// Passed to on_collision_* methods, contact normal points from receiving gameobject
+/// to the other, on_collision_exit gets the last contact of the pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collision {
+    pub other: GameObjectId,
+    pub contact: Contact,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    pub id: GameObjectId,
+    pub point: glm::Vec3,
+    /// surface normal at the hit point
+    pub normal: glm::Vec3,
+    /// distance along the ray
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CollisionEvent {
+    Enter(Collision),
+    Stay(Collision),
+    Exit(Collision),
+    TriggerEnter(GameObjectId),
+    TriggerStay(GameObjectId),
+    TriggerExit(GameObjectId),
+}
+
+/// Collider of a gameobject placed in world
+pub(crate) struct Body {
+    pub id: GameObjectId,
+    pub collider: Collider,
+    pub shape: WorldShape,
+}
+
+/// Pairs are keyed with the lower (layer, id) gameobject first and whether their
+/// colliders are 2D, so a pair touching with both 3D and 2D colliders is tracked twice
+type PairKey = (GameObjectId, GameObjectId, bool);
+
+fn pair_key(a: GameObjectId, b: GameObjectId, planar: bool) -> (PairKey, bool) {
+    match (a.layer, a.id) <= (b.layer, b.id) {
+        true => ((a, b, planar), false),
+        false => ((b, a, planar), true),
+    }
+}
+
+/// Remembers touching pairs between fixed steps, so enter, stay and exit can be told apart
+pub(crate) struct CollisionWorld {
+    grid: UniformGrid,
+    grid_2d: UniformGrid,
+    /// contact of touching pair (normal from first to second) and whether it is a trigger
+    touching: HashMap<PairKey, (Contact, bool)>,
+}
+
+impl CollisionWorld {
+    pub fn new() -> Self {
+        CollisionWorld {
+            grid: UniformGrid::new(DEFAULT_CELL_SIZE),
+            grid_2d: UniformGrid::new(DEFAULT_CELL_SIZE),
+            touching: HashMap::new(),
+        }
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        self.grid = UniformGrid::new(cell_size);
+        self.grid_2d = UniformGrid::new(cell_size);
+    }
+
+    /// Finds touching colliders and returns events for each gameobject of every pair
+    /// which started touching, is still touching or stopped touching since the last step.
+    /// 2D colliders only touch other 2D colliders.
+    pub fn step(
+        &mut self,
+        bodies: &[Body],
+        bodies_2d: &[Body2D],
+    ) -> Vec<(GameObjectId, CollisionEvent)> {
+        let bounds = bodies.iter().map(|b| b.shape.bounds()).collect::<Vec<_>>();
+        let found = self
+            .grid
+            .pairs(&bounds)
+            .into_iter()
+            .filter_map(|(i, j)| {
+                let (a, b) = (&bodies[i], &bodies[j]);
+                if !a.collider.interacts_with(&b.collider) {
+                    return None;
+                }
+                let contact = narrowphase::contact(&a.shape, &b.shape)?;
+                let trigger = a.collider.is_trigger || b.collider.is_trigger;
+                Some((a.id, b.id, contact, trigger, false))
+            })
+            .chain(
+                collision2d::find_contacts(&mut self.grid_2d, bodies_2d)
+                    .into_iter()
+                    .map(|(a, b, contact, trigger)| (a, b, contact, trigger, true)),
+            )
+            .collect::<Vec<_>>();
+        let mut touching = HashMap::new();
+        let mut events = Vec::new();
+        for (a, b, contact, trigger, planar) in found {
+            let (key, swapped) = pair_key(a, b, planar);
+            let contact = if swapped { contact.flipped() } else { contact };
+            let entered = self.touching.remove(&key).is_none();
+            Self::push_events(&mut events, key, contact, trigger, entered.then_some(true));
+            touching.insert(key, (contact, trigger));
+        }
+        // pairs touching in previous step only, sorted so events come in stable order
+        let mut exited = self.touching.drain().collect::<Vec<_>>();
+        exited.sort_by_key(|((a, b, planar), _)| (a.layer, a.id, b.layer, b.id, *planar));
+        for (key, (contact, trigger)) in exited {
+            Self::push_events(&mut events, key, contact, trigger, Some(false));
+        }
+        self.touching = touching;
+        events
+    }
+
+    /// Returns pairs touching in the last step which aren't triggers,
+    /// the contact normal points from the first gameobject to the second
+    pub fn contacts(&self) -> Vec<(GameObjectId, GameObjectId, Contact)> {
+        let mut contacts = self
+            .touching
+            .iter()
+            .filter(|(_, (_, trigger))| !trigger)
+            .collect::<Vec<_>>();
+        contacts.sort_by_key(|((a, b, planar), _)| (a.layer, a.id, b.layer, b.id, *planar));
+        contacts
+            .into_iter()
+            .map(|(&(a, b, _), &(contact, _))| (a, b, contact))
+            .collect()
+    }
+
+    /// entered is Some(true) for enter events, Some(false) for exit and None for stay
+    fn push_events(
+        events: &mut Vec<(GameObjectId, CollisionEvent)>,
+        (a, b, _): PairKey,
+        contact: Contact,
+        trigger: bool,
+        entered: Option<bool>,
+    ) {
+        let event = |other: GameObjectId, contact: Contact| {
+            let collision = Collision { other, contact };
+            match (trigger, entered) {
+                (false, Some(true)) => CollisionEvent::Enter(collision),
+                (false, None) => CollisionEvent::Stay(collision),
+                (false, Some(false)) => CollisionEvent::Exit(collision),
+                (true, Some(true)) => CollisionEvent::TriggerEnter(other),
+                (true, None) => CollisionEvent::TriggerStay(other),
+                (true, Some(false)) => CollisionEvent::TriggerExit(other),
+            }
+        };
+        events.push((a, event(b, contact)));
+        events.push((b, event(a, contact.flipped())));
+    }
+
+    /// Returns hits of a sphere of given radius moving along the ray, sorted by distance.
+    /// Triggers and colliders not on mask's layers are ignored.
+    pub fn cast(
+        bodies: &[Body],
+        ray: &Ray,
+        radius: f32,
+        max_distance: f32,
+        mask: u32,
+    ) -> Vec<RaycastHit> {
+        let mut hits = bodies
+            .iter()
+            .filter(|b| !b.collider.is_trigger && mask & b.collider.layer_bit() != 0)
+            .filter(|b| {
+                b.shape
+                    .bounds()
+                    .expanded(radius)
+                    .raycast(ray)
+                    .is_some_and(|t| t <= max_distance)
+            })
+            .filter_map(|b| {
+                let (distance, normal) = b.shape.cast(ray, radius, max_distance)?;
+                Some(RaycastHit {
+                    id: b.id,
+                    point: ray.point_at(distance) - normal * radius,
+                    normal,
+                    distance,
+                })
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision2d::{tests::body as body_2d, Collider2D, Shape2D};
+
+    fn body(id: usize, x: f32, collider: Collider) -> Body {
+        let shape = WorldShape::new(
+            &collider.shape,
+            &collider.offset,
+            &crate::components::transform::Transform::new(
+                glm::vec3(x, 0.0, 0.0),
+                glm::Vec3::zeros(),
+                glm::vec3(1.0, 1.0, 1.0),
+            ),
+        );
+        Body {
+            id: GameObjectId {
+                layer: 0,
+                id,
+                is_dead: false,
+            },
+            collider,
+            shape,
+        }
+    }
+
+    fn sphere() -> Collider {
+        Collider::new(Shape::Sphere { radius: 1.0 })
+    }
+
+    #[test]
+    fn collision_events_follow_touching_pairs() {
+        let mut world = CollisionWorld::new();
+        let kinds = |events: Vec<(GameObjectId, CollisionEvent)>| {
+            events
+                .into_iter()
+                .map(|(id, e)| match e {
+                    CollisionEvent::Enter(c) => (id.id, "enter", c.other.id, c.contact.normal.x),
+                    CollisionEvent::Stay(c) => (id.id, "stay", c.other.id, c.contact.normal.x),
+                    CollisionEvent::Exit(c) => (id.id, "exit", c.other.id, c.contact.normal.x),
+                    CollisionEvent::TriggerEnter(o) => (id.id, "trigger_enter", o.id, 0.0),
+                    CollisionEvent::TriggerStay(o) => (id.id, "trigger_stay", o.id, 0.0),
+                    CollisionEvent::TriggerExit(o) => (id.id, "trigger_exit", o.id, 0.0),
+                })
+                .collect::<Vec<_>>()
+        };
+        let bodies = [body(1, 0.0, sphere()), body(0, 1.5, sphere())];
+        assert_eq!(
+            kinds(world.step(&bodies, &[])),
+            vec![(0, "enter", 1, -1.0), (1, "enter", 0, 1.0)]
+        );
+        assert_eq!(
+            kinds(world.step(&bodies, &[])),
+            vec![(0, "stay", 1, -1.0), (1, "stay", 0, 1.0)]
+        );
+        let apart = [body(1, 0.0, sphere()), body(0, 3.0, sphere())];
+        assert_eq!(
+            kinds(world.step(&apart, &[])),
+            vec![(0, "exit", 1, -1.0), (1, "exit", 0, 1.0)]
+        );
+        assert!(world.step(&apart, &[]).is_empty());
+
+        let trigger = [body(1, 0.0, sphere()), body(0, 1.5, sphere().trigger(true))];
+        assert_eq!(
+            kinds(world.step(&trigger, &[])),
+            vec![(0, "trigger_enter", 1, 0.0), (1, "trigger_enter", 0, 0.0)]
+        );
+        let masked = [
+            body(1, 0.0, sphere().layer(3)),
+            body(0, 1.5, sphere().mask(!(1 << 3))),
+        ];
+        assert_eq!(
+            kinds(world.step(&masked, &[])),
+            vec![(0, "trigger_exit", 1, 0.0), (1, "trigger_exit", 0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn pairs_touching_in_3d_and_2d_are_tracked_separately() {
+        let mut world = CollisionWorld::new();
+        let bodies = [body(1, 0.0, sphere()), body(0, 1.5, sphere())];
+        let circle = || Collider2D::new(Shape2D::Circle { radius: 1.0 });
+        let bodies_2d = [
+            body_2d(1, 0.0, 0.0, circle()),
+            body_2d(0, 1.5, 0.0, circle()),
+        ];
+        let enters = world
+            .step(&bodies, &bodies_2d)
+            .into_iter()
+            .filter(|(_, e)| matches!(e, CollisionEvent::Enter(_)))
+            .count();
+        assert_eq!(enters, 4);
+        // the 2D pair separating doesn't end the 3D one
+        let events = world.step(&bodies, &[]);
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|(_, e)| matches!(e, CollisionEvent::Stay(_)))
+                .count(),
+            2
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|(_, e)| matches!(e, CollisionEvent::Exit(_)))
+                .count(),
+            2
+        );
+        assert_eq!(world.contacts().len(), 1);
+    }
+
+    #[test]
+    fn casts_skip_triggers_and_masked_layers() {
+        let bodies = [
+            body(0, 5.0, sphere()),
+            body(1, 2.0, sphere().trigger(true)),
+            body(2, 8.0, sphere().layer(2)),
+        ];
+        let ray = Ray::new(glm::Vec3::zeros(), glm::Vec3::x());
+        let hits = CollisionWorld::cast(&bodies, &ray, 0.0, 100.0, ALL_LAYERS);
+        assert_eq!(hits.iter().map(|h| h.id.id).collect::<Vec<_>>(), vec![0, 2]);
+        assert!((hits[0].distance - 4.0).abs() < 1e-4);
+        let hits = CollisionWorld::cast(&bodies, &ray, 0.5, 100.0, 1 << 2);
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].distance - 6.5).abs() < 1e-4);
+        assert!(glm::distance(&hits[0].point, &glm::vec3(7.0, 0.0, 0.0)) < 1e-4);
+        assert!(CollisionWorld::cast(&bodies, &ray, 0.0, 3.0, ALL_LAYERS).is_empty());
+        let mesh = TriangleMesh::new(vec![glm::Vec3::zeros(); 3], vec![0, 1, 3]);
+        assert!(mesh.is_err());
+    }
+}
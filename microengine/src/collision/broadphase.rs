@@ -0,0 +1,109 @@
+//! Uniform grid finding pairs of colliders whose bounds overlap
+
+use crate::geometry::Aabb;
+use std::collections::{HashMap, HashSet};
+
+/// Colliders covering more cells than this are tested against every other collider
+const MAX_CELLS: i64 = 64;
+
+type Cell = (i32, i32, i32);
+
+/// Spatial hash of bounding boxes, cell size should be close to the size of
+/// a typical collider, much smaller cells make big colliders cover many cells
+pub struct UniformGrid {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<usize>>,
+    /// indices of colliders too big to be put into cells
+    large: Vec<usize>,
+}
+
+impl UniformGrid {
+    pub fn new(cell_size: f32) -> Self {
+        UniformGrid {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            large: Vec::new(),
+        }
+    }
+
+    fn cell(&self, p: &glm::Vec3) -> Cell {
+        let c = (p / self.cell_size).map(f32::floor);
+        (c.x as i32, c.y as i32, c.z as i32)
+    }
+
+    /// Returns pairs of indices (lower first, sorted) into bounds which overlap
+    pub fn pairs(&mut self, bounds: &[Aabb]) -> Vec<(usize, usize)> {
+        self.cells.clear();
+        self.large.clear();
+        for (i, aabb) in bounds.iter().enumerate() {
+            let (min, max) = (self.cell(&aabb.min), self.cell(&aabb.max));
+            // huge (or infinite) bounds saturate cell indices, their product could overflow
+            let count = (max.0 as i64 - min.0 as i64 + 1)
+                .saturating_mul(max.1 as i64 - min.1 as i64 + 1)
+                .saturating_mul(max.2 as i64 - min.2 as i64 + 1);
+            if count > MAX_CELLS {
+                self.large.push(i);
+                continue;
+            }
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    for z in min.2..=max.2 {
+                        self.cells.entry((x, y, z)).or_default().push(i);
+                    }
+                }
+            }
+        }
+
+        let mut pairs = HashSet::new();
+        let mut add = |a: usize, b: usize| {
+            if a != b && bounds[a].intersects(&bounds[b]) {
+                pairs.insert((a.min(b), a.max(b)));
+            }
+        };
+        for cell in self.cells.values() {
+            for (n, &a) in cell.iter().enumerate() {
+                for &b in cell[n + 1..].iter() {
+                    add(a, b);
+                }
+            }
+        }
+        for &a in self.large.iter() {
+            for b in 0..bounds.len() {
+                add(a, b);
+            }
+        }
+        let mut pairs = pairs.into_iter().collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_finds_overlapping_pairs_once() {
+        let boxes = [
+            Aabb::from_center(glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.6, 0.6, 0.6)),
+            Aabb::from_center(glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.6, 0.6, 0.6)),
+            Aabb::from_center(glm::vec3(5.0, 0.0, 0.0), glm::vec3(0.6, 0.6, 0.6)),
+            // floor spanning far more cells than MAX_CELLS
+            Aabb::new(glm::vec3(-50.0, -1.0, -50.0), glm::vec3(50.0, -0.5, 50.0)),
+        ];
+        let mut grid = UniformGrid::new(1.0);
+        assert_eq!(grid.pairs(&boxes), vec![(0, 1), (0, 3), (1, 3), (2, 3)]);
+        assert!(grid.pairs(&boxes[..1]).is_empty());
+    }
+
+    #[test]
+    fn grid_handles_unbounded_colliders() {
+        let infinite = glm::vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let boxes = [
+            Aabb::new(-infinite, infinite),
+            Aabb::from_center(glm::vec3(1e7, 0.0, 0.0), glm::vec3(0.6, 0.6, 0.6)),
+        ];
+        let mut grid = UniformGrid::new(1.0);
+        assert_eq!(grid.pairs(&boxes), vec![(0, 1)]);
+    }
+}
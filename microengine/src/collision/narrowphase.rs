@@ -0,0 +1,313 @@
+//! Exact overlap tests of collider pairs generating a single contact per pair
+
+use super::{
+    shapes::{
+        closest_point_triangle, closest_points_segments, deepest_face, direction, OrientedBox,
+        WorldShape,
+    },
+    Contact,
+};
+
+/// Number of ternary search steps finding segment point closest to a convex shape
+const SEARCH_STEPS: usize = 32;
+
+/// Returns contact of two overlapping shapes, normal points from a to b.
+/// Mesh-mesh pairs are not supported and never collide.
+pub fn contact(a: &WorldShape, b: &WorldShape) -> Option<Contact> {
+    match (rounded_segment(a), rounded_segment(b)) {
+        (Some((a0, a1, ra)), Some((b0, b1, rb))) => {
+            let (pa, pb) = closest_points_segments(&a0, &a1, &b0, &b1);
+            return points_contact(&pa, ra, &pb, rb);
+        }
+        (Some((a0, a1, ra)), None) => return segment_contact(&a0, &a1, ra, b),
+        (None, Some((b0, b1, rb))) => {
+            return segment_contact(&b0, &b1, rb, a).map(Contact::flipped)
+        }
+        (None, None) => (),
+    }
+    match (a, b) {
+        (WorldShape::Box(a), WorldShape::Box(b)) => box_contact(a, b),
+        (WorldShape::Box(a), WorldShape::Mesh(triangles)) => {
+            deepest(triangles, |t| box_triangle_contact(a, t))
+        }
+        (WorldShape::Mesh(triangles), WorldShape::Box(b)) => deepest(triangles, |t| {
+            box_triangle_contact(b, t).map(Contact::flipped)
+        }),
+        _ => None,
+    }
+}
+
+/// Spheres and capsules are points or segments extended by radius
+fn rounded_segment(shape: &WorldShape) -> Option<(glm::Vec3, glm::Vec3, f32)> {
+    match shape {
+        WorldShape::Sphere { center, radius } => Some((*center, *center, *radius)),
+        WorldShape::Capsule { a, b, radius } => Some((*a, *b, *radius)),
+        _ => None,
+    }
+}
+
+/// Contact of spheres with given centers and radii
+fn points_contact(pa: &glm::Vec3, ra: f32, pb: &glm::Vec3, rb: f32) -> Option<Contact> {
+    let (distance, normal) = direction(&(pb - pa));
+    let depth = ra + rb - distance;
+    (depth >= 0.0).then(|| Contact {
+        point: pa + normal * (ra - depth / 2.0),
+        normal,
+        depth,
+    })
+}
+
+fn deepest<F>(triangles: &[[glm::Vec3; 3]], f: F) -> Option<Contact>
+where
+    F: Fn(&[glm::Vec3; 3]) -> Option<Contact>,
+{
+    triangles
+        .iter()
+        .filter_map(f)
+        .max_by(|a, b| a.depth.total_cmp(&b.depth))
+}
+
+/// Returns point of segment closest to a convex shape given by its closest point function,
+/// distance to a convex set is convex along a segment so ternary search finds the minimum
+fn closest_segment_point<F>(a: &glm::Vec3, b: &glm::Vec3, closest: F) -> (glm::Vec3, glm::Vec3)
+where
+    F: Fn(&glm::Vec3) -> glm::Vec3,
+{
+    let point = |t: f32| glm::lerp(a, b, t);
+    let distance = |t: f32| {
+        let p = point(t);
+        glm::distance2(&p, &closest(&p))
+    };
+    let (mut lo, mut hi) = (0.0f32, 1.0f32);
+    if a != b {
+        for _ in 0..SEARCH_STEPS {
+            let (m1, m2) = (lo + (hi - lo) / 3.0, hi - (hi - lo) / 3.0);
+            if distance(m1) <= distance(m2) {
+                hi = m2;
+            } else {
+                lo = m1;
+            }
+        }
+    }
+    let p = point((lo + hi) / 2.0);
+    (p, closest(&p))
+}
+
+/// Contact of sphere or capsule (segment a-b extended by radius) with other shape
+fn segment_contact(
+    a: &glm::Vec3,
+    b: &glm::Vec3,
+    radius: f32,
+    other: &WorldShape,
+) -> Option<Contact> {
+    match other {
+        WorldShape::Box(obb) => {
+            let (p, q) = closest_segment_point(a, b, |p| obb.closest_point(p));
+            if p != q {
+                return points_contact(&p, radius, &q, 0.0);
+            }
+            // segment core is inside the box, push it out through the closest face
+            let (axis, depth, sign) = deepest_face(obb, &p);
+            let normal = -obb.axes[axis] * sign;
+            Some(Contact {
+                point: p,
+                normal,
+                depth: depth + radius,
+            })
+        }
+        WorldShape::Mesh(triangles) => deepest(triangles, |tri| {
+            let (p, q) = closest_segment_point(a, b, |p| closest_point_triangle(p, tri));
+            let mut contact = points_contact(&p, radius, &q, 0.0)?;
+            if glm::distance2(&p, &q) < f32::EPSILON {
+                // core touches the triangle, push it to the side of the segment's other end
+                let n = glm::cross(&(tri[1] - tri[0]), &(tri[2] - tri[0])).normalize();
+                let far = if glm::distance2(a, &p) > glm::distance2(b, &p) {
+                    a
+                } else {
+                    b
+                };
+                contact.normal = if glm::dot(&(far - p), &n) > 0.0 {
+                    -n
+                } else {
+                    n
+                };
+            }
+            Some(contact)
+        }),
+        // rounded pairs are resolved by segment distance in contact
+        _ => None,
+    }
+}
+
+/// Separating axis test, returns (overlap, axis oriented from a to b) or None if separated
+fn separation<A, B>(
+    axes: &[glm::Vec3],
+    offset: &glm::Vec3,
+    project_a: A,
+    project_b: B,
+) -> Option<(f32, glm::Vec3)>
+where
+    A: Fn(&glm::Vec3) -> (f32, f32),
+    B: Fn(&glm::Vec3) -> (f32, f32),
+{
+    let mut best: Option<(f32, glm::Vec3)> = None;
+    for axis in axes {
+        let len = axis.magnitude();
+        if len < 1e-5 {
+            continue;
+        }
+        let axis = axis / len;
+        let (min_a, max_a) = project_a(&axis);
+        let (min_b, max_b) = project_b(&axis);
+        let overlap = (max_a - min_b).min(max_b - min_a);
+        if overlap < 0.0 {
+            return None;
+        }
+        if best.is_none_or(|(o, _)| overlap < o) {
+            let axis = if glm::dot(offset, &axis) < 0.0 {
+                -axis
+            } else {
+                axis
+            };
+            best = Some((overlap, axis));
+        }
+    }
+    best
+}
+
+fn box_interval(b: &OrientedBox, axis: &glm::Vec3) -> (f32, f32) {
+    let c = glm::dot(&b.center, axis);
+    let r = b.projection_radius(axis);
+    (c - r, c + r)
+}
+
+/// Contact point is placed between the points of each box closest to the other's center
+fn box_contact(a: &OrientedBox, b: &OrientedBox) -> Option<Contact> {
+    let mut axes = Vec::with_capacity(15);
+    axes.extend_from_slice(&a.axes);
+    axes.extend_from_slice(&b.axes);
+    for i in a.axes.iter() {
+        for j in b.axes.iter() {
+            axes.push(glm::cross(i, j));
+        }
+    }
+    let (depth, normal) = separation(
+        &axes,
+        &(b.center - a.center),
+        |axis| box_interval(a, axis),
+        |axis| box_interval(b, axis),
+    )?;
+    Some(Contact {
+        point: (a.closest_point(&b.center) + b.closest_point(&a.center)) / 2.0,
+        normal,
+        depth,
+    })
+}
+
+fn box_triangle_contact(b: &OrientedBox, tri: &[glm::Vec3; 3]) -> Option<Contact> {
+    let edges = [tri[1] - tri[0], tri[2] - tri[1], tri[0] - tri[2]];
+    let mut axes = Vec::with_capacity(13);
+    axes.extend_from_slice(&b.axes);
+    axes.push(glm::cross(&edges[0], &edges[1]));
+    for axis in b.axes.iter() {
+        for edge in edges.iter() {
+            axes.push(glm::cross(axis, edge));
+        }
+    }
+    let centroid = (tri[0] + tri[1] + tri[2]) / 3.0;
+    let (depth, normal) = separation(
+        &axes,
+        &(centroid - b.center),
+        |axis| box_interval(b, axis),
+        |axis| {
+            tri.iter()
+                .map(|v| glm::dot(v, axis))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| {
+                    (lo.min(d), hi.max(d))
+                })
+        },
+    )?;
+    Some(Contact {
+        point: closest_point_triangle(&b.center, tri),
+        normal,
+        depth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(center: glm::Vec3, rotation: glm::Quat) -> WorldShape {
+        let axes = [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()]
+            .map(|a| glm::quat_rotate_vec3(&rotation, &a));
+        WorldShape::Box(OrientedBox {
+            center,
+            axes,
+            half_extents: glm::vec3(0.5, 0.5, 0.5),
+        })
+    }
+
+    fn sphere(x: f32, y: f32, radius: f32) -> WorldShape {
+        WorldShape::Sphere {
+            center: glm::vec3(x, y, 0.0),
+            radius,
+        }
+    }
+
+    #[test]
+    fn spheres_and_capsules_collide() {
+        let c = contact(&sphere(0.0, 0.0, 1.0), &sphere(1.5, 0.0, 1.0)).unwrap();
+        assert!((c.depth - 0.5).abs() < 1e-5);
+        assert_eq!(c.normal, glm::Vec3::x());
+        assert!(contact(&sphere(0.0, 0.0, 1.0), &sphere(2.5, 0.0, 1.0)).is_none());
+        let capsule = WorldShape::Capsule {
+            a: glm::vec3(1.0, -2.0, 0.0),
+            b: glm::vec3(1.0, 2.0, 0.0),
+            radius: 0.5,
+        };
+        let c = contact(&capsule, &sphere(0.0, 1.0, 0.75)).unwrap();
+        assert!((c.depth - 0.25).abs() < 1e-5);
+        assert_eq!(c.normal, -glm::Vec3::x());
+    }
+
+    #[test]
+    fn boxes_collide_along_smallest_overlap() {
+        let identity = glm::quat_identity();
+        let a = cube(glm::Vec3::zeros(), identity);
+        let c = contact(&a, &cube(glm::vec3(0.9, 0.2, 0.0), identity)).unwrap();
+        assert!((c.depth - 0.1).abs() < 1e-4);
+        assert!(glm::distance(&c.normal, &glm::Vec3::x()) < 1e-5);
+        let rotated = glm::quat_angle_axis(std::f32::consts::FRAC_PI_4, &glm::Vec3::z());
+        // rotated cube's corner reaches 0.707 from its center
+        assert!(contact(&a, &cube(glm::vec3(1.15, 0.0, 0.0), rotated)).is_some());
+        assert!(contact(&a, &cube(glm::vec3(1.25, 0.0, 0.0), rotated)).is_none());
+    }
+
+    #[test]
+    fn spheres_hit_boxes_and_meshes() {
+        let a = cube(glm::Vec3::zeros(), glm::quat_identity());
+        let c = contact(&sphere(0.0, 0.8, 0.5), &a).unwrap();
+        assert!((c.depth - 0.2).abs() < 1e-4);
+        assert!(glm::distance(&c.normal, &-glm::Vec3::y()) < 1e-4);
+        let c = contact(&a, &sphere(0.0, 0.3, 0.5)).unwrap();
+        assert!((c.depth - 0.7).abs() < 1e-4);
+        assert!(glm::distance(&c.normal, &glm::Vec3::y()) < 1e-4);
+        let floor = WorldShape::Mesh(vec![[
+            glm::vec3(-5.0, 0.0, -5.0),
+            glm::vec3(5.0, 0.0, -5.0),
+            glm::vec3(0.0, 0.0, 5.0),
+        ]]);
+        let c = contact(&sphere(0.0, 0.4, 0.5), &floor).unwrap();
+        assert!((c.depth - 0.1).abs() < 1e-4);
+        assert!(glm::distance(&c.normal, &-glm::Vec3::y()) < 1e-4);
+        let c = contact(
+            &floor,
+            &cube(glm::vec3(0.0, 0.3, 0.0), glm::quat_identity()),
+        )
+        .unwrap();
+        assert!((c.depth - 0.2).abs() < 1e-4);
+        assert!(glm::distance(&c.normal, &glm::Vec3::y()) < 1e-4);
+        assert!(contact(&floor, &floor).is_none());
+    }
+}
@@ -0,0 +1,460 @@
+//! Collider shapes placed in world space and geometric queries on them
+
+use super::Shape;
+use crate::{
+    components::transform::Transform,
+    geometry::{Aabb, Ray},
+};
+
+/// Maximal number of sphere tracing steps used by casts against rounded shapes
+const MAX_CAST_STEPS: usize = 128;
+const CAST_EPSILON: f32 = 1e-4;
+
+/// Box with arbitrary orientation, axes are normalized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedBox {
+    pub center: glm::Vec3,
+    pub axes: [glm::Vec3; 3],
+    pub half_extents: glm::Vec3,
+}
+
+impl OrientedBox {
+    /// Returns point of the box closest to given point, the point itself if it is inside
+    pub fn closest_point(&self, p: &glm::Vec3) -> glm::Vec3 {
+        let d = p - self.center;
+        (0..3).fold(self.center, |q, i| {
+            let dist =
+                glm::dot(&d, &self.axes[i]).clamp(-self.half_extents[i], self.half_extents[i]);
+            q + self.axes[i] * dist
+        })
+    }
+
+    /// Returns coordinates of point in box's local space
+    pub fn to_local(&self, p: &glm::Vec3) -> glm::Vec3 {
+        let d = p - self.center;
+        glm::vec3(
+            glm::dot(&d, &self.axes[0]),
+            glm::dot(&d, &self.axes[1]),
+            glm::dot(&d, &self.axes[2]),
+        )
+    }
+
+    /// Returns half length of box's projection onto given axis
+    pub fn projection_radius(&self, axis: &glm::Vec3) -> f32 {
+        (0..3)
+            .map(|i| self.half_extents[i] * glm::dot(&self.axes[i], axis).abs())
+            .sum()
+    }
+
+    pub fn corners(&self) -> [glm::Vec3; 8] {
+        let mut corners = [self.center; 8];
+        for (n, corner) in corners.iter_mut().enumerate() {
+            for i in 0..3 {
+                let sign = if n & (1 << i) == 0 { -1.0 } else { 1.0 };
+                *corner += self.axes[i] * self.half_extents[i] * sign;
+            }
+        }
+        corners
+    }
+}
+
+/// Collider shape transformed to world space
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorldShape {
+    Sphere {
+        center: glm::Vec3,
+        radius: f32,
+    },
+    Box(OrientedBox),
+    /// all points closer than radius to the segment a-b
+    Capsule {
+        a: glm::Vec3,
+        b: glm::Vec3,
+        radius: f32,
+    },
+    Mesh(Vec<[glm::Vec3; 3]>),
+}
+
+impl WorldShape {
+    /// Places shape in world, offset is in transform's local space
+    pub fn new(shape: &Shape, offset: &glm::Vec3, transform: &Transform) -> Self {
+        let scale = transform.scale().abs();
+        let max_scale = scale.max();
+        let center = transform.point_to_world(offset);
+        match shape {
+            Shape::Sphere { radius } => WorldShape::Sphere {
+                center,
+                radius: radius * max_scale,
+            },
+            Shape::Aabb { half_extents } => WorldShape::Box(OrientedBox {
+                center,
+                axes: [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()],
+                half_extents: half_extents.component_mul(&scale),
+            }),
+            Shape::Obb { half_extents } => WorldShape::Box(OrientedBox {
+                center,
+                axes: [transform.right(), transform.up(), -transform.forward()],
+                half_extents: half_extents.component_mul(&scale),
+            }),
+            Shape::Capsule {
+                radius,
+                half_height,
+            } => {
+                let axis = transform.up() * *half_height * scale.y;
+                WorldShape::Capsule {
+                    a: center - axis,
+                    b: center + axis,
+                    radius: radius * scale.x.max(scale.z),
+                }
+            }
+            Shape::Mesh(mesh) => WorldShape::Mesh(
+                mesh.triangles()
+                    .map(|tri| tri.map(|v| transform.point_to_world(&(v + offset))))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        match self {
+            WorldShape::Sphere { center, radius } => {
+                Aabb::from_center(*center, glm::vec3(*radius, *radius, *radius))
+            }
+            WorldShape::Box(b) => {
+                let extents = (0..3).fold(glm::Vec3::zeros(), |e, i| {
+                    e + b.axes[i].abs() * b.half_extents[i]
+                });
+                Aabb::from_center(b.center, extents)
+            }
+            WorldShape::Capsule { a, b, radius } => {
+                let r = glm::vec3(*radius, *radius, *radius);
+                Aabb::new(glm::min2(a, b) - r, glm::max2(a, b) + r)
+            }
+            WorldShape::Mesh(triangles) => {
+                Aabb::from_points(&triangles.iter().flatten().copied().collect::<Vec<_>>())
+            }
+        }
+    }
+
+    /// Returns signed distance from point to shape's surface (negative inside)
+    /// and surface normal pointing towards the point, meshes are surfaces without inside
+    pub fn distance(&self, p: &glm::Vec3) -> (f32, glm::Vec3) {
+        match self {
+            WorldShape::Sphere { center, radius } => {
+                let (len, normal) = direction(&(p - center));
+                (len - radius, normal)
+            }
+            WorldShape::Capsule { a, b, radius } => {
+                let (len, normal) = direction(&(p - closest_point_segment(p, a, b)));
+                (len - radius, normal)
+            }
+            WorldShape::Box(b) => {
+                let (outside, normal) = direction(&(p - b.closest_point(p)));
+                if outside > 0.0 {
+                    return (outside, normal);
+                }
+                let (axis, depth, sign) = deepest_face(b, p);
+                (-depth, b.axes[axis] * sign)
+            }
+            WorldShape::Mesh(triangles) => triangles
+                .iter()
+                .map(|tri| direction(&(p - closest_point_triangle(p, tri))))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .unwrap_or((f32::INFINITY, glm::Vec3::y())),
+        }
+    }
+
+    /// Returns distance along the ray and surface normal where a sphere of given radius
+    /// moving along the ray first touches the shape, radius 0 is a plain raycast
+    pub fn cast(&self, ray: &Ray, radius: f32, max_distance: f32) -> Option<(f32, glm::Vec3)> {
+        match self {
+            WorldShape::Sphere {
+                center,
+                radius: sphere_radius,
+            } => cast_sphere(ray, center, sphere_radius + radius, max_distance),
+            WorldShape::Box(b) if radius == 0.0 => cast_box(ray, b, max_distance),
+            WorldShape::Mesh(triangles) if radius == 0.0 => triangles
+                .iter()
+                .filter_map(|tri| cast_triangle(ray, tri, max_distance))
+                .min_by(|a, b| a.0.total_cmp(&b.0)),
+            _ => self.sphere_trace(ray, radius, max_distance),
+        }
+    }
+
+    /// Marches along the ray by distance to the shape, which never skips over the surface
+    fn sphere_trace(&self, ray: &Ray, radius: f32, max_distance: f32) -> Option<(f32, glm::Vec3)> {
+        let mut t = 0.0;
+        for _ in 0..MAX_CAST_STEPS {
+            let p = ray.point_at(t);
+            let (d, normal) = self.distance(&p);
+            if d - radius < CAST_EPSILON {
+                return Some((t, normal));
+            }
+            t += d - radius;
+            if t > max_distance {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+/// Returns (axis index, distance to the face, sign of the face) of face closest
+/// to point inside the box
+pub(crate) fn deepest_face(b: &OrientedBox, p: &glm::Vec3) -> (usize, f32, f32) {
+    let local = b.to_local(p);
+    (0..3)
+        .map(|i| {
+            let sign = if local[i] < 0.0 { -1.0 } else { 1.0 };
+            (i, b.half_extents[i] - local[i].abs(), sign)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+/// Returns length and normalized direction of given vector, y axis for zero vector
+pub(crate) fn direction(v: &glm::Vec3) -> (f32, glm::Vec3) {
+    let len = v.magnitude();
+    match len > f32::EPSILON {
+        true => (len, v / len),
+        false => (0.0, glm::Vec3::y()),
+    }
+}
+
+pub fn closest_point_segment(p: &glm::Vec3, a: &glm::Vec3, b: &glm::Vec3) -> glm::Vec3 {
+    let ab = b - a;
+    let len2 = ab.magnitude_squared();
+    if len2 < f32::EPSILON {
+        return *a;
+    }
+    let t = (glm::dot(&(p - a), &ab) / len2).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// Returns closest points (on the first segment, on the second segment) of two segments
+pub fn closest_points_segments(
+    p1: &glm::Vec3,
+    q1: &glm::Vec3,
+    p2: &glm::Vec3,
+    q2: &glm::Vec3,
+) -> (glm::Vec3, glm::Vec3) {
+    let (d1, d2, r) = (q1 - p1, q2 - p2, p1 - p2);
+    let (a, e, f) = (
+        d1.magnitude_squared(),
+        d2.magnitude_squared(),
+        glm::dot(&d2, &r),
+    );
+    if a < f32::EPSILON && e < f32::EPSILON {
+        return (*p1, *p2);
+    }
+    let (s, t) = if a < f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = glm::dot(&d1, &r);
+        if e < f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = glm::dot(&d1, &d2);
+            let denom = a * e - b * b;
+            let s = match denom > f32::EPSILON {
+                true => ((b * f - c * e) / denom).clamp(0.0, 1.0),
+                false => 0.0,
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+pub fn closest_point_triangle(p: &glm::Vec3, tri: &[glm::Vec3; 3]) -> glm::Vec3 {
+    let [a, b, c] = tri;
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (glm::dot(&ab, &ap), glm::dot(&ac, &ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+    let bp = p - b;
+    let (d3, d4) = (glm::dot(&ab, &bp), glm::dot(&ac, &bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let (d5, d6) = (glm::dot(&ab, &cp), glm::dot(&ac, &cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+fn cast_sphere(
+    ray: &Ray,
+    center: &glm::Vec3,
+    radius: f32,
+    max_distance: f32,
+) -> Option<(f32, glm::Vec3)> {
+    let m = ray.origin - center;
+    let c = m.magnitude_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some((0.0, -ray.direction));
+    }
+    let b = glm::dot(&m, &ray.direction);
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let t = -b - discriminant.sqrt();
+    (t <= max_distance).then(|| (t, (ray.point_at(t) - center) / radius))
+}
+
+fn cast_box(ray: &Ray, b: &OrientedBox, max_distance: f32) -> Option<(f32, glm::Vec3)> {
+    let origin = b.to_local(&ray.origin);
+    let dir = glm::vec3(
+        glm::dot(&ray.direction, &b.axes[0]),
+        glm::dot(&ray.direction, &b.axes[1]),
+        glm::dot(&ray.direction, &b.axes[2]),
+    );
+    let (mut t_min, mut t_max) = (0.0f32, max_distance);
+    let mut normal = -ray.direction;
+    for i in 0..3 {
+        if dir[i].abs() < f32::EPSILON {
+            if origin[i].abs() > b.half_extents[i] {
+                return None;
+            }
+            continue;
+        }
+        let mut t1 = (-b.half_extents[i] - origin[i]) / dir[i];
+        let mut t2 = (b.half_extents[i] - origin[i]) / dir[i];
+        let mut sign = -1.0;
+        if t1 > t2 {
+            std::mem::swap(&mut t1, &mut t2);
+            sign = 1.0;
+        }
+        if t1 > t_min {
+            t_min = t1;
+            normal = b.axes[i] * sign;
+        }
+        t_max = t_max.min(t2);
+        if t_min > t_max {
+            return None;
+        }
+    }
+    Some((t_min, normal))
+}
+
+/// Möller–Trumbore ray-triangle intersection, triangles are double sided
+fn cast_triangle(ray: &Ray, tri: &[glm::Vec3; 3], max_distance: f32) -> Option<(f32, glm::Vec3)> {
+    let (e1, e2) = (tri[1] - tri[0], tri[2] - tri[0]);
+    let p = glm::cross(&ray.direction, &e2);
+    let det = glm::dot(&e1, &p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let s = ray.origin - tri[0];
+    let u = glm::dot(&s, &p) / det;
+    let q = glm::cross(&s, &e1);
+    let v = glm::dot(&ray.direction, &q) / det;
+    if u < 0.0 || v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = glm::dot(&e2, &q) / det;
+    if t < 0.0 || t > max_distance {
+        return None;
+    }
+    let normal = glm::cross(&e1, &e2).normalize();
+    let normal = if glm::dot(&normal, &ray.direction) > 0.0 {
+        -normal
+    } else {
+        normal
+    };
+    Some((t, normal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> WorldShape {
+        WorldShape::Box(OrientedBox {
+            center: glm::Vec3::zeros(),
+            axes: [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()],
+            half_extents: glm::vec3(1.0, 1.0, 1.0),
+        })
+    }
+
+    #[test]
+    fn raycasts_hit_each_shape() {
+        let ray = Ray::new(glm::vec3(-5.0, 0.0, 0.0), glm::Vec3::x());
+        let capsule = WorldShape::Capsule {
+            a: glm::vec3(0.0, -1.0, 0.0),
+            b: glm::vec3(0.0, 1.0, 0.0),
+            radius: 0.5,
+        };
+        let triangle = WorldShape::Mesh(vec![[
+            glm::vec3(0.0, -1.0, -1.0),
+            glm::vec3(0.0, 1.0, -1.0),
+            glm::vec3(0.0, 0.0, 1.0),
+        ]]);
+        let sphere = WorldShape::Sphere {
+            center: glm::Vec3::zeros(),
+            radius: 2.0,
+        };
+        for (shape, distance) in [
+            (unit_box(), 4.0),
+            (capsule, 4.5),
+            (triangle, 5.0),
+            (sphere, 3.0),
+        ] {
+            let (t, normal) = shape.cast(&ray, 0.0, 100.0).unwrap();
+            assert!((t - distance).abs() < 1e-3, "{shape:?} {t}");
+            assert!(glm::distance(&normal, &-glm::Vec3::x()) < 1e-3);
+            assert!(shape.cast(&ray, 0.0, distance - 0.1).is_none());
+        }
+    }
+
+    #[test]
+    fn sphere_cast_touches_box_earlier() {
+        let ray = Ray::new(glm::vec3(-5.0, 1.2, 0.0), glm::Vec3::x());
+        assert!(unit_box().cast(&ray, 0.0, 100.0).is_none());
+        let (t, normal) = unit_box().cast(&ray, 0.5, 100.0).unwrap();
+        let expected = 4.0 - (0.25f32 - 0.04).sqrt();
+        assert!((t - expected).abs() < 1e-3, "{t}");
+        assert!(normal.y > 0.0 && normal.x < 0.0);
+    }
+
+    #[test]
+    fn closest_points_of_crossing_segments() {
+        let (a, b) = closest_points_segments(
+            &glm::vec3(-1.0, 0.0, 0.0),
+            &glm::vec3(1.0, 0.0, 0.0),
+            &glm::vec3(0.5, 1.0, -1.0),
+            &glm::vec3(0.5, 1.0, 1.0),
+        );
+        assert!(glm::distance(&a, &glm::vec3(0.5, 0.0, 0.0)) < 1e-5);
+        assert!(glm::distance(&b, &glm::vec3(0.5, 1.0, 0.0)) < 1e-5);
+        let tri = [glm::Vec3::zeros(), glm::Vec3::x(), glm::Vec3::y()];
+        let q = closest_point_triangle(&glm::vec3(0.25, 0.25, 3.0), &tri);
+        assert!(glm::distance(&q, &glm::vec3(0.25, 0.25, 0.0)) < 1e-5);
+        let q = closest_point_triangle(&glm::vec3(2.0, 2.0, 0.0), &tri);
+        assert!(glm::distance(&q, &glm::vec3(0.5, 0.5, 0.0)) < 1e-5);
+    }
+}
@@ -0,0 +1,359 @@
+//! 2D counterpart of the collision module for sprite based games.
+//! Colliders lie in the XY plane and are placed by gameobject's Transform,
+//! gameobjects return them from GameObject::collider_2d. Touching 2D colliders
+//! are detected in the same fixed step as 3D ones and reported with the same
+//! on_collision_* and on_trigger_* methods (contacts have z = 0).
+//! Rigid bodies with 2D colliders move in the XY plane and rotate around the z axis.
+
+pub mod character;
+pub mod shapes;
+pub mod tilemap;
+
+use crate::{
+    collision::{broadphase::UniformGrid, Contact, ALL_LAYERS},
+    error::{GameError, GameResult},
+    gameobject::GameObjectId,
+};
+use shapes::{WorldShape2D, ONE_WAY_NORMAL_Y};
+use std::rc::Rc;
+use tilemap::Tilemap;
+
+/// Convex polygon in gameobject's local space
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexPolygon {
+    points: Vec<glm::Vec2>,
+}
+
+impl ConvexPolygon {
+    /// Points can be given in either winding, they are stored counter-clockwise
+    pub fn new(mut points: Vec<glm::Vec2>) -> GameResult<Self> {
+        if points.len() < 3 {
+            return Err(GameError::GameLogicError(format!(
+                "Polygon collider needs at least 3 points, got {}",
+                points.len()
+            )));
+        }
+        let n = points.len();
+        let degenerate =
+            (0..n).any(|i| glm::distance(&points[i], &points[(i + 1) % n]) <= f32::EPSILON);
+        if degenerate || shapes::signed_area(&points).abs() <= f32::EPSILON {
+            return Err(GameError::GameLogicError(
+                "Polygon collider can't have repeated points or zero area".into(),
+            ));
+        }
+        if shapes::signed_area(&points) < 0.0 {
+            points.reverse();
+        }
+        let convex = (0..n).all(|i| {
+            let (a, b, c) = (points[i], points[(i + 1) % n], points[(i + 2) % n]);
+            let (ab, bc) = (b - a, c - b);
+            ab.x * bc.y - ab.y * bc.x >= 0.0
+        });
+        if !convex {
+            return Err(GameError::GameLogicError(
+                "Polygon collider has to be convex".into(),
+            ));
+        }
+        Ok(ConvexPolygon { points })
+    }
+
+    pub fn points(&self) -> &[glm::Vec2] {
+        &self.points
+    }
+}
+
+/// Shape of a 2D collider in gameobject's local space, scaled by its transform
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape2D {
+    Circle {
+        radius: f32,
+    },
+    /// box rotated with the gameobject
+    Box {
+        half_extents: glm::Vec2,
+    },
+    Polygon(Rc<ConvexPolygon>),
+    /// tilemaps don't collide with other tilemaps
+    Tilemap(Rc<Tilemap>),
+}
+
+/// Collider returned by GameObject::collider_2d
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collider2D {
+    pub shape: Shape2D,
+    /// position of shape's center in gameobject's local space
+    pub offset: glm::Vec2,
+    /// triggers report overlaps with on_trigger_* methods and are ignored by scene queries
+    pub is_trigger: bool,
+    /// one way platforms only block colliders coming from above (positive y)
+    pub one_way: bool,
+    /// collision layer (0-31) of the collider
+    pub layer: u8,
+    /// bitmask of layers this collider collides with,
+    /// both colliders have to include each other's layer
+    pub mask: u32,
+}
+
+impl Collider2D {
+    pub fn new(shape: Shape2D) -> Self {
+        Collider2D {
+            shape,
+            offset: glm::Vec2::zeros(),
+            is_trigger: false,
+            one_way: false,
+            layer: 0,
+            mask: ALL_LAYERS,
+        }
+    }
+
+    pub fn offset(mut self, offset: glm::Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn trigger(mut self, is_trigger: bool) -> Self {
+        self.is_trigger = is_trigger;
+        self
+    }
+
+    pub fn one_way(mut self, one_way: bool) -> Self {
+        self.one_way = one_way;
+        self
+    }
+
+    pub fn layer(mut self, layer: u8) -> Self {
+        self.layer = layer.min(31);
+        self
+    }
+
+    pub fn mask(mut self, mask: u32) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Returns bit of collider's layer, as used in masks
+    pub fn layer_bit(&self) -> u32 {
+        1 << self.layer
+    }
+
+    pub fn interacts_with(&self, other: &Collider2D) -> bool {
+        self.mask & other.layer_bit() != 0 && other.mask & self.layer_bit() != 0
+    }
+}
+
+/// Contact of two 2D colliders, see collision::Contact
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact2D {
+    pub point: glm::Vec2,
+    /// normalized direction from the first collider to the second
+    pub normal: glm::Vec2,
+    pub depth: f32,
+}
+
+impl Contact2D {
+    /// Returns contact seen from the other collider
+    pub fn flipped(self) -> Self {
+        Contact2D {
+            normal: -self.normal,
+            ..self
+        }
+    }
+
+    /// Returns contact in the XY plane at given depth
+    pub fn to_3d(&self, z: f32) -> Contact {
+        Contact {
+            point: glm::vec3(self.point.x, self.point.y, z),
+            normal: glm::vec3(self.normal.x, self.normal.y, 0.0),
+            depth: self.depth,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit2D {
+    pub id: GameObjectId,
+    pub point: glm::Vec2,
+    /// surface normal at the hit point
+    pub normal: glm::Vec2,
+    /// distance along the ray
+    pub distance: f32,
+}
+
+/// 2D collider of a gameobject placed in world
+pub(crate) struct Body2D {
+    pub id: GameObjectId,
+    pub collider: Collider2D,
+    pub shape: WorldShape2D,
+    /// gameobject's z position, used as z of contact points
+    pub z: f32,
+}
+
+impl Body2D {
+    /// Returns the deepest contact of two bodies, normal points from a to b
+    pub fn contact(&self, other: &Body2D) -> Option<Contact2D> {
+        if matches!(self.shape, WorldShape2D::Tilemap { .. })
+            && matches!(other.shape, WorldShape2D::Tilemap { .. })
+        {
+            return None;
+        }
+        let (a, b) = (self.shape.bounds(), other.shape.bounds());
+        let (min, max) = (
+            glm::max2(&a.min, &b.min).xy(),
+            glm::min2(&a.max, &b.max).xy(),
+        );
+        let parts_b = other.shape.parts(&min, &max, other.collider.one_way);
+        let mut deepest: Option<Contact2D> = None;
+        for (part_a, one_way_a) in self.shape.parts(&min, &max, self.collider.one_way) {
+            for (part_b, one_way_b) in parts_b.iter() {
+                let Some(c) = shapes::contact(&part_a, part_b) else {
+                    continue;
+                };
+                // one way parts push other colliders only up
+                let blocked = (one_way_a && c.normal.y < ONE_WAY_NORMAL_Y)
+                    || (*one_way_b && -c.normal.y < ONE_WAY_NORMAL_Y);
+                if !blocked && deepest.is_none_or(|d| c.depth > d.depth) {
+                    deepest = Some(c);
+                }
+            }
+        }
+        deepest
+    }
+}
+
+/// Returns touching pairs of bodies (first id, second id, contact from first to second,
+/// is trigger)
+pub(crate) fn find_contacts(
+    grid: &mut UniformGrid,
+    bodies: &[Body2D],
+) -> Vec<(GameObjectId, GameObjectId, Contact, bool)> {
+    let bounds = bodies.iter().map(|b| b.shape.bounds()).collect::<Vec<_>>();
+    grid.pairs(&bounds)
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let (a, b) = (&bodies[i], &bodies[j]);
+            if !a.collider.interacts_with(&b.collider) {
+                return None;
+            }
+            let contact = a.contact(b)?;
+            let trigger = a.collider.is_trigger || b.collider.is_trigger;
+            Some((a.id, b.id, contact.to_3d(a.z), trigger))
+        })
+        .collect()
+}
+
+/// Returns hits of the ray sorted by distance, triggers and colliders
+/// not on mask's layers are ignored
+pub(crate) fn raycast(
+    bodies: &[Body2D],
+    origin: &glm::Vec2,
+    direction: &glm::Vec2,
+    max_distance: f32,
+    mask: u32,
+) -> Vec<RaycastHit2D> {
+    let direction = direction.normalize();
+    let mut hits = bodies
+        .iter()
+        .filter(|b| !b.collider.is_trigger && mask & b.collider.layer_bit() != 0)
+        .filter_map(|b| {
+            let (distance, normal) =
+                b.shape
+                    .raycast(origin, &direction, max_distance, b.collider.one_way)?;
+            Some(RaycastHit2D {
+                id: b.id,
+                point: origin + direction * distance,
+                normal,
+                distance,
+            })
+        })
+        .collect::<Vec<_>>();
+    hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    hits
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::components::transform::Transform;
+
+    pub(crate) fn body(id: usize, x: f32, y: f32, collider: Collider2D) -> Body2D {
+        let transform = Transform::new(
+            glm::vec3(x, y, 0.0),
+            glm::Vec3::zeros(),
+            glm::vec3(1.0, 1.0, 1.0),
+        );
+        Body2D {
+            id: GameObjectId {
+                layer: 0,
+                id,
+                is_dead: false,
+            },
+            shape: WorldShape2D::new(&collider.shape, &collider.offset, &transform),
+            collider,
+            z: 0.0,
+        }
+    }
+
+    #[test]
+    fn polygons_are_validated_and_wound_counter_clockwise() {
+        let clockwise = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(0.0, 1.0),
+            glm::vec2(1.0, 0.0),
+        ];
+        let polygon = ConvexPolygon::new(clockwise).unwrap();
+        assert_eq!(polygon.points()[0], glm::vec2(1.0, 0.0));
+        let concave = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(2.0, 0.0),
+            glm::vec2(1.0, 0.5),
+            glm::vec2(2.0, 2.0),
+            glm::vec2(0.0, 2.0),
+        ];
+        assert!(ConvexPolygon::new(concave).is_err());
+        assert!(ConvexPolygon::new(vec![glm::Vec2::zeros(); 2]).is_err());
+        let repeated = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(1.0, 0.0),
+            glm::vec2(1.0, 0.0),
+            glm::vec2(0.0, 1.0),
+        ];
+        assert!(ConvexPolygon::new(repeated).is_err());
+        let flat = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(1.0, 0.0),
+            glm::vec2(2.0, 0.0),
+        ];
+        assert!(ConvexPolygon::new(flat).is_err());
+    }
+
+    #[test]
+    fn bodies_touch_tilemaps_and_one_way_platforms() {
+        let map = Tilemap::from_rows(&["...", "#-#"], glm::vec2(1.0, 1.0)).unwrap();
+        let level = body(0, 0.0, 0.0, Collider2D::new(Shape2D::Tilemap(Rc::new(map))));
+        let ball = |x: f32, y: f32| body(1, x, y, Collider2D::new(Shape2D::Circle { radius: 0.5 }));
+        let c = level.contact(&ball(0.5, 1.3)).unwrap();
+        assert!((c.depth - 0.2).abs() < 1e-5);
+        assert!(glm::distance(&c.normal, &glm::vec2(0.0, 1.0)) < 1e-5);
+        // one way tile pushes up only
+        assert!(level.contact(&ball(1.5, 1.3)).is_some());
+        assert!(level.contact(&ball(1.5, -0.3)).is_none());
+
+        let bodies = [level, ball(2.5, 1.3)];
+        let mut grid = UniformGrid::new(1.0);
+        let contacts = find_contacts(&mut grid, &bodies);
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].2.normal, glm::vec3(0.0, 1.0, 0.0));
+        let hits = raycast(
+            &bodies,
+            &glm::vec2(1.5, 5.0),
+            &glm::vec2(0.0, -2.0),
+            10.0,
+            ALL_LAYERS,
+        );
+        let hits = hits
+            .iter()
+            .map(|h| (h.id.id, h.distance))
+            .collect::<Vec<_>>();
+        assert_eq!(hits, vec![(0, 4.0)]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Kinematic character controller moving a 2D collider through the scene.
+//! The character is not affected by rigid body physics, instead it is moved by given
+//! displacement (usually velocity * fixed time step with gravity added by the game),
+//! sliding along walls, walking up and down slopes and standing on one way platforms.
+
+use super::{
+    shapes::{self, ConvexShape2D, WorldShape2D, ONE_WAY_NORMAL_Y},
+    Body2D, Collider2D, Contact2D,
+};
+use crate::{components::transform::Transform, scene::Scene};
+
+/// Longest part of movement done at once, relative to the character's smallest half extent
+const MAX_STEP: f32 = 0.5;
+const MAX_STEPS: usize = 64;
+/// Number of times overlaps are resolved after each step
+const RESOLVE_ITERATIONS: usize = 4;
+/// Extra depth allowed when landing on one way platforms
+const ONE_WAY_SKIN: f32 = 0.01;
+/// Steepest walkable slope, so walls (and ceilings) are never treated as ground
+const MAX_WALKABLE_SLOPE: f32 = 89f32 * std::f32::consts::PI / 180.0;
+
+#[derive(Debug, Clone)]
+pub struct CharacterController2D {
+    /// shape of the character, it shouldn't be a tilemap
+    pub collider: Collider2D,
+    /// steepest slope in radians the character can stand on, at most 89 degrees
+    pub max_slope: f32,
+    grounded: bool,
+    ground_normal: glm::Vec2,
+    contacts: Vec<Contact2D>,
+}
+
+impl CharacterController2D {
+    pub fn new(collider: Collider2D) -> Self {
+        CharacterController2D {
+            collider,
+            max_slope: 45f32.to_radians(),
+            grounded: false,
+            ground_normal: glm::vec2(0.0, 1.0),
+            contacts: Vec::new(),
+        }
+    }
+
+    pub fn max_slope(mut self, radians: f32) -> Self {
+        self.max_slope = radians.min(MAX_WALKABLE_SLOPE);
+        self
+    }
+
+    /// Returns true if the character stood on walkable ground during the last move
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    pub fn ground_normal(&self) -> &glm::Vec2 {
+        &self.ground_normal
+    }
+
+    /// Returns contacts resolved during the last move, normals point towards the character
+    pub fn contacts(&self) -> &[Contact2D] {
+        &self.contacts
+    }
+
+    /// Moves transform by delta in the XY plane, stopping at colliders on layers
+    /// in collider's mask and returns the actual displacement.
+    /// Call it from the character's gameobject (e.g. in fixed_update) so it doesn't
+    /// collide with its own collider. Obstacles are placed as they were at the beginning
+    /// of fixed_update or update, like in scene queries.
+    pub fn move_by(
+        &mut self,
+        scene: &Scene,
+        transform: &mut Transform,
+        delta: glm::Vec2,
+    ) -> glm::Vec2 {
+        self.move_through(scene.query_bodies_2d(), transform, delta)
+    }
+
+    pub(crate) fn move_through<'a>(
+        &mut self,
+        obstacles: impl IntoIterator<Item = &'a Body2D>,
+        transform: &mut Transform,
+        delta: glm::Vec2,
+    ) -> glm::Vec2 {
+        self.grounded = false;
+        self.contacts.clear();
+        let WorldShape2D::Convex(shape) =
+            WorldShape2D::new(&self.collider.shape, &self.collider.offset, transform)
+        else {
+            return glm::Vec2::zeros();
+        };
+        let (min, max) = shape.bounds();
+        let max_step = ((max - min).min() / 2.0 * MAX_STEP).max(f32::EPSILON);
+        // only colliders within reach of the whole move are tested
+        let reach = glm::vec2(1.0, 1.0) * (delta.magnitude() + max_step);
+        let (reach_min, reach_max) = (min - reach, max + reach);
+        let obstacles = obstacles
+            .into_iter()
+            .filter(|o| !o.collider.is_trigger && o.collider.interacts_with(&self.collider))
+            .flat_map(|o| o.shape.parts(&reach_min, &reach_max, o.collider.one_way))
+            .collect::<Vec<_>>();
+
+        let walkable = self.max_slope.min(MAX_WALKABLE_SLOPE).cos();
+        let mut moved = glm::Vec2::zeros();
+        let mut remaining = delta;
+        for _ in 0..MAX_STEPS {
+            if remaining.magnitude() < f32::EPSILON {
+                break;
+            }
+            let step = match remaining.magnitude() > max_step {
+                true => remaining.normalize() * max_step,
+                false => remaining,
+            };
+            remaining -= step;
+            moved += step;
+            for _ in 0..RESOLVE_ITERATIONS {
+                let Some(c) = Self::deepest(&obstacles, &shape.translated(&moved), &step) else {
+                    break;
+                };
+                let n = c.normal;
+                if n.y >= walkable && n.y > 0.0 && step.y <= 0.0 {
+                    // ground is left vertically, so the character doesn't slide down slopes
+                    moved.y += c.depth / n.y;
+                    remaining.y = remaining.y.max(0.0);
+                    self.grounded = true;
+                    self.ground_normal = n;
+                } else if n.y > 0.0 && step.y <= 0.0 && n.x.abs() > f32::EPSILON {
+                    // too steep slopes block walking characters like walls
+                    let wall = glm::vec2(n.x.signum(), 0.0);
+                    moved += wall * (c.depth / n.x.abs());
+                    remaining.x = match remaining.x * wall.x < 0.0 {
+                        true => 0.0,
+                        false => remaining.x,
+                    };
+                } else {
+                    moved += n * c.depth;
+                    let into = glm::dot(&remaining, &n);
+                    if into < 0.0 {
+                        remaining -= n * into;
+                    }
+                }
+                self.contacts.push(c);
+            }
+        }
+        *transform.position_mut() += glm::vec3(moved.x, moved.y, 0.0);
+        moved
+    }
+
+    /// Returns the deepest contact (normal towards the character) with obstacles,
+    /// one way platforms block the character only when it lands on them
+    fn deepest(
+        obstacles: &[(ConvexShape2D, bool)],
+        character: &ConvexShape2D,
+        step: &glm::Vec2,
+    ) -> Option<Contact2D> {
+        obstacles
+            .iter()
+            .filter_map(|(obstacle, one_way)| {
+                let c = shapes::contact(obstacle, character)?;
+                let blocks = !one_way
+                    || (c.normal.y >= ONE_WAY_NORMAL_Y
+                        && step.y <= 0.0
+                        && c.depth <= -step.y + ONE_WAY_SKIN);
+                (blocks && c.depth > f32::EPSILON).then_some(c)
+            })
+            .max_by(|a, b| a.depth.total_cmp(&b.depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision2d::{tests::body, tilemap::Tilemap, Shape2D};
+    use std::rc::Rc;
+
+    fn character(x: f32, y: f32) -> (CharacterController2D, Transform) {
+        let collider = Collider2D::new(Shape2D::Box {
+            half_extents: glm::vec2(0.25, 0.5),
+        });
+        let transform = Transform::new(
+            glm::vec3(x, y, 0.0),
+            glm::Vec3::zeros(),
+            glm::vec3(1.0, 1.0, 1.0),
+        );
+        (CharacterController2D::new(collider), transform)
+    }
+
+    fn level() -> Vec<Body2D> {
+        let map = Tilemap::from_rows(
+            &["..........#", "...--.....#", "........./#", "###########"],
+            glm::vec2(1.0, 1.0),
+        )
+        .unwrap();
+        vec![body(
+            0,
+            0.0,
+            0.0,
+            Collider2D::new(Shape2D::Tilemap(Rc::new(map))),
+        )]
+    }
+
+    #[test]
+    fn character_lands_and_slides_along_walls() {
+        let level = level();
+        let (mut c, mut t) = character(1.5, 3.0);
+        let moved = c.move_through(&level, &mut t, glm::vec2(0.0, -3.0));
+        assert!(c.is_grounded());
+        assert!((t.position().y - 1.5).abs() < 1e-3, "{}", t.position());
+        assert!((moved.y + 1.5).abs() < 1e-3);
+        // falling diagonally onto the floor keeps horizontal movement
+        let (mut c, mut t) = character(1.5, 2.0);
+        c.move_through(&level, &mut t, glm::vec2(1.0, -1.0));
+        assert!((t.position() - glm::vec3(2.5, 1.5, 0.0)).magnitude() < 1e-3);
+        // running into the wall on the right
+        let (mut c, mut t) = character(9.0, 3.5);
+        c.move_through(&level, &mut t, glm::vec2(3.0, 0.0));
+        assert!((t.position().x - 9.75).abs() < 1e-3, "{}", t.position());
+        assert!(!c.is_grounded());
+    }
+
+    #[test]
+    fn character_walks_up_slopes() {
+        let level = level();
+        let (mut c, mut t) = character(7.5, 1.5);
+        for _ in 0..20 {
+            c.move_through(&level, &mut t, glm::vec2(0.1, -0.1));
+        }
+        // bottom right corner of the character stands on the slope rising from x = 9 to 10
+        assert!(c.is_grounded());
+        assert!((t.position().x - 9.5).abs() < 1e-3, "{}", t.position());
+        assert!((t.position().y - 2.25).abs() < 0.02, "{}", t.position());
+        // steeper slopes than max_slope are walls
+        let (mut c, mut t) = character(8.5, 1.5);
+        c.max_slope = 30f32.to_radians();
+        c.move_through(&level, &mut t, glm::vec2(1.0, -0.1));
+        assert!(
+            t.position().x < 9.3 && t.position().y < 1.6,
+            "{}",
+            t.position()
+        );
+    }
+
+    #[test]
+    fn walls_are_not_walkable_with_vertical_max_slope() {
+        let level = level();
+        for max_slope in [90f32.to_radians(), std::f32::consts::PI] {
+            let (mut c, mut t) = character(9.0, 3.5);
+            c.max_slope = max_slope;
+            c.move_through(&level, &mut t, glm::vec2(3.0, -0.1));
+            assert!((t.position().x - 9.75).abs() < 1e-3, "{}", t.position());
+            assert!(!c.is_grounded());
+        }
+        let c = character(0.0, 0.0).0.max_slope(90f32.to_radians());
+        assert!(c.max_slope < 90f32.to_radians());
+    }
+
+    #[test]
+    fn one_way_platforms_are_jumped_through() {
+        let level = level();
+        let (mut c, mut t) = character(3.5, 1.5);
+        c.move_through(&level, &mut t, glm::vec2(0.0, 2.0));
+        assert!((t.position().y - 3.5).abs() < 1e-3);
+        c.move_through(&level, &mut t, glm::vec2(0.0, -1.0));
+        assert!(c.is_grounded());
+        assert!((t.position().y - 3.5).abs() < 1e-3, "{}", t.position());
+    }
+}
@@ -0,0 +1,454 @@
+//! 2D collider shapes placed in world and geometric queries on them
+
+use super::{tilemap::Tilemap, Contact2D, Shape2D};
+use crate::{components::transform::Transform, geometry::Aabb};
+use std::rc::Rc;
+
+/// One way platforms block only contacts whose normal points up at least this much
+pub(crate) const ONE_WAY_NORMAL_Y: f32 = 0.7;
+/// Points of a polygon closer than this to its deepest point form the touching face
+const FACE_TOLERANCE: f32 = 1e-3;
+
+/// Convex shape in world space
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvexShape2D {
+    Circle {
+        center: glm::Vec2,
+        radius: f32,
+    },
+    /// counter-clockwise vertices
+    Polygon(Vec<glm::Vec2>),
+}
+
+impl ConvexShape2D {
+    /// Returns (min, max) corners of the bounding box
+    pub fn bounds(&self) -> (glm::Vec2, glm::Vec2) {
+        match self {
+            ConvexShape2D::Circle { center, radius } => {
+                let r = glm::vec2(*radius, *radius);
+                (center - r, center + r)
+            }
+            ConvexShape2D::Polygon(points) => {
+                points.iter().fold((points[0], points[0]), |(min, max), p| {
+                    (glm::min2(&min, p), glm::max2(&max, p))
+                })
+            }
+        }
+    }
+
+    pub fn translated(&self, offset: &glm::Vec2) -> Self {
+        match self {
+            ConvexShape2D::Circle { center, radius } => ConvexShape2D::Circle {
+                center: center + offset,
+                radius: *radius,
+            },
+            ConvexShape2D::Polygon(points) => {
+                ConvexShape2D::Polygon(points.iter().map(|p| p + offset).collect())
+            }
+        }
+    }
+
+    /// Returns distance along the ray and surface normal where the ray enters the shape,
+    /// direction has to be normalized
+    pub fn raycast(
+        &self,
+        origin: &glm::Vec2,
+        direction: &glm::Vec2,
+        max_distance: f32,
+    ) -> Option<(f32, glm::Vec2)> {
+        match self {
+            ConvexShape2D::Circle { center, radius } => {
+                let m = origin - center;
+                let c = m.magnitude_squared() - radius * radius;
+                if c <= 0.0 {
+                    return Some((0.0, -direction));
+                }
+                let b = glm::dot(&m, direction);
+                let discriminant = b * b - c;
+                if b > 0.0 || discriminant < 0.0 {
+                    return None;
+                }
+                let t = -b - discriminant.sqrt();
+                (t <= max_distance).then(|| (t, (origin + direction * t - center) / *radius))
+            }
+            ConvexShape2D::Polygon(points) => {
+                // Cyrus-Beck clipping of the ray by each edge's half plane
+                let (mut enter, mut exit) = (0.0f32, max_distance);
+                let mut normal = -direction;
+                for (i, a) in points.iter().enumerate() {
+                    let n = edge_normal(a, &points[(i + 1) % points.len()]);
+                    let denom = glm::dot(&n, direction);
+                    let distance = glm::dot(&n, &(a - origin));
+                    if denom.abs() < f32::EPSILON {
+                        if distance < 0.0 {
+                            return None;
+                        }
+                        continue;
+                    }
+                    let t = distance / denom;
+                    if denom < 0.0 && t > enter {
+                        enter = t;
+                        normal = n;
+                    } else if denom > 0.0 {
+                        exit = exit.min(t);
+                    }
+                    if enter > exit {
+                        return None;
+                    }
+                }
+                Some((enter, normal))
+            }
+        }
+    }
+}
+
+/// Collider shape transformed to world space
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorldShape2D {
+    Convex(ConvexShape2D),
+    /// tilemap's bottom left corner is at origin
+    Tilemap {
+        map: Rc<Tilemap>,
+        origin: glm::Vec2,
+    },
+}
+
+impl WorldShape2D {
+    /// Places shape in the XY plane, offset is in transform's local space.
+    /// Tilemaps are only moved, they ignore rotation and scale.
+    pub fn new(shape: &Shape2D, offset: &glm::Vec2, transform: &Transform) -> Self {
+        let to_world = |p: &glm::Vec2| transform.point_to_world(&glm::vec3(p.x, p.y, 0.0)).xy();
+        let polygon = |points: &[glm::Vec2]| {
+            let mut points = points
+                .iter()
+                .map(|p| to_world(&(p + offset)))
+                .collect::<Vec<_>>();
+            // mirroring scale flips the winding
+            if signed_area(&points) < 0.0 {
+                points.reverse();
+            }
+            // flat boxes (or zero scale) repeat points, their edges would have no normal
+            points.dedup_by(|a, b| glm::distance(a, b) <= f32::EPSILON);
+            while points.len() > 1
+                && glm::distance(&points[0], &points[points.len() - 1]) <= f32::EPSILON
+            {
+                points.pop();
+            }
+            match points.len() {
+                1 => WorldShape2D::Convex(ConvexShape2D::Circle {
+                    center: points[0],
+                    radius: 0.0,
+                }),
+                _ => WorldShape2D::Convex(ConvexShape2D::Polygon(points)),
+            }
+        };
+        match shape {
+            Shape2D::Circle { radius } => {
+                let scale = transform.scale().xy().abs();
+                WorldShape2D::Convex(ConvexShape2D::Circle {
+                    center: to_world(offset),
+                    radius: radius * scale.max(),
+                })
+            }
+            Shape2D::Box { half_extents: h } => polygon(&[
+                glm::vec2(-h.x, -h.y),
+                glm::vec2(h.x, -h.y),
+                glm::vec2(h.x, h.y),
+                glm::vec2(-h.x, h.y),
+            ]),
+            Shape2D::Polygon(p) => polygon(p.points()),
+            Shape2D::Tilemap(map) => WorldShape2D::Tilemap {
+                map: map.clone(),
+                origin: transform.position().xy() + offset,
+            },
+        }
+    }
+
+    /// Returns bounds in the XY plane (z is 0)
+    pub fn bounds(&self) -> Aabb {
+        let (min, max) = match self {
+            WorldShape2D::Convex(shape) => shape.bounds(),
+            WorldShape2D::Tilemap { map, origin } => {
+                let size = glm::vec2(map.width() as f32, map.height() as f32);
+                (*origin, origin + size.component_mul(map.tile_size()))
+            }
+        };
+        Aabb::new(glm::vec3(min.x, min.y, 0.0), glm::vec3(max.x, max.y, 0.0))
+    }
+
+    /// Returns convex parts of the shape overlapping box min-max with flag
+    /// telling if the part is a one way platform
+    pub fn parts(
+        &self,
+        min: &glm::Vec2,
+        max: &glm::Vec2,
+        one_way: bool,
+    ) -> Vec<(ConvexShape2D, bool)> {
+        match self {
+            WorldShape2D::Convex(shape) => vec![(shape.clone(), one_way)],
+            WorldShape2D::Tilemap { map, origin } => map
+                .shapes(origin, min, max)
+                .into_iter()
+                .map(|(shape, tile_one_way)| (shape, one_way || tile_one_way))
+                .collect(),
+        }
+    }
+
+    /// Returns the first hit of the ray, one way parts are hit only from above
+    pub fn raycast(
+        &self,
+        origin: &glm::Vec2,
+        direction: &glm::Vec2,
+        max_distance: f32,
+        one_way: bool,
+    ) -> Option<(f32, glm::Vec2)> {
+        let end = origin + direction * max_distance;
+        self.parts(&glm::min2(origin, &end), &glm::max2(origin, &end), one_way)
+            .iter()
+            .filter_map(|(part, one_way)| {
+                let (t, normal) = part.raycast(origin, direction, max_distance)?;
+                (!one_way || (t > 0.0 && normal.y >= ONE_WAY_NORMAL_Y)).then_some((t, normal))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+/// Returns outward normal of edge a-b of a counter-clockwise polygon
+fn edge_normal(a: &glm::Vec2, b: &glm::Vec2) -> glm::Vec2 {
+    let e = b - a;
+    glm::vec2(e.y, -e.x).normalize()
+}
+
+/// Positive for counter-clockwise polygons
+pub(crate) fn signed_area(points: &[glm::Vec2]) -> f32 {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+fn project(points: &[glm::Vec2], axis: &glm::Vec2) -> (f32, f32) {
+    points
+        .iter()
+        .map(|p| glm::dot(p, axis))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| {
+            (lo.min(d), hi.max(d))
+        })
+}
+
+/// Returns contact of two overlapping shapes, normal points from a to b
+pub fn contact(a: &ConvexShape2D, b: &ConvexShape2D) -> Option<Contact2D> {
+    match (a, b) {
+        (
+            ConvexShape2D::Circle {
+                center: ca,
+                radius: ra,
+            },
+            ConvexShape2D::Circle {
+                center: cb,
+                radius: rb,
+            },
+        ) => {
+            let d = cb - ca;
+            let distance = d.magnitude();
+            let depth = ra + rb - distance;
+            if depth < 0.0 {
+                return None;
+            }
+            let normal = match distance > f32::EPSILON {
+                true => d / distance,
+                false => glm::vec2(0.0, 1.0),
+            };
+            Some(Contact2D {
+                point: ca + normal * (ra - depth / 2.0),
+                normal,
+                depth,
+            })
+        }
+        (ConvexShape2D::Polygon(points), ConvexShape2D::Circle { center, radius }) => {
+            polygon_circle_contact(points, center, *radius)
+        }
+        (ConvexShape2D::Circle { center, radius }, ConvexShape2D::Polygon(points)) => {
+            polygon_circle_contact(points, center, *radius).map(Contact2D::flipped)
+        }
+        (ConvexShape2D::Polygon(a), ConvexShape2D::Polygon(b)) => polygon_contact(a, b),
+    }
+}
+
+fn polygon_circle_contact(
+    points: &[glm::Vec2],
+    center: &glm::Vec2,
+    radius: f32,
+) -> Option<Contact2D> {
+    let edges = (0..points.len()).map(|i| (points[i], points[(i + 1) % points.len()]));
+    let (separation, face) = edges
+        .clone()
+        .map(|(a, b)| {
+            let n = edge_normal(&a, &b);
+            (glm::dot(&n, &(center - a)), n)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))?;
+    if separation > radius {
+        return None;
+    }
+    if separation <= 0.0 {
+        // center is inside, push it out through the closest face
+        return Some(Contact2D {
+            point: center - face * separation,
+            normal: face,
+            depth: radius - separation,
+        });
+    }
+    let closest = edges
+        .map(|(a, b)| {
+            let ab = b - a;
+            let t = (glm::dot(&(center - a), &ab) / ab.magnitude_squared()).clamp(0.0, 1.0);
+            a + ab * t
+        })
+        .min_by(|p, q| glm::distance2(p, center).total_cmp(&glm::distance2(q, center)))?;
+    let d = center - closest;
+    let distance = d.magnitude();
+    if distance > radius {
+        return None;
+    }
+    let normal = match distance > f32::EPSILON {
+        true => d / distance,
+        false => face,
+    };
+    Some(Contact2D {
+        point: closest,
+        normal,
+        depth: radius - distance,
+    })
+}
+
+/// Separating axis test over edge normals of both polygons
+fn polygon_contact(a: &[glm::Vec2], b: &[glm::Vec2]) -> Option<Contact2D> {
+    let normals = |p: &[glm::Vec2]| {
+        (0..p.len())
+            .map(|i| edge_normal(&p[i], &p[(i + 1) % p.len()]))
+            .collect::<Vec<_>>()
+    };
+    let mut best: Option<(f32, glm::Vec2)> = None;
+    for axis in normals(a).into_iter().chain(normals(b)) {
+        let (min_a, max_a) = project(a, &axis);
+        let (min_b, max_b) = project(b, &axis);
+        // b on the positive side of a along axis or the other way round
+        let (overlap, axis) = match max_a - min_b <= max_b - min_a {
+            true => (max_a - min_b, axis),
+            false => (max_b - min_a, -axis),
+        };
+        if overlap < 0.0 {
+            return None;
+        }
+        if best.is_none_or(|(o, _)| overlap < o) {
+            best = Some((overlap, axis));
+        }
+    }
+    let (depth, normal) = best?;
+    // deepest points of b inside a, averaged so flat faces touch in their middle
+    let (lowest, _) = project(b, &normal);
+    let deepest = b
+        .iter()
+        .filter(|p| glm::dot(p, &normal) - lowest <= FACE_TOLERANCE)
+        .collect::<Vec<_>>();
+    let deepest = deepest.iter().copied().sum::<glm::Vec2>() / deepest.len() as f32;
+    Some(Contact2D {
+        point: deepest + normal * (depth / 2.0),
+        normal,
+        depth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, half: f32) -> ConvexShape2D {
+        ConvexShape2D::Polygon(vec![
+            glm::vec2(x - half, y - half),
+            glm::vec2(x + half, y - half),
+            glm::vec2(x + half, y + half),
+            glm::vec2(x - half, y + half),
+        ])
+    }
+
+    #[test]
+    fn convex_shapes_collide_along_smallest_overlap() {
+        let c = contact(&square(0.0, 0.0, 1.0), &square(1.5, 0.2, 1.0)).unwrap();
+        assert!((c.depth - 0.5).abs() < 1e-5);
+        assert_eq!(c.normal, glm::vec2(1.0, 0.0));
+        assert!(contact(&square(0.0, 0.0, 1.0), &square(2.5, 0.0, 1.0)).is_none());
+
+        let circle = ConvexShape2D::Circle {
+            center: glm::vec2(0.0, 1.3),
+            radius: 0.5,
+        };
+        let c = contact(&circle, &square(0.0, 0.0, 1.0)).unwrap();
+        assert!((c.depth - 0.2).abs() < 1e-5);
+        assert!(glm::distance(&c.normal, &glm::vec2(0.0, -1.0)) < 1e-5);
+        // close to a corner the normal points from the corner to the center
+        let circle = ConvexShape2D::Circle {
+            center: glm::vec2(1.3, 1.3),
+            radius: 0.5,
+        };
+        let c = contact(&square(0.0, 0.0, 1.0), &circle).unwrap();
+        assert!(glm::distance(&c.normal, &glm::vec2(1.0, 1.0).normalize()) < 1e-5);
+        let slope = ConvexShape2D::Polygon(vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(2.0, 0.0),
+            glm::vec2(2.0, 2.0),
+        ]);
+        let c = contact(&slope, &square(0.9, 1.1, 0.5)).unwrap();
+        assert!(glm::distance(&c.normal, &glm::vec2(-1.0, 1.0).normalize()) < 1e-5);
+    }
+
+    #[test]
+    fn rays_hit_shapes_and_one_way_parts_from_above() {
+        let ray_down = (glm::vec2(0.5, 5.0), glm::vec2(0.0, -1.0));
+        let (t, normal) = square(0.0, 0.0, 1.0)
+            .raycast(&ray_down.0, &ray_down.1, 10.0)
+            .unwrap();
+        assert!((t - 4.0).abs() < 1e-5);
+        assert_eq!(normal, glm::vec2(0.0, 1.0));
+        assert!(square(0.0, 0.0, 1.0)
+            .raycast(&ray_down.0, &ray_down.1, 3.0)
+            .is_none());
+        let circle = ConvexShape2D::Circle {
+            center: glm::vec2(0.5, 0.0),
+            radius: 2.0,
+        };
+        assert!((circle.raycast(&ray_down.0, &ray_down.1, 10.0).unwrap().0 - 3.0).abs() < 1e-5);
+
+        let platform = WorldShape2D::Convex(square(0.0, 0.0, 1.0));
+        assert!(platform
+            .raycast(&ray_down.0, &ray_down.1, 10.0, true)
+            .is_some());
+        let ray_up = glm::vec2(0.5, -5.0);
+        assert!(platform
+            .raycast(&ray_up, &-ray_down.1, 10.0, false)
+            .is_some());
+        assert!(platform
+            .raycast(&ray_up, &-ray_down.1, 10.0, true)
+            .is_none());
+    }
+    #[test]
+    fn flat_boxes_collide_with_polygons() {
+        let flat = Shape2D::Box {
+            half_extents: glm::vec2(1.0, 0.0),
+        };
+        let WorldShape2D::Convex(platform) =
+            WorldShape2D::new(&flat, &glm::Vec2::zeros(), &Transform::default())
+        else {
+            panic!("box should be convex");
+        };
+        assert!(matches!(platform, ConvexShape2D::Polygon(ref p) if p.len() == 2));
+        let c = contact(&platform, &square(0.0, 0.4, 0.5)).unwrap();
+        assert!((c.depth - 0.1).abs() < 1e-5);
+        assert!(glm::distance(&c.normal, &glm::vec2(0.0, 1.0)) < 1e-5);
+        assert!(contact(&platform, &square(0.0, 0.6, 0.5)).is_none());
+    }
+}
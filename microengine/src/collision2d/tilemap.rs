@@ -0,0 +1,178 @@
+//! Grid of collision tiles, used as a single collider of a tile based level
+
+use super::shapes::ConvexShape2D;
+use crate::error::{GameError, GameResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tile {
+    #[default]
+    Empty,
+    Solid,
+    /// can be jumped through from below and stood on from above
+    OneWay,
+    /// right triangle with the slope rising to the right
+    SlopeRight,
+    /// right triangle with the slope rising to the left
+    SlopeLeft,
+}
+
+impl Tile {
+    /// Returns tile for characters used by Tilemap::from_rows
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c {
+            '.' | ' ' => Some(Tile::Empty),
+            '#' => Some(Tile::Solid),
+            '-' => Some(Tile::OneWay),
+            '/' => Some(Tile::SlopeRight),
+            '\\' => Some(Tile::SlopeLeft),
+            _ => None,
+        }
+    }
+}
+
+/// Tiles are indexed from the bottom left corner, which is placed at collider's position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tilemap {
+    width: usize,
+    height: usize,
+    tile_size: glm::Vec2,
+    tiles: Vec<Tile>,
+}
+
+impl Tilemap {
+    pub fn new(width: usize, height: usize, tile_size: glm::Vec2) -> Self {
+        Tilemap {
+            width,
+            height,
+            tile_size,
+            tiles: vec![Tile::Empty; width * height],
+        }
+    }
+
+    /// Creates tilemap from rows of characters, the first row is the top one
+    /// '#' solid, '-' one way, '/' and '\' slopes, '.' or space empty
+    pub fn from_rows(rows: &[&str], tile_size: glm::Vec2) -> GameResult<Self> {
+        let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+        let mut map = Tilemap::new(width, rows.len(), tile_size);
+        for (n, row) in rows.iter().enumerate() {
+            let y = rows.len() - 1 - n;
+            for (x, c) in row.chars().enumerate() {
+                let tile = Tile::from_char(c).ok_or_else(|| {
+                    GameError::GameLogicError(format!("Unknown tile '{c}' in row {n}"))
+                })?;
+                map.set(x, y, tile)?;
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn tile_size(&self) -> &glm::Vec2 {
+        &self.tile_size
+    }
+
+    /// Returns Empty for tiles outside of the map
+    pub fn get(&self, x: usize, y: usize) -> Tile {
+        match x < self.width && y < self.height {
+            true => self.tiles[y * self.width + x],
+            false => Tile::Empty,
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, tile: Tile) -> GameResult {
+        if x >= self.width || y >= self.height {
+            return Err(GameError::GameLogicError(format!(
+                "Tile ({x}, {y}) is outside of {}x{} tilemap",
+                self.width, self.height
+            )));
+        }
+        self.tiles[y * self.width + x] = tile;
+        Ok(())
+    }
+
+    /// Returns coordinates of tile containing point given in map's local space
+    pub fn tile_at(&self, point: &glm::Vec2) -> Option<(usize, usize)> {
+        let cell = point.component_div(&self.tile_size).map(f32::floor);
+        let in_map = cell.x >= 0.0
+            && cell.y >= 0.0
+            && (cell.x as usize) < self.width
+            && (cell.y as usize) < self.height;
+        in_map.then_some((cell.x as usize, cell.y as usize))
+    }
+
+    /// Returns shapes of non empty tiles overlapping box min-max given in world space,
+    /// together with flag telling if the tile is one way
+    pub(crate) fn shapes(
+        &self,
+        origin: &glm::Vec2,
+        min: &glm::Vec2,
+        max: &glm::Vec2,
+    ) -> Vec<(ConvexShape2D, bool)> {
+        let to_cell = |p: &glm::Vec2| (p - origin).component_div(&self.tile_size).map(f32::floor);
+        let (lo, hi) = (to_cell(min), to_cell(max));
+        let outside =
+            hi.x < 0.0 || hi.y < 0.0 || lo.x >= self.width as f32 || lo.y >= self.height as f32;
+        if outside || self.tiles.is_empty() {
+            return Vec::new();
+        }
+        let (x0, y0) = (lo.x.max(0.0) as usize, lo.y.max(0.0) as usize);
+        let x1 = (hi.x as usize).min(self.width - 1);
+        let y1 = (hi.y as usize).min(self.height - 1);
+        let mut shapes = Vec::new();
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let tile = self.get(x, y);
+                let corner = origin + glm::vec2(x as f32, y as f32).component_mul(&self.tile_size);
+                let point =
+                    |u: f32, v: f32| corner + glm::vec2(u, v).component_mul(&self.tile_size);
+                let polygon = match tile {
+                    Tile::Empty => continue,
+                    Tile::Solid | Tile::OneWay => {
+                        vec![
+                            point(0.0, 0.0),
+                            point(1.0, 0.0),
+                            point(1.0, 1.0),
+                            point(0.0, 1.0),
+                        ]
+                    }
+                    Tile::SlopeRight => vec![point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0)],
+                    Tile::SlopeLeft => vec![point(0.0, 0.0), point(1.0, 0.0), point(0.0, 1.0)],
+                };
+                shapes.push((ConvexShape2D::Polygon(polygon), tile == Tile::OneWay));
+            }
+        }
+        shapes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tilemap_is_built_from_rows_bottom_up() {
+        let map = Tilemap::from_rows(&["..-", "/##"], glm::vec2(2.0, 1.0)).unwrap();
+        assert_eq!((map.width(), map.height()), (3, 2));
+        assert_eq!(map.get(0, 0), Tile::SlopeRight);
+        assert_eq!(map.get(2, 1), Tile::OneWay);
+        assert_eq!(map.get(5, 5), Tile::Empty);
+        assert_eq!(map.tile_at(&glm::vec2(4.5, 1.5)), Some((2, 1)));
+        assert_eq!(map.tile_at(&glm::vec2(-0.5, 0.5)), None);
+        assert!(Tilemap::from_rows(&["x"], glm::vec2(1.0, 1.0)).is_err());
+
+        let origin = glm::vec2(10.0, 0.0);
+        let shapes = map.shapes(&origin, &glm::vec2(11.0, 0.2), &glm::vec2(14.5, 1.5));
+        let one_way = shapes.iter().filter(|(_, one_way)| *one_way).count();
+        assert_eq!((shapes.len(), one_way), (4, 1));
+        assert!(map
+            .shapes(&origin, &glm::vec2(0.0, 0.0), &glm::vec2(9.0, 9.0))
+            .is_empty());
+    }
+}
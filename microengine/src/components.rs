@@ -1,2 +1,3 @@
+pub mod animation;
 pub mod camera;
 pub mod transform;
@@ -0,0 +1,614 @@
+//! Keyframe and skeletal animation.
+//! AnimationClip holds translation, rotation and scale tracks of joints, a Pose is the
+//! local Transform of each joint. A clip can animate a single gameobject's Transform
+//! (tracks of joint 0, see AnimationClip::sample_transform) or a Skeleton, whose
+//! skinning_matrices are meant for gl_utils::skinning shader:
+//!
+//! ```ignore
+//! animator.update(ctx.time.delta_time() as f32);
+//! let mut pose = skeleton.rest_pose();
+//! animator.sample(&mut pose);
+//! program.set_uniform(JOINTS_UNIFORM, &Uniform::Mat4Array(skeleton.skinning_matrices(&pose)));
+//! ```
+//!
+//! Skinned meshes move outside of their bind pose bounds, so their DrawCommands
+//! should have bounds set to None or to a box enclosing the whole animation.
+//! Animator is a state machine switching between clips with crossfades
+//! when its transitions' conditions are met.
+
+use super::transform::Transform;
+use crate::{
+    error::{GameError, GameResult},
+    tween::Tweenable,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// value jumps to the next keyframe's value when its time is reached
+    Step,
+    #[default]
+    Linear,
+}
+
+/// Keyframes of a single value, sorted by time
+#[derive(Debug, Clone)]
+pub struct Track<T: Tweenable> {
+    times: Vec<f32>,
+    values: Vec<T>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Tweenable> Track<T> {
+    /// Creates track from (time, value) keyframes given in any order
+    pub fn new(mut keyframes: Vec<(f32, T)>) -> Self {
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (times, values) = keyframes.into_iter().unzip();
+        Track {
+            times,
+            values,
+            interpolation: Interpolation::Linear,
+        }
+    }
+
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Returns time of the last keyframe
+    pub fn duration(&self) -> f32 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns value at given time, values before first and after last keyframe are clamped
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let next = self.times.partition_point(|&t| t <= time);
+        if next == 0 {
+            return self.values.first().copied();
+        }
+        if next == self.times.len() || self.interpolation == Interpolation::Step {
+            return Some(self.values[next - 1]);
+        }
+        let (start, end) = (self.times[next - 1], self.times[next]);
+        let t = (time - start) / (end - start);
+        Some(T::interpolate(
+            &self.values[next - 1],
+            &self.values[next],
+            t,
+        ))
+    }
+}
+
+/// Tracks animating local transform of a single joint
+#[derive(Debug, Clone)]
+pub struct JointTrack {
+    /// index of animated joint, 0 for clips animating a single Transform
+    pub joint: usize,
+    pub translation: Option<Track<glm::Vec3>>,
+    pub rotation: Option<Track<glm::Quat>>,
+    pub scale: Option<Track<glm::Vec3>>,
+}
+
+impl JointTrack {
+    pub fn new(joint: usize) -> Self {
+        JointTrack {
+            joint,
+            translation: None,
+            rotation: None,
+            scale: None,
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        let t = self.translation.as_ref().map_or(0.0, |t| t.duration());
+        let r = self.rotation.as_ref().map_or(0.0, |t| t.duration());
+        let s = self.scale.as_ref().map_or(0.0, |t| t.duration());
+        t.max(r).max(s)
+    }
+
+    /// Overwrites animated components of transform with values at given time
+    pub fn apply(&self, time: f32, transform: &mut Transform) {
+        if let Some(v) = self.translation.as_ref().and_then(|t| t.sample(time)) {
+            *transform.position_mut() = v;
+        }
+        if let Some(v) = self.rotation.as_ref().and_then(|t| t.sample(time)) {
+            *transform.rotation_mut() = v;
+        }
+        if let Some(v) = self.scale.as_ref().and_then(|t| t.sample(time)) {
+            *transform.scale_mut() = v;
+        }
+    }
+}
+
+/// Local transform of each joint of a skeleton
+pub type Pose = Vec<Transform>;
+
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    pub name: String,
+    pub tracks: Vec<JointTrack>,
+    /// length in seconds, time of the last keyframe by default
+    pub duration: f32,
+}
+
+impl AnimationClip {
+    pub fn new(name: &str, tracks: Vec<JointTrack>) -> Self {
+        AnimationClip {
+            name: name.into(),
+            duration: tracks.iter().map(|t| t.duration()).fold(0.0, f32::max),
+            tracks,
+        }
+    }
+
+    /// Writes values at given time to the pose, joints without tracks are not changed
+    pub fn sample(&self, time: f32, pose: &mut [Transform]) {
+        for track in self.tracks.iter() {
+            if let Some(transform) = pose.get_mut(track.joint) {
+                track.apply(time, transform);
+            }
+        }
+    }
+
+    /// Animates a single transform with tracks of joint 0
+    pub fn sample_transform(&self, time: f32, transform: &mut Transform) {
+        self.sample(time, std::slice::from_mut(transform));
+    }
+
+    /// Wraps time to clip's duration for looping clips and clamps it otherwise
+    pub fn wrap_time(&self, time: f32, looping: bool) -> f32 {
+        match looping && self.duration > 0.0 {
+            true => time.rem_euclid(self.duration),
+            false => time.clamp(0.0, self.duration),
+        }
+    }
+}
+
+/// Returns pose between a and b, weight 0 gives a and 1 gives b
+pub fn blend_poses(a: &[Transform], b: &[Transform], weight: f32) -> Pose {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| a.slerp(b, weight))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Joint {
+    pub name: String,
+    /// parent has to be placed before its children in skeleton
+    pub parent: Option<usize>,
+    /// local transform in bind pose
+    pub rest: Transform,
+    /// transforms mesh from model space to joint's space, None means it is computed
+    /// from the rest pose
+    pub inverse_bind: Option<glm::Mat4>,
+}
+
+impl Joint {
+    pub fn new(name: &str, parent: Option<usize>, rest: Transform) -> Self {
+        Joint {
+            name: name.into(),
+            parent,
+            rest,
+            inverse_bind: None,
+        }
+    }
+}
+
+/// Joint hierarchy of a skinned mesh
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+    inverse_binds: Vec<glm::Mat4>,
+}
+
+impl Skeleton {
+    pub fn new(joints: Vec<Joint>) -> GameResult<Self> {
+        if joints.len() > gl_utils::skinning::MAX_JOINTS {
+            return Err(GameError::GameLogicError(format!(
+                "skeleton has {} joints, at most {} are supported",
+                joints.len(),
+                gl_utils::skinning::MAX_JOINTS
+            )));
+        }
+        if let Some((i, j)) = joints
+            .iter()
+            .enumerate()
+            .find(|(i, j)| j.parent.is_some_and(|p| p >= *i))
+        {
+            return Err(GameError::GameLogicError(format!(
+                "joint {i} ('{}') has to be placed after its parent",
+                j.name
+            )));
+        }
+        let mut skeleton = Skeleton {
+            joints,
+            inverse_binds: Vec::new(),
+        };
+        let rest = skeleton.global_matrices(&skeleton.rest_pose());
+        skeleton.inverse_binds = skeleton
+            .joints
+            .iter()
+            .zip(rest.iter())
+            .map(|(j, m)| j.inverse_bind.unwrap_or_else(|| glm::inverse(m)))
+            .collect();
+        Ok(skeleton)
+    }
+
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    pub fn len(&self) -> usize {
+        self.joints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.joints.is_empty()
+    }
+
+    pub fn joint_index(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|j| j.name == name)
+    }
+
+    /// Returns bind pose, a good base for sampling clips which don't animate all joints
+    pub fn rest_pose(&self) -> Pose {
+        self.joints.iter().map(|j| j.rest.clone()).collect()
+    }
+
+    /// Returns model space transform of each joint in given pose
+    pub fn global_matrices(&self, pose: &[Transform]) -> Vec<glm::Mat4> {
+        let mut globals: Vec<glm::Mat4> = Vec::with_capacity(self.joints.len());
+        for (joint, local) in self.joints.iter().zip(pose.iter()) {
+            let local = local.calculate_local_to_world_matrix();
+            let global = match joint.parent {
+                Some(p) => globals[p] * local,
+                None => local,
+            };
+            globals.push(global);
+        }
+        globals
+    }
+
+    /// Returns matrices moving vertices from bind pose to given pose,
+    /// upload them as gl_utils::skinning::JOINTS_UNIFORM
+    pub fn skinning_matrices(&self, pose: &[Transform]) -> Vec<glm::Mat4> {
+        self.global_matrices(pose)
+            .iter()
+            .zip(self.inverse_binds.iter())
+            .map(|(global, inverse_bind)| global * inverse_bind)
+            .collect()
+    }
+}
+
+/// Condition of animator transition
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Bool(String, bool),
+    Greater(String, f32),
+    Less(String, f32),
+    /// met once after Animator::set_trigger, taking the transition resets the trigger
+    Trigger(String),
+    /// current clip played to its end (at least once for looping states)
+    Finished,
+}
+
+#[derive(Debug, Clone)]
+pub struct Transition {
+    /// None means transition can be taken from any other state
+    pub from: Option<String>,
+    pub to: String,
+    /// all conditions have to be met, empty list means transition is taken immediately
+    pub conditions: Vec<Condition>,
+    /// crossfade length in seconds
+    pub duration: f32,
+}
+
+impl Transition {
+    pub fn new(from: Option<&str>, to: &str, conditions: Vec<Condition>, duration: f32) -> Self {
+        Transition {
+            from: from.map(Into::into),
+            to: to.into(),
+            conditions,
+            duration,
+        }
+    }
+}
+
+struct State {
+    name: String,
+    clip: Rc<AnimationClip>,
+    looping: bool,
+    speed: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Playback {
+    state: usize,
+    time: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    from: Playback,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Animation state machine, each state plays a clip
+#[derive(Default)]
+pub struct Animator {
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+    params: HashMap<String, f32>,
+    triggers: HashSet<String>,
+    current: Option<Playback>,
+    fade: Option<Fade>,
+}
+
+impl Animator {
+    /// Adds state playing given clip, the first added state is played at start
+    pub fn add_state(&mut self, name: &str, clip: Rc<AnimationClip>, looping: bool) {
+        self.states.push(State {
+            name: name.into(),
+            clip,
+            looping,
+            speed: 1.0,
+        });
+        if self.current.is_none() {
+            self.current = Some(Playback {
+                state: self.states.len() - 1,
+                time: 0.0,
+            });
+        }
+    }
+
+    pub fn set_state_speed(&mut self, name: &str, speed: f32) -> GameResult {
+        let state = self.state_index(name)?;
+        self.states[state].speed = speed;
+        Ok(())
+    }
+
+    /// Adds transition, transitions are checked in order they were added
+    pub fn add_transition(&mut self, transition: Transition) {
+        self.transitions.push(transition);
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.params.insert(name.into(), value);
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set_float(name, value as i32 as f32);
+    }
+
+    pub fn set_trigger(&mut self, name: &str) {
+        self.triggers.insert(name.into());
+    }
+
+    pub fn float(&self, name: &str) -> f32 {
+        self.params.get(name).copied().unwrap_or(0.0)
+    }
+
+    pub fn current_state(&self) -> Option<&str> {
+        self.current.map(|c| self.states[c.state].name.as_str())
+    }
+
+    /// Returns time since current state started
+    pub fn state_time(&self) -> f32 {
+        self.current.map_or(0.0, |c| c.time)
+    }
+
+    /// Returns true while crossfading between two states
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Switches to given state, fading from the current one for fade seconds
+    pub fn play(&mut self, name: &str, fade: f32) -> GameResult {
+        let state = self.state_index(name)?;
+        self.fade = match (self.current, fade > 0.0) {
+            (Some(from), true) => Some(Fade {
+                from,
+                elapsed: 0.0,
+                duration: fade,
+            }),
+            _ => None,
+        };
+        self.current = Some(Playback { state, time: 0.0 });
+        Ok(())
+    }
+
+    fn state_index(&self, name: &str) -> GameResult<usize> {
+        self.states
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| GameError::GameLogicError(format!("unknown animator state '{name}'")))
+    }
+
+    fn condition_met(&self, condition: &Condition, current: &Playback) -> bool {
+        match condition {
+            Condition::Bool(name, v) => (self.float(name) != 0.0) == *v,
+            Condition::Greater(name, v) => self.float(name) > *v,
+            Condition::Less(name, v) => self.float(name) < *v,
+            Condition::Trigger(name) => self.triggers.contains(name),
+            Condition::Finished => current.time >= self.states[current.state].clip.duration,
+        }
+    }
+
+    /// Advances playback and takes the first transition whose conditions are met
+    pub fn update(&mut self, delta_time: f32) -> GameResult {
+        let Some(mut current) = self.current else {
+            return Ok(());
+        };
+        current.time += delta_time * self.states[current.state].speed;
+        self.current = Some(current);
+        if let Some(fade) = self.fade.as_mut() {
+            fade.from.time += delta_time * self.states[fade.from.state].speed;
+            fade.elapsed += delta_time;
+            if fade.elapsed >= fade.duration {
+                self.fade = None;
+            }
+        }
+        let name = &self.states[current.state].name;
+        let transition = self.transitions.iter().find(|t| {
+            t.from.as_ref().map_or(&t.to != name, |from| from == name)
+                && t.conditions.iter().all(|c| self.condition_met(c, &current))
+        });
+        if let Some(transition) = transition.cloned() {
+            for condition in transition.conditions.iter() {
+                if let Condition::Trigger(name) = condition {
+                    self.triggers.remove(name);
+                }
+            }
+            self.play(&transition.to, transition.duration)?;
+        }
+        Ok(())
+    }
+
+    /// Writes current animation to the pose, joints not animated by the clips are not changed
+    pub fn sample(&self, pose: &mut Pose) {
+        let Some(current) = self.current else {
+            return;
+        };
+        let sample = |playback: &Playback, pose: &mut [Transform]| {
+            let state = &self.states[playback.state];
+            let time = state.clip.wrap_time(playback.time, state.looping);
+            state.clip.sample(time, pose);
+        };
+        match self.fade {
+            Some(fade) => {
+                let mut from = pose.clone();
+                sample(&fade.from, &mut from);
+                sample(&current, pose);
+                *pose = blend_poses(&from, pose, fade.elapsed / fade.duration);
+            }
+            None => sample(&current, pose),
+        }
+    }
+
+    /// Animates a single transform with tracks of joint 0
+    pub fn sample_transform(&self, transform: &mut Transform) {
+        let mut pose = vec![transform.clone()];
+        self.sample(&mut pose);
+        *transform = pose.swap_remove(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::assert_close;
+
+    fn moving_clip(name: &str, to: f32) -> Rc<AnimationClip> {
+        let mut track = JointTrack::new(0);
+        track.translation = Some(Track::new(vec![
+            (1.0, glm::vec3(to, 0.0, 0.0)),
+            (0.0, glm::Vec3::zeros()),
+        ]));
+        Rc::new(AnimationClip::new(name, vec![track]))
+    }
+
+    #[test]
+    fn tracks_interpolate_and_clamp() {
+        let track = Track::new(vec![(0.0, 0.0f32), (1.0, 2.0), (3.0, 0.0)]);
+        assert_eq!(track.sample(-1.0), Some(0.0));
+        assert_eq!(track.sample(0.5), Some(1.0));
+        assert_eq!(track.sample(2.0), Some(1.0));
+        assert_eq!(track.sample(5.0), Some(0.0));
+        let track = track.with_interpolation(Interpolation::Step);
+        assert_eq!(track.sample(0.99), Some(0.0));
+        assert_eq!(track.sample(1.0), Some(2.0));
+        assert_eq!(Track::<f32>::new(vec![]).sample(1.0), None);
+    }
+
+    #[test]
+    fn clip_drives_transform() {
+        let clip = moving_clip("move", 4.0);
+        assert_eq!(clip.duration, 1.0);
+        let mut t = Transform::default();
+        clip.sample_transform(clip.wrap_time(1.25, true), &mut t);
+        assert_close(t.position(), &glm::vec3(1.0, 0.0, 0.0));
+        clip.sample_transform(clip.wrap_time(1.25, false), &mut t);
+        assert_close(t.position(), &glm::vec3(4.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn skinning_matrices_follow_joint_hierarchy() {
+        let mut child_rest = Transform::default();
+        *child_rest.position_mut() = glm::vec3(0.0, 1.0, 0.0);
+        let skeleton = Skeleton::new(vec![
+            Joint::new("root", None, Transform::default()),
+            Joint::new("child", Some(0), child_rest),
+        ])
+        .unwrap();
+        let mut pose = skeleton.rest_pose();
+        for m in skeleton.skinning_matrices(&pose) {
+            assert!(glm::abs(&(m - glm::Mat4::identity())).max() < 1e-4);
+        }
+        pose[0].rotate(
+            glm::Vec3::z(),
+            std::f32::consts::FRAC_PI_2,
+            crate::components::transform::Space::World,
+        );
+        let skin = skeleton.skinning_matrices(&pose);
+        // vertex at child's tip is rotated around root
+        let tip = skin[1]
+            .transform_point(&glm::vec3(0.0, 2.0, 0.0).into())
+            .coords;
+        assert_close(&tip, &glm::vec3(-2.0, 0.0, 0.0));
+        let wrong_order = vec![
+            Joint::new("child", Some(1), Transform::default()),
+            Joint::new("root", None, Transform::default()),
+        ];
+        assert!(Skeleton::new(wrong_order).is_err());
+    }
+
+    #[test]
+    fn animator_transitions_and_crossfades() {
+        let mut animator = Animator::default();
+        animator.add_state("idle", moving_clip("idle", 0.0), true);
+        animator.add_state("walk", moving_clip("walk", 4.0), true);
+        animator.add_state("jump", moving_clip("jump", 8.0), false);
+        animator.add_transition(Transition::new(
+            Some("idle"),
+            "walk",
+            vec![Condition::Greater("speed".into(), 0.1)],
+            1.0,
+        ));
+        animator.add_transition(Transition::new(
+            None,
+            "jump",
+            vec![Condition::Trigger("jump".into())],
+            0.0,
+        ));
+        animator.add_transition(Transition::new(
+            Some("jump"),
+            "idle",
+            vec![Condition::Finished],
+            0.0,
+        ));
+        animator.update(0.5).unwrap();
+        assert_eq!(animator.current_state(), Some("idle"));
+        animator.set_float("speed", 1.0);
+        animator.update(0.0).unwrap();
+        assert_eq!(animator.current_state(), Some("walk"));
+        animator.update(0.5).unwrap();
+        let mut pose = vec![Transform::default()];
+        animator.sample(&mut pose);
+        // halfway between idle (0) and walk at 0.5s (2)
+        assert_close(pose[0].position(), &glm::vec3(1.0, 0.0, 0.0));
+        animator.set_trigger("jump");
+        animator.update(0.6).unwrap();
+        assert_eq!(animator.current_state(), Some("jump"));
+        assert!(!animator.is_fading());
+        animator.update(0.5).unwrap();
+        assert_eq!(animator.current_state(), Some("jump"));
+        animator.update(0.5).unwrap();
+        assert_eq!(animator.current_state(), Some("idle"));
+        assert!(animator.play("run", 0.0).is_err());
+    }
+}
@@ -1,17 +1,58 @@
 /// Basic camera component implementation
 ///
 use super::transform::Transform;
+use crate::geometry::{Frustum, Ray};
 
-#[derive(Clone, Copy)]
+/// What camera clears in its viewport before rendering
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ClearFlags {
+    /// keep whatever was rendered before, e.g. by cameras with lower priority
+    Nothing,
+    /// clear only depth, so camera draws on top of previous cameras
+    #[default]
+    DepthOnly,
+    /// clear depth and fill viewport with given RGBA color
+    Color(glm::Vec4),
+}
+
+/// Decides which part of the world orthographic camera shows
+/// All modes are centered at camera position except Bounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrthographicMode {
+    /// shows size world units vertically, width follows viewport's aspect ratio
+    Size(f32),
+    /// shows given (left, right, bottom, top) bounds relative to camera,
+    /// stretched to the viewport
+    Bounds {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+    },
+    /// one world unit takes pixels_per_unit * zoom screen pixels, so visible area grows
+    /// with the window instead of scaling sprites; camera position is snapped to pixels
+    PixelPerfect { pixels_per_unit: f32, zoom: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ProjectionType {
-    Ortographic,
+    Ortographic(OrthographicMode),
     Perspective { fov: f32 },
 }
 
 pub struct Camera {
     pub transform: Transform,
     pub projection: Projection,
+    /// normalized (x, y, width, height) of the window, (0, 0) is bottom-left corner
+    pub viewport: glm::Vec4,
+    /// cameras with lower priority are rendered first
+    pub priority: i32,
+    pub clear: ClearFlags,
+    /// bit n set means gameobjects from layer n are rendered by this camera
+    pub culling_mask: u32,
     window_dim: (f32, f32),
+    /// dimensions projection was last updated with
+    viewport_dim: (f32, f32),
 }
 
 impl Camera {
@@ -23,83 +64,260 @@ impl Camera {
         width: f32,
         height: f32,
     ) -> Self {
+        let mut projection = Projection::new(projection, near_clip_plane, far_clip_plane);
+        projection.update(width, height);
         Self {
             transform: Transform::default(),
-            projection: Projection::new(projection, near_clip_plane, far_clip_plane),
+            projection,
+            viewport: glm::vec4(0.0, 0.0, 1.0, 1.0),
+            priority: 0,
+            clear: ClearFlags::default(),
+            culling_mask: u32::MAX,
             window_dim: (width, height),
+            viewport_dim: (width, height),
         }
     }
 
     /// Returns world to projection transformation matrix
     pub fn world_to_projection_matrix(&self) -> glm::Mat4 {
-        let inverse_angle = glm::quat_angle(&self.transform.rotation) * -1.0;
-        let inverse_rotation =
-            glm::quat_angle_axis(inverse_angle, &glm::quat_axis(&self.transform.rotation));
-        let view = glm::translation(&(self.transform.position() * -1.0));
-        let view = glm::quat_to_mat4(&inverse_rotation) * view;
+        let mut eye = self.transform.clone();
+        *eye.position_mut() = self.projection.snap_to_pixels(self.transform.position());
+        let view = eye.world_to_local();
         let projection = self.projection.projection_matrix();
         projection * view
     }
 
-    /// Should be called each time window dimensions change
+    /// Updates projection to given size of camera's viewport in pixels,
+    /// use update_viewport_projection when window dimensions change
     pub fn update_projection(&mut self, width: f32, height: f32) {
-        if self.window_dim != (width, height) {
-            self.window_dim = (width, height);
+        if self.viewport_dim != (width, height) {
+            self.viewport_dim = (width, height);
+            // empty viewport keeps the last window size, so set_viewport can restore it
+            if self.viewport.z > 0.0 && self.viewport.w > 0.0 {
+                self.window_dim = (width / self.viewport.z, height / self.viewport.w);
+            }
             self.projection.update(width, height);
         }
     }
 
+    /// Updates projection using size of camera's viewport in a window of given dimensions,
+    /// should be called each time window dimensions change
+    pub fn update_viewport_projection(&mut self, window_width: f32, window_height: f32) {
+        self.update_projection(
+            window_width * self.viewport.z,
+            window_height * self.viewport.w,
+        );
+    }
+
+    /// Sets viewport to a normalized rect, e.g. (0.0, 0.0, 0.5, 1.0) is left half of the window
+    pub fn set_viewport(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let (window_width, window_height) = self.window_dim;
+        self.viewport = glm::vec4(x, y, width, height);
+        self.update_viewport_projection(window_width, window_height);
+    }
+
+    /// Returns volume visible by the camera, use it to skip drawing off-screen objects
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(&self.world_to_projection_matrix())
+    }
+
+    /// Converts window pixel coordinates (origin in top-left corner, like mouse.position)
+    /// to viewport coordinates ((0, 0) is bottom-left and (1, 1) top-right corner of viewport)
+    pub fn screen_to_viewport_point(&self, point: (f32, f32)) -> glm::Vec2 {
+        let x = point.0 / self.window_dim.0;
+        let y = 1.0 - point.1 / self.window_dim.1;
+        glm::vec2(
+            (x - self.viewport.x) / self.viewport.z,
+            (y - self.viewport.y) / self.viewport.w,
+        )
+    }
+
+    /// Converts viewport coordinates to window pixel coordinates (origin in top-left corner)
+    pub fn viewport_to_screen_point(&self, point: &glm::Vec2) -> (f32, f32) {
+        let x = self.viewport.x + point.x * self.viewport.z;
+        let y = self.viewport.y + point.y * self.viewport.w;
+        (x * self.window_dim.0, (1.0 - y) * self.window_dim.1)
+    }
+
+    /// Returns distance of point from camera along its front vector
+    fn depth(&self, point: &glm::Vec3) -> f32 {
+        glm::dot(&(point - self.transform.position()), &self.front())
+    }
+
+    /// Projects world point to viewport coordinates, z is its depth in world units
+    /// (distance from camera along front vector), negative if point is behind camera
+    pub fn world_to_viewport_point(&self, point: &glm::Vec3) -> glm::Vec3 {
+        let clip = self.world_to_projection_matrix() * glm::vec4(point.x, point.y, point.z, 1.0);
+        let ndc = clip.xy() / clip.w;
+        glm::vec3((ndc.x + 1.0) / 2.0, (ndc.y + 1.0) / 2.0, self.depth(point))
+    }
+
+    /// Projects world point to window pixel coordinates (origin in top-left corner)
+    /// z is its depth in world units
+    pub fn world_to_screen(&self, point: &glm::Vec3) -> glm::Vec3 {
+        let v = self.world_to_viewport_point(point);
+        let (x, y) = self.viewport_to_screen_point(&v.xy());
+        glm::vec3(x, y, v.z)
+    }
+
+    /// Returns ray going from near clip plane through given viewport point
+    pub fn viewport_point_to_ray(&self, point: &glm::Vec2) -> Ray {
+        let inverse = glm::inverse(&self.world_to_projection_matrix());
+        let unproject = |z: f32| {
+            let p = inverse * glm::vec4(point.x * 2.0 - 1.0, point.y * 2.0 - 1.0, z, 1.0);
+            p.xyz() / p.w
+        };
+        let near = unproject(-1.0);
+        Ray::new(near, unproject(1.0) - near)
+    }
+
+    /// Returns ray going from near clip plane through given window pixel, use it for picking:
+    /// camera.screen_point_to_ray(ctx.input.mouse.position)
+    pub fn screen_point_to_ray(&self, point: (f32, f32)) -> Ray {
+        self.viewport_point_to_ray(&self.screen_to_viewport_point(point))
+    }
+
+    /// Returns world point under given viewport point (z is ignored) at given depth
+    pub fn viewport_to_world(&self, point: &glm::Vec2, depth: f32) -> glm::Vec3 {
+        let ray = self.viewport_point_to_ray(point);
+        let t = (depth - self.depth(&ray.origin)) / glm::dot(&ray.direction, &self.front());
+        ray.point_at(t)
+    }
+
+    /// Returns world point under given window pixel at given depth
+    /// (distance from camera along its front vector)
+    pub fn screen_to_world(&self, point: (f32, f32), depth: f32) -> glm::Vec3 {
+        self.viewport_to_world(&self.screen_to_viewport_point(point), depth)
+    }
+
+    /// Checks if objects from given layer are rendered by this camera
+    pub fn renders_layer(&self, layer: usize) -> bool {
+        layer_visible(self.culling_mask, layer)
+    }
+
     pub fn front(&self) -> glm::Vec3 {
-        self.transform.vector_to_world(&(-glm::Vec3::z()))
+        self.transform.forward()
     }
 
     pub fn up(&self) -> glm::Vec3 {
-        self.transform.vector_to_world(&(glm::Vec3::y()))
+        self.transform.up()
     }
 
     pub fn right(&self) -> glm::Vec3 {
-        self.transform.vector_to_world(&(glm::Vec3::x()))
+        self.transform.right()
     }
 
+    /// Returns aspect ratio of camera's viewport
     pub fn aspect(&self) -> f32 {
-        self.window_dim.0 / self.window_dim.1
+        (self.window_dim.0 * self.viewport.z) / (self.window_dim.1 * self.viewport.w)
     }
 }
 
+/// layers above 31 can't be culled
+pub(crate) fn layer_visible(mask: u32, layer: usize) -> bool {
+    layer >= 32 || mask & (1 << layer) != 0
+}
+
 pub struct Projection {
     pub p_type: ProjectionType,
     pub z_near: f32,
     pub z_far: f32,
+    /// viewport dimensions in pixels
+    dim: (f32, f32),
     matrix: glm::Mat4,
 }
 
 impl Projection {
     pub fn new(p_type: ProjectionType, z_near: f32, z_far: f32) -> Self {
-        Self {
+        let mut projection = Self {
             p_type,
             z_near,
             z_far,
-            matrix: match p_type {
-                ProjectionType::Ortographic => glm::ortho(0.0, 800.0, 0.0, 600.0, z_near, z_far),
-                ProjectionType::Perspective { fov } => glm::perspective(
-                    1.33,
-                    glm::radians(&glm::Vec1::new(fov)).x as f32,
-                    z_near,
-                    z_far,
-                ),
-            },
-        }
+            dim: (800.0, 600.0),
+            matrix: glm::Mat4::identity(),
+        };
+        projection.matrix = projection.calculate();
+        projection
     }
 
+    /// Recalculates projection for viewport of given dimensions (in pixels)
     pub fn update(&mut self, width: f32, height: f32) {
-        // TODO! update fov?
-        self.matrix = match self.p_type {
-            ProjectionType::Ortographic => {
-                glm::ortho(0.0, width, 0.0, height, self.z_near, self.z_far)
+        self.dim = (width.max(1.0), height.max(1.0));
+        self.matrix = self.calculate();
+    }
+
+    /// Changes projection type, e.g. to zoom orthographic camera
+    pub fn set_type(&mut self, p_type: ProjectionType) {
+        self.p_type = p_type;
+        self.matrix = self.calculate();
+    }
+
+    /// Returns (left, right, bottom, top) bounds of orthographic projection
+    /// relative to camera position, None for perspective projection
+    pub fn orthographic_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        let (width, height) = self.dim;
+        let (half_w, half_h) = match self.p_type {
+            ProjectionType::Ortographic(OrthographicMode::PixelPerfect { .. }) => {
+                let scale = self.pixel_scale();
+                // bounds span exactly one world pixel per screen pixel, in odd sized
+                // viewports the center is shifted by half a pixel instead of stretching
+                let (left, bottom) = ((width / 2.0).floor(), (height / 2.0).floor());
+                return Some((
+                    -left / scale,
+                    (width - left) / scale,
+                    -bottom / scale,
+                    (height - bottom) / scale,
+                ));
+            }
+            ProjectionType::Perspective { .. } => return None,
+            ProjectionType::Ortographic(OrthographicMode::Bounds {
+                left,
+                right,
+                bottom,
+                top,
+            }) => return Some((left, right, bottom, top)),
+            ProjectionType::Ortographic(OrthographicMode::Size(size)) => {
+                (size / 2.0 * width / height, size / 2.0)
+            }
+        };
+        Some((-half_w, half_w, -half_h, half_h))
+    }
+
+    /// Screen pixels per world unit in PixelPerfect mode
+    fn pixel_scale(&self) -> f32 {
+        match self.p_type {
+            ProjectionType::Ortographic(OrthographicMode::PixelPerfect {
+                pixels_per_unit,
+                zoom,
+            }) => pixels_per_unit * zoom.max(1) as f32,
+            _ => 1.0,
+        }
+    }
+
+    /// Rounds position to whole screen pixels in PixelPerfect mode
+    pub fn snap_to_pixels(&self, position: &glm::Vec3) -> glm::Vec3 {
+        match self.p_type {
+            ProjectionType::Ortographic(OrthographicMode::PixelPerfect { .. }) => {
+                let scale = self.pixel_scale();
+                glm::vec3(
+                    (position.x * scale).round() / scale,
+                    (position.y * scale).round() / scale,
+                    position.z,
+                )
+            }
+            _ => *position,
+        }
+    }
+
+    fn calculate(&self) -> glm::Mat4 {
+        match self.p_type {
+            ProjectionType::Ortographic(_) => {
+                let (left, right, bottom, top) = self.orthographic_bounds().unwrap_or_default();
+                glm::ortho(left, right, bottom, top, self.z_near, self.z_far)
             }
             ProjectionType::Perspective { fov } => glm::perspective(
-                width / height,
-                glm::radians(&glm::Vec1::new(fov)).x as f32,
+                self.dim.0 / self.dim.1,
+                glm::radians(&glm::Vec1::new(fov)).x,
                 self.z_near,
                 self.z_far,
             ),
@@ -110,3 +328,105 @@ impl Projection {
         &self.matrix
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::assert_close;
+
+    fn perspective_camera() -> Camera {
+        let mut camera = Camera::new(
+            ProjectionType::Perspective { fov: 60.0 },
+            0.1,
+            100.0,
+            800.0,
+            600.0,
+        );
+        camera.transform.position_mut().z = 5.0;
+        camera
+    }
+
+    #[test]
+    fn screen_center_ray_goes_along_front() {
+        let camera = perspective_camera();
+        let ray = camera.screen_point_to_ray((400.0, 300.0));
+        assert_close(&ray.direction, &camera.front());
+        assert_close(
+            &camera.screen_to_world((400.0, 300.0), 5.0),
+            &glm::Vec3::zeros(),
+        );
+    }
+
+    #[test]
+    fn world_and_screen_conversions_are_inverse() {
+        let mut camera = perspective_camera();
+        camera.set_viewport(0.5, 0.0, 0.5, 1.0);
+        let point = glm::vec3(0.7, -0.4, 1.0);
+        let screen = camera.world_to_screen(&point);
+        assert!(screen.x > 600.0 && screen.y > 300.0);
+        assert!((screen.z - 4.0).abs() < 1e-4);
+        assert_close(
+            &camera.screen_to_world((screen.x, screen.y), screen.z),
+            &point,
+        );
+        let v = camera.screen_to_viewport_point((600.0, 0.0));
+        assert_close(&glm::vec3(v.x, v.y, 0.0), &glm::vec3(0.5, 1.0, 0.0));
+    }
+
+    #[test]
+    fn empty_viewport_keeps_window_size() {
+        let mut camera = perspective_camera();
+        camera.set_viewport(0.0, 0.0, 0.0, 1.0);
+        camera.set_viewport(0.0, 0.0, 0.5, 1.0);
+        let v = camera.screen_to_viewport_point((200.0, 300.0));
+        assert_close(&glm::vec3(v.x, v.y, 0.0), &glm::vec3(0.5, 0.5, 0.0));
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut camera = perspective_camera();
+        camera
+            .projection
+            .set_type(ProjectionType::Ortographic(OrthographicMode::Size(6.0)));
+        let ray = camera.screen_point_to_ray((0.0, 0.0));
+        assert_close(&ray.direction, &camera.front());
+        assert_close(
+            &camera.screen_to_world((0.0, 0.0), 5.0),
+            &glm::vec3(-4.0, 3.0, 0.0),
+        );
+    }
+
+    fn ortho(mode: OrthographicMode, width: f32, height: f32) -> Projection {
+        let mut p = Projection::new(ProjectionType::Ortographic(mode), 0.1, 100.0);
+        p.update(width, height);
+        p
+    }
+
+    #[test]
+    fn orthographic_size_follows_aspect_ratio() {
+        let p = ortho(OrthographicMode::Size(10.0), 1600.0, 800.0);
+        assert_eq!(p.orthographic_bounds(), Some((-10.0, 10.0, -5.0, 5.0)));
+        let corner = p.projection_matrix() * glm::vec4(10.0, 5.0, -1.0, 1.0);
+        assert!((corner.x - 1.0).abs() < 1e-6 && (corner.y - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pixel_perfect_shows_more_of_the_world_in_bigger_windows() {
+        let mode = OrthographicMode::PixelPerfect {
+            pixels_per_unit: 16.0,
+            zoom: 2,
+        };
+        let small = ortho(mode, 640.0, 320.0);
+        assert_eq!(small.orthographic_bounds(), Some((-10.0, 10.0, -5.0, 5.0)));
+        let big = ortho(mode, 1280.0, 640.0);
+        assert_eq!(big.orthographic_bounds(), Some((-20.0, 20.0, -10.0, 10.0)));
+        // odd sizes keep one screen pixel per world pixel
+        let odd = ortho(mode, 641.0, 321.0);
+        assert_eq!(
+            odd.orthographic_bounds(),
+            Some((-10.0, 10.03125, -5.0, 5.03125))
+        );
+        let snapped = big.snap_to_pixels(&glm::vec3(0.51, 0.0, 3.0));
+        assert_eq!(snapped, glm::vec3(16.0 / 32.0, 0.0, 3.0));
+    }
+}
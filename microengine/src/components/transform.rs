@@ -46,7 +46,7 @@ impl Transform {
         self.changed = true;
         &mut self.scale
     }
-    
+
     /// Returns a reference to rotation quaternion.
     pub fn rotation(&self) -> &glm::Quat {
         &self.rotation
@@ -61,10 +61,10 @@ impl Transform {
     pub fn calculate_local_to_world_matrix(&self) -> glm::Mat4 {
         let wrld = glm::translation(&self.position);
         let wrld = wrld * glm::quat_to_mat4(&self.rotation);
-        
+
         glm::scale(&wrld, &self.scale)
     }
-    
+
     // TODO! should return reference!
     /// Returns local to world transformation matrix
     pub fn local_to_world(&mut self) -> glm::Mat4 {
@@ -103,11 +103,141 @@ impl Transform {
         self.changed = true;
     }
 
+    /// Returns world to local transformation matrix, inverse of local_to_world
+    pub fn world_to_local(&self) -> glm::Mat4 {
+        let inverse_scale = glm::vec3(1.0 / self.scale.x, 1.0 / self.scale.y, 1.0 / self.scale.z);
+        let m =
+            glm::scaling(&inverse_scale) * glm::quat_to_mat4(&glm::quat_conjugate(&self.rotation));
+        glm::translate(&m, &-self.position)
+    }
+
+    /// Returns transform undoing this one, exact only for uniform scale
+    /// (otherwise use world_to_local matrix)
+    pub fn inverse(&self) -> Transform {
+        let rotation = glm::quat_conjugate(&self.rotation);
+        let scale = glm::vec3(1.0 / self.scale.x, 1.0 / self.scale.y, 1.0 / self.scale.z);
+        let position = -glm::quat_rotate_vec3(&rotation, &self.position).component_mul(&scale);
+        Transform {
+            position,
+            rotation,
+            scale,
+            local_to_world: glm::Mat4::default(),
+            changed: true,
+        }
+    }
+
+    /// Moves object by given vector, in local space translation is rotated
+    /// with the object but not scaled
+    pub fn translate(&mut self, v: glm::Vec3, relative_to: Space) {
+        let v = match relative_to {
+            Space::Local => self.vector_to_world(&v),
+            Space::World => v,
+        };
+        self.position += v;
+        self.changed = true;
+    }
+
+    /// Sets rotation to euler angles applied in the same order as rotate_euler does
+    pub fn set_euler(&mut self, angles: glm::Vec3) {
+        self.rotation = glm::quat_angle_axis(angles.y, &glm::Vec3::y())
+            * glm::quat_angle_axis(angles.x, &glm::Vec3::x())
+            * glm::quat_angle_axis(angles.z, &glm::Vec3::z());
+        self.changed = true;
+    }
+
+    /// Rotates object so its forward vector points at target and its up vector
+    /// is as close to given up as possible
+    pub fn look_at(&mut self, target: &glm::Vec3, up: &glm::Vec3) {
+        let forward = target - self.position;
+        if forward.magnitude_squared() < f32::EPSILON {
+            return;
+        }
+        let forward = forward.normalize();
+        let mut right = glm::cross(&forward, up);
+        if right.magnitude_squared() < f32::EPSILON {
+            // looking along up, any perpendicular axis will do
+            right = glm::cross(&forward, &glm::Vec3::z());
+            if right.magnitude_squared() < f32::EPSILON {
+                right = glm::cross(&forward, &glm::Vec3::x());
+            }
+        }
+        let right = right.normalize();
+        let up = glm::cross(&right, &forward);
+        let basis = glm::Mat3::from_columns(&[right, up, -forward]);
+        self.rotation = glm::mat3_to_quat(&basis).normalize();
+        self.changed = true;
+    }
+
+    /// Returns direction object is facing (local -z axis) in world space
+    pub fn forward(&self) -> glm::Vec3 {
+        self.vector_to_world(&-glm::Vec3::z())
+    }
+
+    /// Returns local y axis in world space
+    pub fn up(&self) -> glm::Vec3 {
+        self.vector_to_world(&glm::Vec3::y())
+    }
+
+    /// Returns local x axis in world space
+    pub fn right(&self) -> glm::Vec3 {
+        self.vector_to_world(&glm::Vec3::x())
+    }
+
     /// Transforms a vector from local space to world space
     pub fn vector_to_world(&self, v: &glm::Vec3) -> glm::Vec3 {
         glm::quat_rotate_vec3(&self.rotation, v)
     }
 
+    /// Transforms a vector from world space to local space
+    pub fn vector_to_local(&self, v: &glm::Vec3) -> glm::Vec3 {
+        glm::quat_rotate_vec3(&glm::quat_conjugate(&self.rotation), v)
+    }
+
+    /// Transforms a point from local space to world space, applying scale, rotation and position
+    pub fn point_to_world(&self, p: &glm::Vec3) -> glm::Vec3 {
+        self.position + self.vector_to_world(&p.component_mul(&self.scale))
+    }
+
+    /// Transforms a point from world space to local space
+    pub fn point_to_local(&self, p: &glm::Vec3) -> glm::Vec3 {
+        self.vector_to_local(&(p - self.position))
+            .component_div(&self.scale)
+    }
+
+    /// Interpolates position and scale linearly and rotation spherically
+    pub fn slerp(&self, other: &Transform, t: f32) -> Transform {
+        self.interpolate(
+            other,
+            t,
+            glm::quat_slerp(&self.rotation, &other.rotation, t),
+        )
+    }
+
+    /// Like slerp but with cheaper, normalized linear rotation interpolation,
+    /// good enough for small angles
+    pub fn lerp(&self, other: &Transform, t: f32) -> Transform {
+        // take the shorter path between q and -q
+        let target = match glm::quat_dot(&self.rotation, &other.rotation) < 0.0 {
+            true => -other.rotation,
+            false => other.rotation,
+        };
+        self.interpolate(
+            other,
+            t,
+            glm::quat_lerp(&self.rotation, &target, t).normalize(),
+        )
+    }
+
+    fn interpolate(&self, other: &Transform, t: f32, rotation: glm::Quat) -> Transform {
+        Transform {
+            position: glm::lerp(&self.position, &other.position, t),
+            rotation,
+            scale: glm::lerp(&self.scale, &other.scale, t),
+            local_to_world: glm::Mat4::default(),
+            changed: true,
+        }
+    }
+
     /// Return rotation in euler angles (pitch, yaw, roll)
     pub fn euler_angles(&self) -> glm::Vec3 {
         glm::quat_euler_angles(&self.rotation)
@@ -125,3 +255,77 @@ impl Default for Transform {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::assert_close;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn transform() -> Transform {
+        Transform::new(
+            glm::vec3(1.0, 2.0, 3.0),
+            glm::vec3(0.3, 1.2, -0.4),
+            glm::vec3(2.0, 2.0, 2.0),
+        )
+    }
+
+    #[test]
+    fn world_to_local_inverts_local_to_world() {
+        let mut t = transform();
+        let m = t.world_to_local() * t.local_to_world();
+        assert!(glm::abs(&(m - glm::Mat4::identity())).max() < 1e-4);
+        let p = glm::vec3(-1.0, 0.5, 4.0);
+        assert_close(&t.point_to_local(&t.point_to_world(&p)), &p);
+        let world = t.point_to_world(&p);
+        assert_close(&t.inverse().point_to_world(&world), &p);
+        let from_matrix = t.local_to_world().transform_point(&p.into()).coords;
+        assert_close(&world, &from_matrix);
+    }
+
+    #[test]
+    fn set_euler_matches_rotate_euler() {
+        let angles = glm::vec3(0.3, 1.2, -0.4);
+        let mut t = Transform::default();
+        t.set_euler(angles);
+        let rotated = Transform::new(glm::Vec3::zeros(), angles, glm::vec3(1.0, 1.0, 1.0));
+        assert_close(&t.forward(), &rotated.forward());
+        assert_close(&t.up(), &rotated.up());
+    }
+
+    #[test]
+    fn look_at_points_forward_at_target() {
+        let mut t = transform();
+        let target = glm::vec3(5.0, -1.0, 0.0);
+        t.look_at(&target, &glm::Vec3::y());
+        assert_close(&t.forward(), &(target - t.position()).normalize());
+        assert!(t.right().y.abs() < 1e-4);
+        t.look_at(&(t.position() + glm::Vec3::y()), &glm::Vec3::y());
+        assert_close(&t.forward(), &glm::Vec3::y());
+    }
+
+    #[test]
+    fn translate_in_local_space_follows_rotation() {
+        let mut t = Transform::default();
+        t.set_euler(glm::vec3(0.0, FRAC_PI_2, 0.0));
+        t.translate(glm::vec3(0.0, 0.0, -1.0), Space::Local);
+        assert_close(t.position(), &glm::vec3(-1.0, 0.0, 0.0));
+        t.translate(glm::vec3(0.0, 0.0, -1.0), Space::World);
+        assert_close(t.position(), &glm::vec3(-1.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn interpolation_hits_both_ends_and_middle() {
+        let a = Transform::default();
+        let mut b = transform();
+        b.set_euler(glm::vec3(0.0, FRAC_PI_2, 0.0));
+        for f in [Transform::lerp, Transform::slerp] {
+            assert_close(f(&a, &b, 0.0).position(), a.position());
+            assert_close(&f(&a, &b, 1.0).forward(), &b.forward());
+            let half = f(&a, &b, 0.5);
+            assert_close(half.scale(), &glm::vec3(1.5, 1.5, 1.5));
+            let expected = glm::vec3(-1.0, 0.0, -1.0).normalize();
+            assert_close(&half.forward(), &expected);
+        }
+    }
+}
@@ -0,0 +1,587 @@
+//! Module providing in-game developer console.
+//! Console is always available to gameobjects as ctx.console so they can register commands
+//! with typed arguments. It is drawn on top of the game and controlled with keyboard
+//! after calling game.enable_console(ConsoleConfig).
+//! Built-in commands: help, clear, timescale, scene, quit
+
+use crate::{
+    context::Context,
+    error::{GameError, GameResult},
+    input::keyboard::keys::KeyCode,
+    scene::Scene,
+};
+use gl_utils::TextRenderer;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    rc::Rc,
+};
+
+/// how many lines of output are kept
+const MAX_OUTPUT_LINES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgType {
+    Int,
+    Float,
+    Bool,
+    /// if the last argument is a string it takes the rest of the line
+    Str,
+}
+
+impl fmt::Display for ArgType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            ArgType::Int => "int",
+            ArgType::Float => "float",
+            ArgType::Bool => "bool",
+            ArgType::Str => "string",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl ArgValue {
+    fn parse(value: &str, arg_type: ArgType) -> Option<ArgValue> {
+        match arg_type {
+            ArgType::Int => value.parse().ok().map(ArgValue::Int),
+            ArgType::Float => value.parse().ok().map(ArgValue::Float),
+            ArgType::Bool => match value {
+                "true" | "on" | "1" => Some(ArgValue::Bool(true)),
+                "false" | "off" | "0" => Some(ArgValue::Bool(false)),
+                _ => None,
+            },
+            ArgType::Str => Some(ArgValue::Str(value.into())),
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match *self {
+            ArgValue::Int(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Int arguments are converted to float as well
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            ArgValue::Float(v) => Some(v),
+            ArgValue::Int(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            ArgValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            ArgValue::Str(ref v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Command handlers get parsed arguments and return text printed to the console
+pub type CommandHandler = dyn Fn(&[ArgValue], &Context, &Scene) -> GameResult<String>;
+
+struct Command {
+    args: Vec<(String, ArgType)>,
+    help: String,
+    handler: Rc<CommandHandler>,
+}
+
+impl Command {
+    fn usage(&self, name: &str) -> String {
+        self.args
+            .iter()
+            .fold(String::from(name), |usage, (arg, t)| {
+                format!("{usage} <{arg}: {t}>")
+            })
+    }
+}
+
+/// Console configuration used by game.enable_console()
+pub struct ConsoleConfig {
+    pub toggle_key: KeyCode,
+    /// font atlas image: a grid of equally sized glyphs in ASCII order
+    pub font_atlas: String,
+    pub atlas_columns: u32,
+    pub atlas_rows: u32,
+    /// first character in the font atlas
+    pub first_char: char,
+    /// glyph height in pixels
+    pub font_size: f32,
+    /// how many lines of output are visible
+    pub visible_lines: usize,
+    pub history_size: usize,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        ConsoleConfig {
+            toggle_key: KeyCode::KeyGraveAccent,
+            font_atlas: String::new(),
+            atlas_columns: 16,
+            atlas_rows: 6,
+            first_char: ' ',
+            font_size: 16.0,
+            visible_lines: 12,
+            history_size: 100,
+        }
+    }
+}
+
+struct ConsoleState {
+    enabled: bool,
+    open: bool,
+    input: String,
+    history: Vec<String>,
+    /// position in history while browsing it with up/down arrows
+    history_pos: Option<usize>,
+    output: VecDeque<String>,
+    toggle_key: KeyCode,
+    font_size: f32,
+    visible_lines: usize,
+    history_size: usize,
+}
+
+pub struct Console {
+    commands: RefCell<BTreeMap<String, Command>>,
+    state: RefCell<ConsoleState>,
+    renderer: Option<TextRenderer>,
+}
+
+impl Console {
+    pub(crate) fn new() -> Self {
+        let cfg = ConsoleConfig::default();
+        let console = Console {
+            commands: RefCell::new(BTreeMap::new()),
+            state: RefCell::new(ConsoleState {
+                enabled: false,
+                open: false,
+                input: String::new(),
+                history: Vec::new(),
+                history_pos: None,
+                output: VecDeque::new(),
+                toggle_key: cfg.toggle_key,
+                font_size: cfg.font_size,
+                visible_lines: cfg.visible_lines,
+                history_size: cfg.history_size,
+            }),
+            renderer: None,
+        };
+        console.register_builtins();
+        console
+    }
+
+    fn register_builtins(&self) {
+        self.register("help", &[], "lists all commands", |_, ctx, _| {
+            Ok(ctx.console.help())
+        });
+        self.register("clear", &[], "clears console output", |_, ctx, _| {
+            ctx.console.clear();
+            Ok(String::new())
+        });
+        self.register(
+            "timescale",
+            &[("scale", ArgType::Float)],
+            "changes game speed",
+            |args, ctx, _| {
+                ctx.time.set_time_scale(args[0].as_float().unwrap_or(1.0));
+                Ok(String::new())
+            },
+        );
+        self.register(
+            "scene",
+            &[("name", ArgType::Str)],
+            "changes active scene",
+            |args, ctx, _| {
+                ctx.change_scene(args[0].as_str().unwrap_or_default());
+                Ok(String::new())
+            },
+        );
+        self.register("quit", &[], "closes the game", |_, ctx, _| {
+            ctx.window.close();
+            Ok(String::new())
+        });
+    }
+
+    /// Configures console and creates its renderer, requires graphics context
+    pub(crate) fn enable(&mut self, cfg: ConsoleConfig) -> GameResult {
+        self.renderer = Some(TextRenderer::new(
+            &cfg.font_atlas,
+            cfg.atlas_columns,
+            cfg.atlas_rows,
+            cfg.first_char,
+        )?);
+        let state = self.state.get_mut();
+        state.enabled = true;
+        state.toggle_key = cfg.toggle_key;
+        state.font_size = cfg.font_size;
+        state.visible_lines = cfg.visible_lines;
+        state.history_size = cfg.history_size;
+        Ok(())
+    }
+
+    /// Registers command with given name and argument (name, type) pairs
+    /// registering command with the same name again replaces it
+    pub fn register<F>(&self, name: &str, args: &[(&str, ArgType)], help: &str, handler: F)
+    where
+        F: Fn(&[ArgValue], &Context, &Scene) -> GameResult<String> + 'static,
+    {
+        self.commands.borrow_mut().insert(
+            name.into(),
+            Command {
+                args: args.iter().map(|(n, t)| (String::from(*n), *t)).collect(),
+                help: help.into(),
+                handler: Rc::new(handler),
+            },
+        );
+    }
+
+    pub fn unregister(&self, name: &str) {
+        self.commands.borrow_mut().remove(name);
+    }
+
+    /// Parses and runs given command line, returns command output
+    pub fn execute(&self, line: &str, ctx: &Context, scene: &Scene) -> GameResult<String> {
+        let line = line.trim();
+        let (name, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        if name.is_empty() {
+            return Ok(String::new());
+        }
+        // handler is cloned so commands can use the console while running
+        let (handler, arg_types, usage) = match self.commands.borrow().get(name) {
+            Some(cmd) => (cmd.handler.clone(), cmd.args.clone(), cmd.usage(name)),
+            None => return Err(GameError::Error(format!("Unknown command: {name}"))),
+        };
+        let mut args = Vec::with_capacity(arg_types.len());
+        for (i, (arg_name, arg_type)) in arg_types.iter().enumerate() {
+            rest = rest.trim_start();
+            let word = if i + 1 == arg_types.len() && *arg_type == ArgType::Str {
+                std::mem::take(&mut rest).trim_end()
+            } else {
+                let (word, r) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                rest = r;
+                word
+            };
+            if word.is_empty() {
+                return Err(GameError::Error(format!("Usage: {usage}")));
+            }
+            match ArgValue::parse(word, *arg_type) {
+                Some(v) => args.push(v),
+                None => {
+                    return Err(GameError::Error(format!(
+                        "Argument '{arg_name}' should be {arg_type}, got: {word}"
+                    )))
+                }
+            }
+        }
+        if !rest.trim().is_empty() {
+            return Err(GameError::Error(format!("Usage: {usage}")));
+        }
+        handler(&args, ctx, scene)
+    }
+
+    /// Returns names of commands starting with given prefix
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        self.commands
+            .borrow()
+            .keys()
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns usage and description of all commands
+    pub fn help(&self) -> String {
+        self.commands
+            .borrow()
+            .iter()
+            .map(|(name, cmd)| format!("{} - {}", cmd.usage(name), cmd.help))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Prints text to console output
+    pub fn print(&self, text: &str) {
+        let mut state = self.state.borrow_mut();
+        for line in text.lines() {
+            if state.output.len() == MAX_OUTPUT_LINES {
+                state.output.pop_front();
+            }
+            state.output.push_back(line.into());
+        }
+    }
+
+    pub fn clear(&self) {
+        self.state.borrow_mut().output.clear();
+    }
+
+    /// Returns console output lines, oldest first
+    pub fn output(&self) -> Vec<String> {
+        self.state.borrow().output.iter().cloned().collect()
+    }
+
+    /// Returns previously executed command lines, oldest first
+    pub fn history(&self) -> Vec<String> {
+        self.state.borrow().history.clone()
+    }
+
+    /// Check if console is open, gameobjects may want to ignore keyboard input then
+    pub fn is_open(&self) -> bool {
+        self.state.borrow().open
+    }
+
+    pub fn set_open(&self, open: bool) {
+        let mut state = self.state.borrow_mut();
+        state.open = open && state.enabled;
+    }
+
+    /// Executes line, saves it in history and prints its output
+    fn submit(&self, line: &str, ctx: &Context, scene: &Scene) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.history_pos = None;
+            let repeated = state.history.last().map(String::as_str) == Some(line);
+            if !line.trim().is_empty() && !repeated && state.history_size > 0 {
+                while state.history.len() >= state.history_size {
+                    state.history.remove(0);
+                }
+                state.history.push(line.into());
+            }
+        }
+        self.print(&format!("> {line}"));
+        match self.execute(line, ctx, scene) {
+            Ok(out) => self.print(&out),
+            Err(GameError::Error(e)) => self.print(&e),
+            Err(e) => self.print(&e.to_string()),
+        }
+    }
+
+    /// Completes command name in the input line
+    fn autocomplete(&self) {
+        let input = self.state.borrow().input.clone();
+        if input.contains(char::is_whitespace) {
+            return;
+        }
+        let candidates = self.complete(&input);
+        match candidates.len() {
+            0 => (),
+            1 => self.state.borrow_mut().input = format!("{} ", candidates[0]),
+            _ => {
+                let common = candidates.iter().fold(candidates[0].clone(), |common, c| {
+                    common
+                        .chars()
+                        .zip(c.chars())
+                        .take_while(|(a, b)| a == b)
+                        .map(|(a, _)| a)
+                        .collect()
+                });
+                self.print(&candidates.join(" "));
+                self.state.borrow_mut().input = common;
+            }
+        }
+    }
+
+    /// Moves through history, older if back is true
+    fn browse_history(&self, back: bool) {
+        let mut state = self.state.borrow_mut();
+        if state.history.is_empty() {
+            return;
+        }
+        let last = state.history.len() - 1;
+        state.history_pos = match (state.history_pos, back) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(p), true) => Some(p.saturating_sub(1)),
+            (Some(p), false) if p < last => Some(p + 1),
+            (Some(_), false) => None,
+        };
+        state.input = match state.history_pos {
+            Some(p) => state.history[p].clone(),
+            None => String::new(),
+        };
+    }
+
+    /// Handles keyboard input, called by Game every frame after running the scene
+    pub(crate) fn update(&self, ctx: &Context, scene: &Scene) {
+        let kb = &ctx.input.kb;
+        {
+            let mut state = self.state.borrow_mut();
+            if !state.enabled {
+                return;
+            }
+            if kb.get_key_down(state.toggle_key) {
+                state.open = !state.open;
+                return;
+            }
+            if !state.open {
+                return;
+            }
+            state
+                .input
+                .extend(kb.text().chars().filter(|c| !c.is_control()));
+            if kb.get_key_down(KeyCode::KeyBackspace) {
+                state.input.pop();
+            }
+        }
+        if kb.get_key_down(KeyCode::KeyEnter) || kb.get_key_down(KeyCode::KeyKpEnter) {
+            let line = std::mem::take(&mut self.state.borrow_mut().input);
+            self.submit(&line, ctx, scene);
+        }
+        if kb.get_key_down(KeyCode::KeyTab) {
+            self.autocomplete();
+        }
+        if kb.get_key_down(KeyCode::KeyUp) {
+            self.browse_history(true);
+        }
+        if kb.get_key_down(KeyCode::KeyDown) {
+            self.browse_history(false);
+        }
+    }
+
+    /// Draws console on top of the screen, called by Game every frame
+    pub(crate) fn draw(&self, ctx: &Context) {
+        let state = self.state.borrow();
+        let renderer = match self.renderer {
+            Some(ref r) if state.open => r,
+            _ => return,
+        };
+        let screen = (ctx.window.width() as f32, ctx.window.height() as f32);
+        let size = state.font_size;
+        let padding = size / 2.0;
+        let height = (state.visible_lines + 1) as f32 * size + padding * 2.0;
+        renderer.draw_rect(
+            (0.0, 0.0),
+            (screen.0, height),
+            &glm::vec4(0.0, 0.0, 0.0, 0.75),
+            screen,
+        );
+        let skip = state.output.len().saturating_sub(state.visible_lines);
+        let output = state
+            .output
+            .iter()
+            .skip(skip)
+            .cloned()
+            .collect::<Vec<String>>()
+            .join("\n");
+        let text_color = glm::vec4(0.9, 0.9, 0.9, 1.0);
+        renderer.draw_text(&output, (padding, padding), size, &text_color, screen);
+        renderer.draw_text(
+            &format!("> {}_", state.input),
+            (padding, padding + state.visible_lines as f32 * size),
+            size,
+            &text_color,
+            screen,
+        );
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Console::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn parses_typed_arguments() {
+        let ctx = Context::default();
+        let scene = Scene::default();
+        ctx.console.register(
+            "spawn",
+            &[("count", ArgType::Int), ("kind", ArgType::Str)],
+            "spawns enemies",
+            |args, _, _| {
+                Ok(format!(
+                    "{} {}",
+                    args[0].as_int().unwrap(),
+                    args[1].as_str().unwrap()
+                ))
+            },
+        );
+        let out = ctx.console.execute("spawn 3 big enemy", &ctx, &scene);
+        assert_eq!(out.unwrap(), "3 big enemy");
+        assert!(ctx
+            .console
+            .execute("spawn three enemy", &ctx, &scene)
+            .is_err());
+        assert!(ctx.console.execute("spawn 3", &ctx, &scene).is_err());
+        assert!(ctx.console.execute("unknown", &ctx, &scene).is_err());
+    }
+
+    #[test]
+    fn builtin_timescale_changes_timer() {
+        let ctx = Context::default();
+        let scene = Scene::default();
+        assert!(ctx.console.execute("timescale 0.5", &ctx, &scene).is_ok());
+        assert_eq!(ctx.time.time_scale(), 0.5);
+    }
+
+    #[test]
+    fn commands_can_use_console_while_running() {
+        let ctx = Context::default();
+        let scene = Scene::default();
+        let called = Rc::new(Cell::new(false));
+        let c = called.clone();
+        ctx.console.register("test", &[], "", move |_, ctx, _| {
+            c.set(true);
+            ctx.console.print("printed");
+            Ok(ctx.console.help())
+        });
+        ctx.console.submit("test", &ctx, &scene);
+        assert!(called.get());
+        assert!(ctx.console.output().contains(&String::from("printed")));
+        assert_eq!(ctx.console.history(), vec![String::from("test")]);
+    }
+
+    #[test]
+    fn completes_command_names() {
+        let console = Console::default();
+        assert_eq!(console.complete("ti"), vec![String::from("timescale")]);
+        console.state.borrow_mut().input = "q".into();
+        console.autocomplete();
+        assert_eq!(console.state.borrow().input, "quit ");
+    }
+
+    #[test]
+    fn browses_history() {
+        let ctx = Context::default();
+        let scene = Scene::default();
+        ctx.console.submit("help", &ctx, &scene);
+        ctx.console.submit("clear", &ctx, &scene);
+        ctx.console.browse_history(true);
+        ctx.console.browse_history(true);
+        assert_eq!(ctx.console.state.borrow().input, "help");
+        ctx.console.browse_history(false);
+        assert_eq!(ctx.console.state.borrow().input, "clear");
+        ctx.console.browse_history(false);
+        assert_eq!(ctx.console.state.borrow().input, "");
+
+        ctx.console.state.borrow_mut().history_size = 1;
+        ctx.console.submit("help", &ctx, &scene);
+        assert_eq!(ctx.console.history(), vec![String::from("help")]);
+        ctx.console.state.borrow_mut().history_size = 0;
+        ctx.console.submit("clear", &ctx, &scene);
+        assert_eq!(ctx.console.history(), vec![String::from("help")]);
+    }
+}
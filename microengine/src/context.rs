@@ -1,4 +1,8 @@
-use crate::{input::Input, timer::Timer, window::Window};
+use crate::{
+    console::Console, input::Input, inspector::Inspector, logger::Logger, profiler::Profiler,
+    renderer::Renderer, timer::Timer, tween::Tweens, window::Window,
+};
+use std::cell::RefCell;
 
 /// Everything every gameobject should know about, grouped in a single struct
 /// Methods with immutable reference to self are meant to be used by gameobjects
@@ -6,20 +10,39 @@ pub struct Context {
     pub time: Timer,
     pub window: Window,
     pub input: Input,
+    pub log: Logger,
+    pub profiler: Profiler,
+    pub renderer: Renderer,
+    pub console: Console,
+    pub inspector: Inspector,
+    pub tweens: Tweens,
+    pub(crate) scene_change_requested: RefCell<Option<String>>,
 }
 
 impl Context {
-    pub(crate) fn new(time: Timer, window: Window) -> Self {
+    pub(crate) fn new(time: Timer, window: Window, log: Logger) -> Self {
         Context {
             time,
             window,
             input: Default::default(),
+            log,
+            profiler: Default::default(),
+            renderer: Default::default(),
+            console: Default::default(),
+            inspector: Default::default(),
+            tweens: Default::default(),
+            scene_change_requested: RefCell::new(None),
         }
     }
+
+    /// Changes active scene to scene with given name at the start of next frame
+    pub fn change_scene(&self, name: &str) {
+        *self.scene_change_requested.borrow_mut() = Some(name.into());
+    }
 }
 
 impl Default for Context {
     fn default() -> Self {
-        Context::new(Default::default(), Default::default())
+        Context::new(Default::default(), Default::default(), Default::default())
     }
 }
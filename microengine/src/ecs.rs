@@ -0,0 +1,363 @@
+//! Optional ECS-style storage living alongside gameobjects.
+//! Each scene owns a World holding entities with attached components.
+//! Components of each type are kept in a densely packed array, so systems iterating
+//! over many simple entities (bullets, particles) are cache friendly.
+//! Any 'static type can be a component, including Transform and Camera.
+//! Systems are closures added with scene.add_system(phase, system), they run after
+//! gameobjects in given phase and use scene.world() queries.
+
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+};
+
+/// Handle of an entity, it is invalidated when the entity is despawned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+/// Components of a single type, packed in a dense array and indexed by entity index
+struct SparseSet<T> {
+    sparse: Vec<Option<usize>>,
+    entities: Vec<Entity>,
+    components: Vec<T>,
+}
+
+impl<T> SparseSet<T> {
+    fn new() -> Self {
+        SparseSet {
+            sparse: Vec::new(),
+            entities: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    fn dense_index(&self, e: Entity) -> Option<usize> {
+        let i = (*self.sparse.get(e.index as usize)?)?;
+        (self.entities[i] == e).then_some(i)
+    }
+
+    /// Inserts component, returns the replaced one
+    fn insert(&mut self, e: Entity, component: T) -> Option<T> {
+        if let Some(i) = self.sparse.get(e.index as usize).copied().flatten() {
+            self.entities[i] = e;
+            return Some(std::mem::replace(&mut self.components[i], component));
+        }
+        if self.sparse.len() <= e.index as usize {
+            self.sparse.resize(e.index as usize + 1, None);
+        }
+        self.sparse[e.index as usize] = Some(self.components.len());
+        self.entities.push(e);
+        self.components.push(component);
+        None
+    }
+
+    fn remove(&mut self, e: Entity) -> Option<T> {
+        let i = self.dense_index(e)?;
+        self.sparse[e.index as usize] = None;
+        self.entities.swap_remove(i);
+        let component = self.components.swap_remove(i);
+        if let Some(moved) = self.entities.get(i) {
+            self.sparse[moved.index as usize] = Some(i);
+        }
+        Some(component)
+    }
+
+    fn get_mut(&mut self, e: Entity) -> Option<&mut T> {
+        let i = self.dense_index(e)?;
+        Some(&mut self.components[i])
+    }
+}
+
+/// Type erased storage, used to remove components of despawned entities
+trait AnyStorage {
+    fn remove_entity(&self, e: Entity);
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: 'static> AnyStorage for RefCell<SparseSet<T>> {
+    fn remove_entity(&self, e: Entity) {
+        self.borrow_mut().remove(e);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct Entities {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    count: usize,
+}
+
+/// Container of entities and their components
+/// All methods take &self so gameobjects can spawn entities with scene.world()
+/// Components of queried types can't be inserted or removed inside query closures
+/// (this panics), collect entities and do it after the query instead.
+#[derive(Default)]
+pub struct World {
+    entities: RefCell<Entities>,
+    storages: RefCell<HashMap<TypeId, Rc<dyn AnyStorage>>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a new entity without components
+    pub fn spawn(&self) -> Entity {
+        let mut entities = self.entities.borrow_mut();
+        entities.count += 1;
+        match entities.free.pop() {
+            Some(index) => {
+                entities.alive[index as usize] = true;
+                Entity {
+                    index,
+                    generation: entities.generations[index as usize],
+                }
+            }
+            None => {
+                entities.generations.push(0);
+                entities.alive.push(true);
+                Entity {
+                    index: entities.generations.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Removes entity with all of its components, returns false if it was already despawned.
+    /// Panics when called inside a query closure, as queried components are borrowed
+    pub fn despawn(&self, e: Entity) -> bool {
+        {
+            let mut entities = self.entities.borrow_mut();
+            if !Self::alive(&entities, e) {
+                return false;
+            }
+            entities.alive[e.index as usize] = false;
+            entities.generations[e.index as usize] += 1;
+            entities.free.push(e.index);
+            entities.count -= 1;
+        }
+        let storages: Vec<Rc<dyn AnyStorage>> = self.storages.borrow().values().cloned().collect();
+        for storage in storages {
+            storage.remove_entity(e);
+        }
+        true
+    }
+
+    fn alive(entities: &Entities, e: Entity) -> bool {
+        entities.alive.get(e.index as usize) == Some(&true)
+            && entities.generations[e.index as usize] == e.generation
+    }
+
+    pub fn is_alive(&self, e: Entity) -> bool {
+        Self::alive(&self.entities.borrow(), e)
+    }
+
+    /// Returns number of living entities
+    pub fn len(&self) -> usize {
+        self.entities.borrow().count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn storage<T: 'static>(&self) -> Option<Rc<dyn AnyStorage>> {
+        self.storages.borrow().get(&TypeId::of::<T>()).cloned()
+    }
+
+    fn storage_or_insert<T: 'static>(&self) -> Rc<dyn AnyStorage> {
+        self.storages
+            .borrow_mut()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Rc::new(RefCell::new(SparseSet::<T>::new())))
+            .clone()
+    }
+
+    fn downcast<T: 'static>(storage: &Rc<dyn AnyStorage>) -> &RefCell<SparseSet<T>> {
+        storage
+            .as_any()
+            .downcast_ref()
+            .expect("component storage has wrong type")
+    }
+
+    /// Attaches component to entity, replacing (and returning) the old one of the same type
+    /// Component is not added if entity was despawned
+    pub fn insert<T: 'static>(&self, e: Entity, component: T) -> Option<T> {
+        if !self.is_alive(e) {
+            return None;
+        }
+        let storage = self.storage_or_insert::<T>();
+        let replaced = Self::downcast::<T>(&storage)
+            .borrow_mut()
+            .insert(e, component);
+        replaced
+    }
+
+    /// Detaches component from entity and returns it
+    pub fn remove<T: 'static>(&self, e: Entity) -> Option<T> {
+        let storage = self.storage::<T>()?;
+        let removed = Self::downcast::<T>(&storage).borrow_mut().remove(e);
+        removed
+    }
+
+    pub fn has<T: 'static>(&self, e: Entity) -> bool {
+        match self.storage::<T>() {
+            Some(storage) => Self::downcast::<T>(&storage)
+                .borrow()
+                .dense_index(e)
+                .is_some(),
+            None => false,
+        }
+    }
+
+    /// Returns a copy of entity's component
+    pub fn get<T: Clone + 'static>(&self, e: Entity) -> Option<T> {
+        self.with(e, |c: &mut T| c.clone())
+    }
+
+    /// Runs closure on entity's component and returns its result
+    pub fn with<T: 'static, R>(&self, e: Entity, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let storage = self.storage::<T>()?;
+        let mut set = Self::downcast::<T>(&storage).borrow_mut();
+        let result = set.get_mut(e).map(f);
+        result
+    }
+
+    /// Runs closure for each entity with component T, despawn inside the closure panics
+    pub fn query<T: 'static>(&self, mut f: impl FnMut(Entity, &mut T)) {
+        let Some(storage) = self.storage::<T>() else {
+            return;
+        };
+        let mut set = Self::downcast::<T>(&storage).borrow_mut();
+        let set = &mut *set;
+        for (e, c) in set.entities.iter().zip(set.components.iter_mut()) {
+            f(*e, c);
+        }
+    }
+
+    /// Runs closure for each entity with both A and B components
+    /// A and B must be different types, despawn inside the closure panics
+    pub fn query2<A: 'static, B: 'static>(&self, mut f: impl FnMut(Entity, &mut A, &mut B)) {
+        assert_ne!(
+            TypeId::of::<A>(),
+            TypeId::of::<B>(),
+            "query2 component types must differ"
+        );
+        let (Some(sa), Some(sb)) = (self.storage::<A>(), self.storage::<B>()) else {
+            return;
+        };
+        let mut a = Self::downcast::<A>(&sa).borrow_mut();
+        let mut b = Self::downcast::<B>(&sb).borrow_mut();
+        let a = &mut *a;
+        for (e, ca) in a.entities.iter().zip(a.components.iter_mut()) {
+            if let Some(cb) = b.get_mut(*e) {
+                f(*e, ca, cb);
+            }
+        }
+    }
+
+    /// Runs closure for each entity with A, B and C components
+    /// A, B and C must be different types, despawn inside the closure panics
+    pub fn query3<A: 'static, B: 'static, C: 'static>(
+        &self,
+        mut f: impl FnMut(Entity, &mut A, &mut B, &mut C),
+    ) {
+        assert_ne!(
+            TypeId::of::<A>(),
+            TypeId::of::<C>(),
+            "query3 component types must differ"
+        );
+        assert_ne!(
+            TypeId::of::<B>(),
+            TypeId::of::<C>(),
+            "query3 component types must differ"
+        );
+        let Some(sc) = self.storage::<C>() else {
+            return;
+        };
+        let mut c = Self::downcast::<C>(&sc).borrow_mut();
+        self.query2::<A, B>(|e, ca, cb| {
+            if let Some(cc) = c.get_mut(e) {
+                f(e, ca, cb, cc);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position(f32);
+    #[derive(Debug, Clone, PartialEq)]
+    struct Velocity(f32);
+
+    #[test]
+    fn despawned_entities_are_not_reused_by_old_handles() {
+        let world = World::new();
+        let e = world.spawn();
+        world.insert(e, Position(1.0));
+        assert!(world.despawn(e));
+        assert!(!world.despawn(e));
+        let reused = world.spawn();
+        assert_eq!(reused.index(), e.index());
+        assert!(!world.is_alive(e));
+        assert_eq!(world.get::<Position>(reused), None);
+        assert_eq!(world.insert(e, Position(2.0)), None);
+        assert_eq!(world.len(), 1);
+    }
+
+    #[test]
+    fn removing_components_keeps_others_reachable() {
+        let world = World::new();
+        let entities: Vec<Entity> = (0..4).map(|_| world.spawn()).collect();
+        for (i, e) in entities.iter().enumerate() {
+            world.insert(*e, Position(i as f32));
+        }
+        assert_eq!(world.remove::<Position>(entities[1]), Some(Position(1.0)));
+        assert!(!world.has::<Position>(entities[1]));
+        assert_eq!(world.get::<Position>(entities[3]), Some(Position(3.0)));
+    }
+
+    #[test]
+    fn queries_match_entities_with_all_components() {
+        let world = World::new();
+        let moving = world.spawn();
+        world.insert(moving, Position(0.0));
+        world.insert(moving, Velocity(2.0));
+        let still = world.spawn();
+        world.insert(still, Position(5.0));
+        world.query2::<Position, Velocity>(|_, p, v| p.0 += v.0);
+        let mut count = 0;
+        world.query::<Position>(|_, _| count += 1);
+        assert_eq!(count, 2);
+        assert_eq!(world.get::<Position>(moving), Some(Position(2.0)));
+        assert_eq!(world.get::<Position>(still), Some(Position(5.0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "query3 component types must differ")]
+    fn query3_rejects_repeated_component_types() {
+        let world = World::new();
+        world.query3::<u32, f32, u32>(|_, _, _, _| {});
+    }
+}
@@ -1,7 +1,42 @@
 //! Error type definitions and Result<T, GameError> wrapper
+use crate::gameobject::GameObjectId;
 use std::error::Error;
 use std::fmt;
 
+/// GameObject lifecycle methods, used to tell where an error occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    OnAdd,
+    Start,
+    Collision,
+    /// scene's physics step (rigid bodies and collision events), it isn't a GameObject method
+    Physics,
+    FixedUpdate,
+    Update,
+    Draw,
+}
+
+impl Phase {
+    /// Returns name of the GameObject method (or of the scene step for Physics)
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Phase::OnAdd => "on_add",
+            Phase::Start => "start",
+            Phase::Collision => "collision",
+            Phase::Physics => "physics",
+            Phase::FixedUpdate => "fixed_update",
+            Phase::Update => "update",
+            Phase::Draw => "draw",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 #[derive(Debug)]
 pub enum GameError {
     UnNamedError(String),
@@ -11,6 +46,28 @@ pub enum GameError {
     BackendError(String),
     EngineError(String),
     Error(String),
+    Io(std::io::Error),
+    Image(image::ImageError),
+    /// Any other error, use it to wrap errors from other crates
+    /// (it has to be Send so errors can be returned from worker threads)
+    Other(Box<dyn Error + Send + Sync>),
+    /// Error returned by a gameobject, annotated by Scene
+    GameObjectError {
+        name: String,
+        id: GameObjectId,
+        phase: Phase,
+        source: Box<GameError>,
+    },
+}
+
+impl GameError {
+    /// Returns the innermost GameError, skipping gameobject annotations
+    pub fn root(&self) -> &GameError {
+        match *self {
+            GameError::GameObjectError { ref source, .. } => source.root(),
+            ref e => e,
+        }
+    }
 }
 
 impl fmt::Display for GameError {
@@ -26,10 +83,69 @@ impl fmt::Display for GameError {
             GameError::Error(ref s) => {
                 write!(f, "Error: {}", s)
             }
+            GameError::Io(ref e) => write!(f, "IO Error: {e}"),
+            GameError::Image(ref e) => write!(f, "Image Error: {e}"),
+            GameError::Other(ref e) => write!(f, "Error: {e}"),
+            GameError::GameObjectError {
+                ref name,
+                ref id,
+                phase,
+                ref source,
+            } => write!(
+                f,
+                "GameObject '{name}' (id: {}, layer: {}) failed in {phase}: {source}",
+                id.id, id.layer
+            ),
+        }
+    }
+}
+
+impl Error for GameError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            GameError::Io(ref e) => Some(e),
+            GameError::Image(ref e) => Some(e),
+            GameError::Other(ref e) => Some(e.as_ref()),
+            GameError::GameObjectError { ref source, .. } => Some(source.as_ref()),
+            _ => None,
         }
     }
 }
 
-impl Error for GameError {}
+impl From<std::io::Error> for GameError {
+    fn from(value: std::io::Error) -> Self {
+        GameError::Io(value)
+    }
+}
+
+impl From<image::ImageError> for GameError {
+    fn from(value: image::ImageError) -> Self {
+        GameError::Image(value)
+    }
+}
+
+impl From<gl_utils::GlError> for GameError {
+    fn from(value: gl_utils::GlError) -> Self {
+        GameError::Other(Box::new(value))
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for GameError {
+    fn from(value: Box<dyn Error + Send + Sync>) -> Self {
+        GameError::Other(value)
+    }
+}
+
+impl From<String> for GameError {
+    fn from(value: String) -> Self {
+        GameError::Error(value)
+    }
+}
+
+impl From<&str> for GameError {
+    fn from(value: &str) -> Self {
+        GameError::Error(value.into())
+    }
+}
 
 pub type GameResult<T = ()> = Result<T, GameError>;
@@ -1,4 +1,4 @@
-use crate::{error::GameResult, input::Input, timer::Timer, window::Window};
+use crate::{error::GameResult, input::Input, logger::Logger, timer::Timer, window::Window};
 
 pub mod glfw;
 
@@ -16,6 +16,7 @@ pub trait SystemEventFacade {
         _window: &mut Window,
         _input: &mut Input,
         _timer: &mut Timer,
+        _log: &Logger,
     ) -> GameResult {
         Ok(())
     }
@@ -26,6 +27,7 @@ pub trait SystemEventFacade {
         _window: &mut Window,
         _input: &mut Input,
         _timer: &mut Timer,
+        _log: &Logger,
     ) -> GameResult {
         Ok(())
     }
@@ -5,38 +5,84 @@ use crate::glfw::{Action, Context, Key};
 
 use crate::timer::Timer;
 use crate::KeyCode;
-use crate::{error::GameResult, gl, glfw, input::Input, timer::GetTime, window::Window};
-use glfw::{Monitor, MouseButton, WindowEvent, WindowMode};
+use crate::{
+    error::{GameError, GameResult},
+    gl, glfw,
+    input::Input,
+    logger::{LogLevel, Logger},
+    timer::GetTime,
+    window::{MonitorInfo, RenderConfig, VideoMode, Window},
+};
+use glfw::{MouseButton, WindowEvent, WindowMode};
+use std::{ffi::CStr, os::raw::c_void, sync::Mutex};
 
 use super::SystemEventFacade;
 
+const LOG_TARGET: &str = "microengine::backend::glfw";
+const GL_LOG_TARGET: &str = "microengine::backend::gl";
+/// Engine shaders (text overlay, skinning) are written in GLSL 3.30
+const MIN_GL_VERSION: (u32, u32) = (3, 3);
+
+/// Messages reported by OpenGL debug output, logged at the end of each frame
+type DebugMessages = Mutex<Vec<(LogLevel, String)>>;
+
 pub struct GLFWBackend {
     window: glfw::PWindow,
     events: glfw::GlfwReceiver<(f64, glfw::WindowEvent)>,
     glfw: glfw::Glfw,
-    primary_monitor: glfw::Monitor,
-    // fullscreen management
+    // display mode management
+    display_mode: DisplayMode,
+    monitor: usize,
     windowed_width: usize,
     windowed_height: usize,
+    clear_mask: gl::types::GLbitfield,
+    /// boxed so its address passed to OpenGL stays valid, None without debug context
+    debug_messages: Option<Box<DebugMessages>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DisplayMode {
+    Windowed,
+    Fullscreen,
+    Borderless,
 }
 
 impl GLFWBackend {
     /// Initializes and configures window exactly as in given reference
-    pub fn new(window: &Window) -> Self {
-        let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
-        glfw.window_hint(glfw::WindowHint::Samples(Some(4)));
-        glfw.window_hint(glfw::WindowHint::ContextVersionMajor(3));
-        glfw.window_hint(glfw::WindowHint::ContextVersionMinor(3));
-        glfw.window_hint(glfw::WindowHint::OpenGlProfile(
-            glfw::OpenGlProfileHint::Core,
+    pub fn new(window: &mut Window, render_cfg: &RenderConfig) -> GameResult<Self> {
+        let (major, minor) = render_cfg.gl_version;
+        if (major, minor) < MIN_GL_VERSION {
+            return Err(GameError::BackendError(format!(
+                "OpenGL {major}.{minor} is not supported, at least {}.{} is required",
+                MIN_GL_VERSION.0, MIN_GL_VERSION.1
+            )));
+        }
+        let mut glfw = glfw::init(glfw::log_errors)
+            .map_err(|e| GameError::BackendError(format!("Failed to initialize GLFW: {e}")))?;
+        glfw.window_hint(glfw::WindowHint::Samples(render_cfg.msaa_samples));
+        glfw.window_hint(glfw::WindowHint::DepthBits(render_cfg.depth_bits));
+        glfw.window_hint(glfw::WindowHint::StencilBits(render_cfg.stencil_bits));
+        glfw.window_hint(glfw::WindowHint::SRgbCapable(render_cfg.srgb));
+        glfw.window_hint(glfw::WindowHint::ContextVersion(major, minor));
+        // core profile is available since OpenGL 3.2
+        if (major, minor) >= (3, 2) {
+            glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+                glfw::OpenGlProfileHint::Core,
+            ));
+            glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+        }
+        glfw.window_hint(glfw::WindowHint::OpenGlDebugContext(
+            render_cfg.debug_context,
         ));
-        glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
 
-        let primary_monitor = Monitor::from_primary();
+        window.system_set_monitors(Self::query_monitors(&mut glfw));
         let mode = WindowMode::Windowed;
         if window.is_fullscreen() {
             window.set_fullscreen(true);
         }
+        if window.is_borderless() {
+            window.set_borderless(true);
+        }
 
         let result = glfw.create_window(
             window.width() as u32,
@@ -45,30 +91,182 @@ impl GLFWBackend {
             mode,
         );
 
-        match result {
-            Some((mut w, events)) => {
-                w.set_pos(window.pos().0 as i32, window.pos().1 as i32);
-                gl::load_with(|s| w.get_proc_address(s) as *const _);
-                unsafe {
-                    gl::Enable(gl::BLEND);
-                    gl::Enable(gl::DEPTH_TEST);
-                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-                    gl::ClearColor(0.1, 0.1, 0.1, 1.0);
-                    gl::Viewport(0, 0, window.width() as i32, window.height() as i32);
-                }
-                w.set_key_polling(true);
-                w.make_current();
-                GLFWBackend {
-                    window: w,
-                    events,
-                    glfw,
-                    primary_monitor,
-                    windowed_width: window.width(),
-                    windowed_height: window.height(),
-                }
+        let (mut w, events) =
+            result.ok_or_else(|| GameError::BackendError("Failed to create GLFW Window".into()))?;
+        w.set_pos(window.pos().0 as i32, window.pos().1 as i32);
+        gl::load_with(|s| w.get_proc_address(s) as *const _);
+        let (r, g, b, a) = render_cfg.clear_color;
+        unsafe {
+            if render_cfg.blending {
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            }
+            if render_cfg.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if render_cfg.msaa_samples.is_some() {
+                gl::Enable(gl::MULTISAMPLE);
+            }
+            if render_cfg.srgb {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            }
+            gl::ClearColor(r, g, b, a);
+            gl::Viewport(0, 0, window.width() as i32, window.height() as i32);
+        }
+        let mut clear_mask = gl::COLOR_BUFFER_BIT;
+        if render_cfg.depth_bits.is_some() {
+            clear_mask |= gl::DEPTH_BUFFER_BIT;
+        }
+        if render_cfg.stencil_bits.is_some() {
+            clear_mask |= gl::STENCIL_BUFFER_BIT;
+        }
+        w.set_key_polling(true);
+        w.set_char_polling(true);
+        w.make_current();
+        let debug_messages = render_cfg.debug_context.then(Self::install_debug_callback);
+        Ok(GLFWBackend {
+            window: w,
+            events,
+            glfw,
+            display_mode: DisplayMode::Windowed,
+            monitor: window.monitor(),
+            windowed_width: window.width(),
+            windowed_height: window.height(),
+            clear_mask,
+            debug_messages,
+        })
+    }
+
+    /// Makes OpenGL report errors and warnings to returned queue,
+    /// requires OpenGL 4.3 or KHR_debug extension
+    fn install_debug_callback() -> Box<DebugMessages> {
+        let messages = Box::new(DebugMessages::default());
+        if !gl::DebugMessageCallback::is_loaded() {
+            let msg = "OpenGL debug output is not supported by this context".to_string();
+            messages.lock().unwrap().push((LogLevel::Warn, msg));
+            return messages;
+        }
+        unsafe {
+            gl::Enable(gl::DEBUG_OUTPUT);
+            gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl::DebugMessageCallback(
+                Some(debug_callback),
+                &*messages as *const DebugMessages as *const c_void,
+            );
+        }
+        messages
+    }
+
+    /// Returns information about all connected monitors
+    fn query_monitors(glfw: &mut glfw::Glfw) -> Vec<MonitorInfo> {
+        let to_video_mode = |m: glfw::VidMode| VideoMode {
+            width: m.width as usize,
+            height: m.height as usize,
+            refresh_rate: m.refresh_rate as usize,
+        };
+        glfw.with_connected_monitors(|_, monitors| {
+            monitors
+                .iter()
+                .map(|m| MonitorInfo {
+                    name: m.get_name().unwrap_or_default(),
+                    video_mode: m.get_video_mode().map(to_video_mode),
+                    video_modes: m.get_video_modes().into_iter().map(to_video_mode).collect(),
+                })
+                .collect()
+        })
+    }
+
+    /// Switches between windowed, fullscreen and borderless modes on given monitor
+    fn set_display_mode(
+        &mut self,
+        window: &mut Window,
+        display_mode: DisplayMode,
+        monitor: usize,
+    ) -> GameResult {
+        if self.display_mode == DisplayMode::Windowed && display_mode != DisplayMode::Windowed {
+            // save window size
+            self.windowed_width = window.width();
+            self.windowed_height = window.height();
+        }
+        let (width, height) = match display_mode {
+            DisplayMode::Windowed => {
+                self.window.set_decorated(true);
+                self.window.set_monitor(
+                    WindowMode::Windowed,
+                    window.pos().0 as i32,
+                    window.pos().1 as i32,
+                    self.windowed_width as u32,
+                    self.windowed_height as u32,
+                    None,
+                );
+                (self.windowed_width, self.windowed_height)
+            }
+            _ => {
+                let glfw_window = &mut self.window;
+                self.glfw
+                    .with_connected_monitors(|_, monitors| -> GameResult<(usize, usize)> {
+                        let m = monitors.get(monitor).ok_or_else(|| {
+                            GameError::BackendError(format!(
+                                "There is no monitor with index: {monitor}"
+                            ))
+                        })?;
+                        let vidmode = m.get_video_mode().ok_or_else(|| {
+                            GameError::BackendError(format!(
+                                "Can't get video mode of monitor: {monitor}"
+                            ))
+                        })?;
+                        if display_mode == DisplayMode::Fullscreen {
+                            glfw_window.set_monitor(
+                                WindowMode::FullScreen(m),
+                                0,
+                                0,
+                                vidmode.width,
+                                vidmode.height,
+                                Some(vidmode.refresh_rate),
+                            );
+                        } else {
+                            let (x, y) = m.get_pos();
+                            glfw_window.set_decorated(false);
+                            glfw_window.set_monitor(
+                                WindowMode::Windowed,
+                                x,
+                                y,
+                                vidmode.width,
+                                vidmode.height,
+                                None,
+                            );
+                        }
+                        Ok((vidmode.width as usize, vidmode.height as usize))
+                    })?
             }
-            None => panic!("Failed to create GLFW Window"),
+        };
+        self.display_mode = display_mode;
+        self.monitor = monitor;
+        window.is_fullscreen = display_mode == DisplayMode::Fullscreen;
+        window.is_borderless = display_mode == DisplayMode::Borderless;
+        window.monitor = monitor;
+        window.system_update_resolution(width, height);
+        unsafe {
+            gl::Viewport(0, 0, width as i32, height as i32);
         }
+        Ok(())
+    }
+
+    /// Loads image file and sets it as window icon
+    fn set_icon(&mut self, img_path: &str) -> GameResult {
+        let img = image::open(img_path)
+            .map_err(|e| {
+                GameError::BackendError(format!("Couldn't load '{img_path}' icon file: {e}"))
+            })?
+            .into_rgba8();
+        let (width, height) = img.dimensions();
+        let pixels = img.pixels().map(|p| u32::from_ne_bytes(p.0)).collect();
+        self.window.set_icon_from_pixels(vec![glfw::PixelImage {
+            width,
+            height,
+            pixels,
+        }]);
+        Ok(())
     }
 }
 
@@ -84,15 +282,16 @@ impl SystemEventFacade for GLFWBackend {
         window: &mut Window,
         input: &mut Input,
         timer: &mut Timer,
+        log: &Logger,
     ) -> GameResult {
         timer.loop_start(&self.glfw);
         unsafe {
-            gl::Clear(gl::COLOR_BUFFER_BIT);
-            gl::Clear(gl::DEPTH_BUFFER_BIT);
+            gl::Clear(self.clear_mask);
         }
         self.glfw.poll_events();
         for (_, event) in glfw::flush_messages(&self.events) {
             match event {
+                WindowEvent::Char(c) => input.kb.push_char(c),
                 // Keyboard events
                 WindowEvent::Key(Key::Space, _, Action::Press, _) => {
                     input.kb.press_key(KeyCode::KeySpace)
@@ -776,14 +975,19 @@ impl SystemEventFacade for GLFWBackend {
             let (w, h) = self.window.get_framebuffer_size();
             if (w as usize, h as usize) != (window.width(), window.height())
                 && !window.is_fullscreen
+                && !window.is_borderless
             {
+                log.debug(LOG_TARGET, format_args!("Window resized to {w}x{h}"));
                 window.system_update_resolution(w as usize, h as usize);
                 unsafe {
                     gl::Viewport(0, 0, w, h);
                 }
             }
             let (x, y) = self.window.get_pos();
-            if (x as isize, y as isize) != window.pos() && !window.is_fullscreen {
+            if (x as isize, y as isize) != window.pos()
+                && !window.is_fullscreen
+                && !window.is_borderless
+            {
                 window.system_set_pos(x as isize, y as isize);
             }
         }
@@ -836,6 +1040,7 @@ impl SystemEventFacade for GLFWBackend {
         window: &mut Window,
         input: &mut Input,
         timer: &mut Timer,
+        log: &Logger,
     ) -> GameResult {
         // Input changes handling
         {
@@ -849,46 +1054,69 @@ impl SystemEventFacade for GLFWBackend {
         }
         // Window changes handling
         self.window.swap_buffers();
+        if let Some(title) = window.title_requested.get_mut().take() {
+            log.debug(LOG_TARGET, format_args!("Window title set to '{title}'"));
+            self.window.set_title(&title);
+            window.system_set_name(title);
+        }
+        if let Some(img_path) = window.icon_requested.get_mut().take() {
+            log.debug(LOG_TARGET, format_args!("Window icon set to '{img_path}'"));
+            self.set_icon(&img_path)?;
+        }
+        if let Some((r, g, b, a)) = window.clear_color_requested.get_mut().take() {
+            window.clear_color = (r, g, b, a);
+            unsafe {
+                gl::ClearColor(r, g, b, a);
+            }
+        }
         {
-            // fullscreen handling
-            let mut fullscreen: bool = false;
-            self.window.with_window_mode(|mode| match mode {
-                WindowMode::FullScreen(_) => fullscreen = true,
-                _ => (),
-            });
-            if *window.fullscreen_requested.borrow() && !fullscreen {
-                let vidmode = self.primary_monitor.get_video_mode().unwrap();
-                window.is_fullscreen = true;
-                // save window size
-                self.windowed_width = window.width();
-                self.windowed_height = window.height();
-                self.window.set_monitor(
-                    glfw::WindowMode::FullScreen(&self.primary_monitor),
-                    0,
-                    0,
-                    vidmode.width,
-                    vidmode.height,
-                    None,
-                );
-                window.system_update_resolution(vidmode.width as usize, vidmode.height as usize);
-                unsafe {
-                    gl::Viewport(0, 0, vidmode.width as i32, vidmode.height as i32);
+            // fullscreen, borderless and monitor handling
+            let requested_monitor = window.monitor_requested.get_mut().take();
+            if let Some(monitor) = requested_monitor {
+                window.system_set_monitors(Self::query_monitors(&mut self.glfw));
+                if monitor >= window.monitors().len() {
+                    return Err(GameError::BackendError(format!(
+                        "There is no monitor with index: {monitor}"
+                    )));
                 }
             }
-            if !*window.fullscreen_requested.borrow() && fullscreen {
-                window.is_fullscreen = false;
-                window.system_update_resolution(self.windowed_width, self.windowed_height);
-                self.window.set_monitor(
-                    glfw::WindowMode::Windowed,
-                    window.pos().0 as i32,
-                    window.pos().1 as i32,
-                    window.width() as u32,
-                    window.height() as u32,
-                    None,
+            let monitor = requested_monitor.unwrap_or(self.monitor);
+            let display_mode = if *window.fullscreen_requested.borrow() {
+                DisplayMode::Fullscreen
+            } else if *window.borderless_requested.borrow() {
+                DisplayMode::Borderless
+            } else {
+                DisplayMode::Windowed
+            };
+            if display_mode == DisplayMode::Windowed {
+                if display_mode != self.display_mode {
+                    log.debug(LOG_TARGET, "Switching to windowed mode");
+                    self.set_display_mode(window, display_mode, monitor)?;
+                }
+                self.monitor = monitor;
+                window.monitor = monitor;
+            } else if display_mode != self.display_mode || monitor != self.monitor {
+                let mode_name = match display_mode {
+                    DisplayMode::Fullscreen => "fullscreen",
+                    _ => "borderless",
+                };
+                log.debug(
+                    LOG_TARGET,
+                    format_args!("Switching to {mode_name} mode on monitor {monitor}"),
                 );
-                unsafe {
-                    gl::Viewport(0, 0, window.width() as i32, window.height() as i32);
-                }
+                self.set_display_mode(window, display_mode, monitor)?;
+            }
+        }
+        if let Some((width, height)) = window.size_requested.get_mut().take() {
+            self.windowed_width = width;
+            self.windowed_height = height;
+            if self.display_mode == DisplayMode::Windowed {
+                self.window.set_size(width as i32, height as i32);
+            }
+        }
+        if let Some(ref messages) = self.debug_messages {
+            for (level, msg) in messages.lock().unwrap().drain(..) {
+                log.log(level, GL_LOG_TARGET, msg);
             }
         }
         if self.window.should_close() {
@@ -898,3 +1126,30 @@ impl SystemEventFacade for GLFWBackend {
         Ok(())
     }
 }
+
+extern "system" fn debug_callback(
+    _source: gl::types::GLenum,
+    _kind: gl::types::GLenum,
+    id: gl::types::GLuint,
+    severity: gl::types::GLenum,
+    _length: gl::types::GLsizei,
+    message: *const gl::types::GLchar,
+    user_param: *mut c_void,
+) {
+    let level = match severity {
+        gl::DEBUG_SEVERITY_HIGH => LogLevel::Error,
+        gl::DEBUG_SEVERITY_MEDIUM => LogLevel::Warn,
+        gl::DEBUG_SEVERITY_LOW => LogLevel::Info,
+        _ => LogLevel::Debug,
+    };
+    // user_param points to DebugMessages owned by GLFWBackend
+    let (messages, message) = unsafe {
+        (
+            &*(user_param as *const DebugMessages),
+            CStr::from_ptr(message).to_string_lossy(),
+        )
+    };
+    if let Ok(mut messages) = messages.lock() {
+        messages.push((level, format!("[{id}] {message}")));
+    }
+}
@@ -5,19 +5,27 @@
 use std::collections::HashMap;
 
 use crate::{
+    console::ConsoleConfig,
     context::Context,
+    inspector::InspectorConfig,
     error::{GameError, GameResult},
     event_handler::{glfw::GLFWBackend, Backend, SystemEventFacade},
-    scene::Scene,
+    logger::{Logger, LoggerConfig},
+    scene::{ErrorPolicy, Scene},
     timer::Timer,
     window::{Window, WindowConfig},
 };
 
+const LOG_TARGET: &str = "microengine::game";
+
 pub struct GameConfig {
     pub backend: Backend,
     pub window_cfg: WindowConfig,
     pub fixed_fps: usize,
     pub starting_scene_name: String,
+    /// used by scenes that don't set their own ErrorPolicy
+    pub error_policy: ErrorPolicy,
+    pub log_cfg: LoggerConfig,
 }
 
 pub struct Game {
@@ -25,6 +33,8 @@ pub struct Game {
     scenes: HashMap<String, Scene>,
     ev_handler: Box<dyn SystemEventFacade>,
     next_scene_name: Option<String>,
+    error_policy: ErrorPolicy,
+    errors: Vec<GameError>,
 }
 
 impl Game {
@@ -32,20 +42,34 @@ impl Game {
     pub fn run(&mut self) -> GameResult {
         let mut active_scene: Option<Scene> = None;
         while !self.ctx.window.should_close() {
+            self.ctx.profiler.begin_frame();
+            let start = self.ctx.profiler.now();
             self.ev_handler.loop_start(
                 &mut self.ctx.window,
                 &mut self.ctx.input,
                 &mut self.ctx.time,
+                &self.ctx.log,
             )?;
+            self.ctx.profiler.record_phase("loop_start", start);
+            if let Some(name) = self.ctx.scene_change_requested.get_mut().take() {
+                self.next_scene_name = Some(name);
+            }
             if let Some(ref name) = self.next_scene_name.take() {
                 if !self.scenes.contains_key(name) {
                     return Err(GameError::GameLogicError(format!(
                         "There is no scene named: {name}"
                     )));
                 }
+                self.ctx
+                    .log
+                    .debug(LOG_TARGET, format_args!("Changing scene to '{name}'"));
                 let prev_scene = active_scene.replace(self.scenes.remove(name).unwrap());
+                // animations of other scenes target their gameobject ids, so they are suspended
+                self.ctx.tweens.set_scene(name);
                 if let Some(s) = prev_scene {
-                    if !s.disposable {
+                    if s.disposable {
+                        self.ctx.tweens.remove_scene(&s.name);
+                    } else {
                         let s = self.scenes.insert(s.name.clone(), s);
                         if let Some(s) = s {
                             return Err(GameError::GameLogicError(format!(
@@ -59,9 +83,28 @@ impl Game {
 
             // RUN SCENE
             // UPDATE SCENES (add dynamically created scenes)
+            let start = self.ctx.profiler.now();
             match active_scene {
                 Some(ref mut scene) => {
-                    scene.run_loop(&mut self.ctx)?;
+                    if self.ctx.time.frame_paused {
+                        // cameras are usually added in update, which doesn't run while paused
+                        self.ctx.renderer.keep_last_views();
+                        scene.run_draw(&mut self.ctx)?;
+                    } else {
+                        scene.run_update(&mut self.ctx)?;
+                        let start = self.ctx.profiler.now();
+                        self.ctx.tweens.update(&self.ctx, scene)?;
+                        self.ctx.profiler.record_phase("tweens", start);
+                        scene.run_draw(&mut self.ctx)?;
+                    }
+                    let start = self.ctx.profiler.now();
+                    self.ctx.renderer.render(&self.ctx.window);
+                    self.ctx.profiler.record_phase("render", start);
+                    self.errors.append(&mut scene.take_errors());
+                    self.ctx.console.update(&self.ctx, scene);
+                    self.ctx.inspector.update(&self.ctx, scene);
+                    self.ctx.inspector.draw(&self.ctx, scene);
+                    self.ctx.console.draw(&self.ctx);
                     // TODO!
                     // GET ALL DYNAMICALLY CREATED SCENES FROM SCENE!
                     // CHECK IF SCENE SHOULD CHANGE ( scene.should_change()-> Option<String> )
@@ -72,19 +115,25 @@ impl Game {
                     ));
                 }
             }
+            self.ctx.profiler.record_phase("scene", start);
             self.ctx.input.update_state();
+            let start = self.ctx.profiler.now();
             self.ev_handler.loop_end(
                 &mut self.ctx.window,
                 &mut self.ctx.input,
                 &mut self.ctx.time,
+                &self.ctx.log,
             )?;
+            self.ctx.profiler.record_phase("loop_end", start);
+            self.ctx.profiler.end_frame();
         }
         Ok(())
     }
 
     /// Adds given scene to the game.
     /// Use this method to compose your game!
-    pub fn add_scene(&mut self, scene: Scene) -> GameResult {
+    pub fn add_scene(&mut self, mut scene: Scene) -> GameResult {
+        scene.error_policy.get_or_insert(self.error_policy);
         let s = self.scenes.insert(scene.name.clone(), scene);
         match s {
             Some(s) => Err(GameError::GameLogicError(format!(
@@ -98,21 +147,46 @@ impl Game {
     pub fn set_starting_scene_name(&mut self, scene_name: &str) {
         self.next_scene_name = Some(scene_name.into());
     }
+
+    /// Enables in-game developer console (see ctx.console)
+    /// Fails if font atlas couldn't be loaded
+    pub fn enable_console(&mut self, cfg: ConsoleConfig) -> GameResult {
+        self.ctx.console.enable(cfg)
+    }
+
+    /// Enables scene inspector debug overlay (see ctx.inspector)
+    /// Fails if font atlas couldn't be loaded
+    pub fn enable_inspector(&mut self, cfg: InspectorConfig) -> GameResult {
+        self.ctx.inspector.enable(cfg)
+    }
+
+    /// Returns all gameobject errors which didn't stop the game
+    /// (see ErrorPolicy), use it for reporting
+    pub fn errors(&self) -> &[GameError] {
+        &self.errors
+    }
 }
 
 /// For now it's the only way to create a Game
-impl From<GameConfig> for Game {
-    fn from(value: GameConfig) -> Self {
-        let window = Window::from(value.window_cfg);
+/// Fails if selected backend couldn't be initialized
+impl TryFrom<GameConfig> for Game {
+    type Error = GameError;
+
+    fn try_from(value: GameConfig) -> GameResult<Self> {
+        let log = Logger::new(value.log_cfg)?;
+        let render_cfg = value.window_cfg.render_cfg;
+        let mut window = Window::from(value.window_cfg);
         let backend = match value.backend {
-            Backend::GLFW => GLFWBackend::new(&window),
+            Backend::GLFW => GLFWBackend::new(&mut window, &render_cfg)?,
         };
-        Game {
-            ctx: Context::new(Timer::new(value.fixed_fps), window),
+        Ok(Game {
+            ctx: Context::new(Timer::new(value.fixed_fps), window, log),
             scenes: HashMap::new(),
             ev_handler: Box::new(backend),
             next_scene_name: Some(value.starting_scene_name),
-        }
+            error_policy: value.error_policy,
+            errors: Vec::new(),
+        })
     }
 }
 
@@ -128,6 +202,8 @@ mod tests {
             scenes: HashMap::new(),
             ev_handler: backend,
             next_scene_name: None,
+            error_policy: ErrorPolicy::Abort,
+            errors: Vec::new(),
         }
     }
 
@@ -163,6 +239,7 @@ mod tests {
             window: &mut Window,
             _input: &mut Input,
             _timer: &mut Timer,
+            _log: &Logger,
         ) -> GameResult {
             window.system_close();
             Ok(())
@@ -176,6 +253,29 @@ mod tests {
         }
     }
 
+    struct FailingGameObject;
+    impl GameObject for FailingGameObject {
+        fn update(&mut self, _ctx: &Context, _scene: &Scene) -> GameResult {
+            Err(GameError::GameLogicError("failed".into()))
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn game_collects_errors_when_policy_does_not_abort() {
+        let mut g = game_from_backend(Box::new(DoNothingBackend));
+        g.error_policy = ErrorPolicy::LogAndContinue;
+        let mut s = Scene::default();
+        _ = s.add_gameobject(FailingGameObject, 0);
+        _ = s.add_gameobject(RequestGameClose, 1);
+        g.set_starting_scene_name(&s.name);
+        _ = g.add_scene(s);
+        assert!(g.run().is_ok());
+        assert_eq!(g.errors().len(), 1);
+    }
+
     #[test]
     fn window_system_close_shutsdown_game() {
         let backend = SystemCloseBackend;
@@ -1,6 +1,11 @@
 //! Provides GameObject trait which handles gameloop events
+use crate::collision::{Collider, Collision};
+use crate::collision2d::Collider2D;
+use crate::components::transform::Transform;
 use crate::context::Context;
-use crate::error::GameResult;
+use crate::error::{GameError, GameResult};
+use crate::physics::RigidBody;
+use crate::parallel::ParallelGameObject;
 use crate::Scene;
 use std::any::Any;
 
@@ -11,6 +16,18 @@ pub struct GameObjectId {
     pub(crate) is_dead: bool,
 }
 
+impl GameObjectId {
+    /// Returns layer the gameobject was added to
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    /// Returns unique (within the scene) number of the gameobject
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
 pub trait GameObject {
 
     /// on_add is executed when gameobejct is added to scene
@@ -36,6 +53,66 @@ pub trait GameObject {
         Ok(())
     }
 
+    /// Returns collider of the gameobject, placed in world by its transform.
+    /// Collisions are detected before each fixed_update (see collision module)
+    fn collider(&self) -> Option<&Collider> {
+        None
+    }
+
+    /// Returns collider in the XY plane, used by 2D games (see collision2d module).
+    /// Gameobject can have both collider and collider_2d, but they never touch each other
+    fn collider_2d(&self) -> Option<&Collider2D> {
+        None
+    }
+
+    /// Returns rigid body moved by scene's physics step, gameobject needs to implement
+    /// transform_mut and rigid_body_mut as well (see physics module)
+    fn rigid_body(&self) -> Option<&RigidBody> {
+        None
+    }
+
+    /// Mutable version of rigid_body(), used to write back results of physics step
+    fn rigid_body_mut(&mut self) -> Option<&mut RigidBody> {
+        None
+    }
+
+    /// called in the first fixed step in which gameobject's collider touches other collider
+    fn on_collision_enter(&mut self, _ctx: &Context, _scene: &Scene, _collision: &Collision) -> GameResult {
+        Ok(())
+    }
+
+    /// called in every following fixed step while colliders keep touching
+    fn on_collision_stay(&mut self, _ctx: &Context, _scene: &Scene, _collision: &Collision) -> GameResult {
+        Ok(())
+    }
+
+    /// called in the first fixed step in which colliders don't touch anymore
+    fn on_collision_exit(&mut self, _ctx: &Context, _scene: &Scene, _collision: &Collision) -> GameResult {
+        Ok(())
+    }
+
+    /// trigger versions of on_collision_* methods, called when either collider is a trigger
+    fn on_trigger_enter(&mut self, _ctx: &Context, _scene: &Scene, _other: GameObjectId) -> GameResult {
+        Ok(())
+    }
+
+    fn on_trigger_stay(&mut self, _ctx: &Context, _scene: &Scene, _other: GameObjectId) -> GameResult {
+        Ok(())
+    }
+
+    fn on_trigger_exit(&mut self, _ctx: &Context, _scene: &Scene, _other: GameObjectId) -> GameResult {
+        Ok(())
+    }
+
+    /// on_error is executed when any other method of this gameobject returns an error,
+    /// before the error is handled according to scene's ErrorPolicy
+    fn on_error(&mut self, _ctx: &Context, _scene: &Scene, _error: &GameError) {}
+
+    /// reset is executed when a pooled gameobject dies and is returned to its pool
+    /// (see scene.create_pool), it should bring the gameobject to its initial state
+    /// so is_dead returns false again
+    fn reset(&mut self) {}
+
     /// if is_dead returns true Scene will clean this gameobject
     fn is_dead(&mut self) -> bool {
         false
@@ -51,6 +128,33 @@ pub trait GameObject {
         ""
     }
 
+    /// z-index used to order drawing within a layer, lower keys are drawn first
+    /// used only by layers with a sort mode set (see scene.set_layer_sort_mode)
+    fn sort_key(&self) -> i32 {
+        0
+    }
+
+    /// Returns gameobject's transform so it can be shown and edited by the scene inspector
+    fn transform(&self) -> Option<&Transform> {
+        None
+    }
+
+    /// Mutable version of transform(), implement both to make transform editable
+    fn transform_mut(&mut self) -> Option<&mut Transform> {
+        None
+    }
+
+    /// Gameobjects which can be updated on worker threads implement it as:
+    /// ```rust=
+    /// fn as_parallel(&mut self) -> Option<&mut dyn ParallelGameObject> {
+    ///     Some(self)
+    /// }
+    /// ```
+    /// they are run in parallel only in layers marked with scene.set_layer_parallel()
+    fn as_parallel(&mut self) -> Option<&mut dyn ParallelGameObject> {
+        None
+    }
+
     /// as_any is needed to make searching for other gameobjects possible
     /// with scene.gameobject_by_id<T>(id).unwrap();
     /// implement as:
@@ -60,4 +164,15 @@ pub trait GameObject {
     /// }
     /// ```
     fn as_any(&self) -> &dyn Any;
+
+    /// Needed only by pooled gameobjects, so they can be initialized by scene.spawn_pooled
+    /// implement as:
+    /// ```rust=
+    /// fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+    ///     Some(self)
+    /// }
+    /// ```
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        None
+    }
 }
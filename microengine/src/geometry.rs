@@ -0,0 +1,296 @@
+//! Basic geometric primitives used by cameras and collision queries
+
+/// Half-line starting at origin, direction is always normalized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: glm::Vec3,
+    pub direction: glm::Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: glm::Vec3, direction: glm::Vec3) -> Self {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Returns point at given distance from ray's origin
+    pub fn point_at(&self, distance: f32) -> glm::Vec3 {
+        self.origin + self.direction * distance
+    }
+}
+
+/// Plane of points p satisfying dot(normal, p) + distance = 0, normal is normalized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: glm::Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn new(normal: glm::Vec3, distance: f32) -> Self {
+        let len = normal.magnitude();
+        Plane {
+            normal: normal / len,
+            distance: distance / len,
+        }
+    }
+
+    /// Creates plane with given normal going through given point
+    pub fn from_point(normal: glm::Vec3, point: &glm::Vec3) -> Self {
+        let normal = normal.normalize();
+        Plane {
+            distance: -glm::dot(&normal, point),
+            normal,
+        }
+    }
+
+    /// Returns signed distance of point from the plane, positive on the side normal points to
+    pub fn signed_distance(&self, point: &glm::Vec3) -> f32 {
+        glm::dot(&self.normal, point) + self.distance
+    }
+
+    /// Returns distance along the ray at which it hits the plane
+    pub fn raycast(&self, ray: &Ray) -> Option<f32> {
+        let denom = glm::dot(&self.normal, &ray.direction);
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = -self.signed_distance(&ray.origin) / denom;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+/// Axis aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: glm::Vec3, max: glm::Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    pub fn from_center(center: glm::Vec3, extents: glm::Vec3) -> Self {
+        Aabb::new(center - extents, center + extents)
+    }
+
+    pub fn from_points(points: &[glm::Vec3]) -> Self {
+        gl_utils::primitives::Bounds::from_points(points).into()
+    }
+
+    pub fn center(&self) -> glm::Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Returns half of the box size
+    pub fn extents(&self) -> glm::Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// Returns box grown by margin in every direction
+    pub fn expanded(&self, margin: f32) -> Aabb {
+        let margin = glm::vec3(margin, margin, margin);
+        Aabb::new(self.min - margin, self.max + margin)
+    }
+
+    /// Returns distance along the ray at which it enters the box, 0 if it starts inside
+    pub fn raycast(&self, ray: &Ray) -> Option<f32> {
+        let (mut t_min, mut t_max) = (0.0f32, f32::INFINITY);
+        for i in 0..3 {
+            if ray.direction[i].abs() < f32::EPSILON {
+                if ray.origin[i] < self.min[i] || ray.origin[i] > self.max[i] {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (self.min[i] - ray.origin[i]) / ray.direction[i];
+            let t2 = (self.max[i] - ray.origin[i]) / ray.direction[i];
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+
+    /// Returns box enclosing this box transformed by given matrix (e.g. model matrix)
+    pub fn transformed(&self, matrix: &glm::Mat4) -> Aabb {
+        let center = matrix.transform_point(&self.center().into()).coords;
+        let linear = matrix.fixed_view::<3, 3>(0, 0).abs();
+        Aabb::from_center(center, linear * self.extents())
+    }
+}
+
+impl From<gl_utils::primitives::Bounds> for Aabb {
+    fn from(value: gl_utils::primitives::Bounds) -> Self {
+        Aabb::new(value.min, value.max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: glm::Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: glm::Vec3, radius: f32) -> Self {
+        BoundingSphere { center, radius }
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool {
+        glm::distance2(&self.center, point) <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &BoundingSphere) -> bool {
+        let r = self.radius + other.radius;
+        glm::distance2(&self.center, &other.center) <= r * r
+    }
+
+    /// Returns sphere enclosing this sphere transformed by given matrix,
+    /// radius is scaled by the largest axis scale
+    pub fn transformed(&self, matrix: &glm::Mat4) -> BoundingSphere {
+        let center = matrix.transform_point(&self.center.into()).coords;
+        let scale = (0..3)
+            .map(|i| matrix.fixed_view::<3, 1>(0, i).magnitude())
+            .fold(0.0f32, f32::max);
+        BoundingSphere::new(center, self.radius * scale)
+    }
+}
+
+impl From<gl_utils::primitives::Bounds> for BoundingSphere {
+    fn from(value: gl_utils::primitives::Bounds) -> Self {
+        BoundingSphere::new(value.center(), value.radius())
+    }
+}
+
+impl From<&Aabb> for BoundingSphere {
+    fn from(value: &Aabb) -> Self {
+        BoundingSphere::new(value.center(), value.extents().magnitude())
+    }
+}
+
+/// Six planes enclosing volume visible by a camera, plane normals point inside
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// left, right, bottom, top, near, far
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts planes from view-projection matrix, see Camera::frustum()
+    pub fn from_matrix(m: &glm::Mat4) -> Self {
+        let row = |i: usize| m.row(i).transpose();
+        let plane = |v: glm::Vec4| Plane::new(v.xyz(), v.w);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        Frustum {
+            planes: [
+                plane(w + x),
+                plane(w - x),
+                plane(w + y),
+                plane(w - y),
+                plane(w + z),
+                plane(w - z),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool {
+        self.planes.iter().all(|p| p.signed_distance(point) >= 0.0)
+    }
+
+    /// Returns false only if sphere is fully outside
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.signed_distance(&sphere.center) >= -sphere.radius)
+    }
+
+    /// Returns false only if box is fully outside, may return true
+    /// for boxes near frustum corners which are not visible
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let (center, extents) = (aabb.center(), aabb.extents());
+        self.planes.iter().all(|p| {
+            let radius = glm::dot(&p.normal.abs(), &extents);
+            p.signed_distance(&center) >= -radius
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frustum() -> Frustum {
+        let projection = glm::perspective(1.0, f32::to_radians(90.0), 1.0, 10.0);
+        let view = glm::look_at(
+            &glm::vec3(0.0, 0.0, 5.0),
+            &glm::Vec3::zeros(),
+            &glm::Vec3::y(),
+        );
+        Frustum::from_matrix(&(projection * view))
+    }
+
+    #[test]
+    fn frustum_contains_points_in_front_of_camera() {
+        let f = frustum();
+        assert!(f.contains_point(&glm::Vec3::zeros()));
+        assert!(f.contains_point(&glm::vec3(4.9, 0.0, 0.0)));
+        assert!(!f.contains_point(&glm::vec3(5.1, 0.0, 0.0)));
+        assert!(!f.contains_point(&glm::vec3(0.0, 0.0, 4.5)));
+        assert!(!f.contains_point(&glm::vec3(0.0, 0.0, -5.5)));
+    }
+
+    #[test]
+    fn frustum_culls_spheres_and_boxes() {
+        let f = frustum();
+        assert!(f.intersects_sphere(&BoundingSphere::new(glm::vec3(5.5, 0.0, 0.0), 1.0)));
+        assert!(!f.intersects_sphere(&BoundingSphere::new(glm::vec3(7.0, 0.0, 0.0), 1.0)));
+        let unit = Aabb::from_center(glm::Vec3::zeros(), glm::vec3(0.5, 0.5, 0.5));
+        assert!(f.intersects_aabb(&unit));
+        let moved = unit.transformed(&glm::translation(&glm::vec3(0.0, 20.0, 0.0)));
+        assert!(!f.intersects_aabb(&moved));
+        assert!(f.intersects_aabb(&Aabb::new(
+            glm::vec3(-100.0, -1.0, -1.0),
+            glm::vec3(100.0, 1.0, 1.0)
+        )));
+    }
+
+    #[test]
+    fn transformed_volumes_enclose_model() {
+        let model = glm::rotation(f32::to_radians(45.0), &glm::Vec3::z())
+            * glm::scaling(&glm::vec3(2.0, 1.0, 1.0));
+        let aabb = Aabb::from_points(&[glm::vec3(-1.0, -1.0, -1.0), glm::vec3(1.0, 1.0, 1.0)]);
+        let t = aabb.transformed(&model);
+        let corner = model
+            .transform_point(&glm::vec3(1.0, 1.0, 1.0).into())
+            .coords;
+        assert!(t.contains_point(&corner));
+        let sphere = BoundingSphere::from(&aabb).transformed(&model);
+        assert!((sphere.radius - 2.0 * 3f32.sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ray_hits_plane() {
+        let plane = Plane::from_point(glm::Vec3::y(), &glm::vec3(0.0, -2.0, 0.0));
+        let ray = Ray::new(glm::Vec3::zeros(), glm::vec3(0.0, -1.0, 1.0));
+        let t = plane.raycast(&ray).unwrap();
+        assert!((ray.point_at(t).y + 2.0).abs() < 1e-5);
+        assert!(plane
+            .raycast(&Ray::new(glm::Vec3::zeros(), glm::Vec3::y()))
+            .is_none());
+    }
+}
@@ -8,6 +8,7 @@ use keys::{KeyCode, NUM_KEYS};
 
 pub struct KeyBoard {
     keys: Vec<KeyState>,
+    text: String,
 }
 
 /// KeyBoard events should be updated here by SystemEventFacade implementations
@@ -17,9 +18,16 @@ impl KeyBoard {
             keys: iter::repeat_with(|| KeyState::NotPressed)
                 .take(NUM_KEYS)
                 .collect(),
+            text: String::new(),
         }
     }
 
+    /// Appends character typed by the user
+    /// Managed by SystemEventFacade implementation
+    pub(crate) fn push_char(&mut self, c: char) {
+        self.text.push(c);
+    }
+
     /// Change key state to KeyState::Down if not pressed already
     /// Managed by SystemEventFacade implementation
     pub(crate) fn press_key(&mut self, key: KeyCode) {
@@ -38,6 +46,7 @@ impl KeyBoard {
     /// updates all keys released in last frame to KeyState::NotPressed
     /// and all keys pressed in last frame to KeyState::Pressed
     pub(crate) fn update_key_state(&mut self) {
+        self.text.clear();
         self.keys.iter_mut().for_each(|k| {
             *k = match *k {
                 KeyState::Up => KeyState::NotPressed,
@@ -70,6 +79,12 @@ impl KeyBoard {
             _ => false,
         }
     }
+
+    /// Returns text typed by the user during this frame
+    /// (respecting keyboard layout and modifiers, unlike KeyCodes)
+    pub fn text(&self) -> &str {
+        &self.text
+    }
 }
 
 impl Default for KeyBoard {
@@ -99,4 +114,14 @@ mod tests {
         assert_eq!(kb.get_key_up(key), false);
         assert_eq!(kb.get_key(key), false);
     }
+
+    #[test]
+    fn typed_text_is_cleared_after_frame() {
+        let mut kb = KeyBoard::default();
+        kb.push_char('a');
+        kb.push_char('B');
+        assert_eq!(kb.text(), "aB");
+        kb.update_key_state();
+        assert_eq!(kb.text(), "");
+    }
 }
@@ -0,0 +1,399 @@
+//! Module providing scene inspector debug overlay.
+//! Inspector lists gameobjects of the active scene with their transforms,
+//! lets you select one of them and edit its transform live.
+//! It can also pause the game and step it frame by frame.
+//! Enable it with game.enable_inspector(InspectorConfig), controls:
+//! toggle key - show/hide, Up/Down - select gameobject, P - pause, N - step one frame,
+//! Tab - switch edited field, 1/2/3 - switch edited axis, Left/Right - change value
+
+use crate::{
+    context::Context,
+    error::GameResult,
+    gameobject::GameObjectId,
+    input::keyboard::keys::KeyCode,
+    scene::{GameObjectState, Scene},
+};
+use gl_utils::TextRenderer;
+use std::cell::RefCell;
+
+/// Transform field edited by the inspector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformField {
+    Position,
+    Rotation,
+    Scale,
+}
+
+impl TransformField {
+    fn next(self) -> Self {
+        match self {
+            TransformField::Position => TransformField::Rotation,
+            TransformField::Rotation => TransformField::Scale,
+            TransformField::Scale => TransformField::Position,
+        }
+    }
+}
+
+/// Inspector configuration used by game.enable_inspector()
+pub struct InspectorConfig {
+    pub toggle_key: KeyCode,
+    /// font atlas image: a grid of equally sized glyphs in ASCII order
+    pub font_atlas: String,
+    pub atlas_columns: u32,
+    pub atlas_rows: u32,
+    /// first character in the font atlas
+    pub first_char: char,
+    /// glyph height in pixels
+    pub font_size: f32,
+    /// how much edited value changes each frame Left/Right is held,
+    /// rotation is changed in radians
+    pub edit_step: f32,
+}
+
+impl Default for InspectorConfig {
+    fn default() -> Self {
+        InspectorConfig {
+            toggle_key: KeyCode::KeyF1,
+            font_atlas: String::new(),
+            atlas_columns: 16,
+            atlas_rows: 6,
+            first_char: ' ',
+            font_size: 16.0,
+            edit_step: 0.05,
+        }
+    }
+}
+
+struct InspectorState {
+    enabled: bool,
+    open: bool,
+    selected: Option<GameObjectId>,
+    field: TransformField,
+    axis: usize,
+    toggle_key: KeyCode,
+    font_size: f32,
+    edit_step: f32,
+}
+
+pub struct Inspector {
+    state: RefCell<InspectorState>,
+    renderer: Option<TextRenderer>,
+}
+
+impl Inspector {
+    pub(crate) fn new() -> Self {
+        let cfg = InspectorConfig::default();
+        Inspector {
+            state: RefCell::new(InspectorState {
+                enabled: false,
+                open: false,
+                selected: None,
+                field: TransformField::Position,
+                axis: 0,
+                toggle_key: cfg.toggle_key,
+                font_size: cfg.font_size,
+                edit_step: cfg.edit_step,
+            }),
+            renderer: None,
+        }
+    }
+
+    /// Configures inspector and creates its renderer, requires graphics context
+    pub(crate) fn enable(&mut self, cfg: InspectorConfig) -> GameResult {
+        self.renderer = Some(TextRenderer::new(
+            &cfg.font_atlas,
+            cfg.atlas_columns,
+            cfg.atlas_rows,
+            cfg.first_char,
+        )?);
+        let state = self.state.get_mut();
+        state.enabled = true;
+        state.toggle_key = cfg.toggle_key;
+        state.font_size = cfg.font_size;
+        state.edit_step = cfg.edit_step;
+        Ok(())
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.borrow().open
+    }
+
+    pub fn set_open(&self, open: bool) {
+        let mut state = self.state.borrow_mut();
+        state.open = open && state.enabled;
+    }
+
+    /// Returns id of the gameobject selected for editing
+    pub fn selected(&self) -> Option<GameObjectId> {
+        self.state.borrow().selected
+    }
+
+    pub fn select(&self, id: Option<GameObjectId>) {
+        self.state.borrow_mut().selected = id;
+    }
+
+    /// Selects which field and axis (0 - x, 1 - y, 2 - z) will be edited
+    pub fn set_edited(&self, field: TransformField, axis: usize) {
+        let mut state = self.state.borrow_mut();
+        state.field = field;
+        state.axis = axis.min(2);
+    }
+
+    /// Selects next (or previous) active gameobject in the scene
+    fn select_next(&self, scene: &Scene, back: bool) {
+        let ids = scene
+            .inspect()
+            .into_iter()
+            .filter(|info| info.state == GameObjectState::Active)
+            .map(|info| info.id)
+            .collect::<Vec<GameObjectId>>();
+        if ids.is_empty() {
+            return;
+        }
+        let mut state = self.state.borrow_mut();
+        let current = state
+            .selected
+            .and_then(|s| ids.iter().position(|id| *id == s));
+        let next = match (current, back) {
+            (None, _) => 0,
+            (Some(i), false) => (i + 1) % ids.len(),
+            (Some(i), true) => (i + ids.len() - 1) % ids.len(),
+        };
+        state.selected = Some(ids[next]);
+    }
+
+    /// Changes edited value of selected gameobject's transform by given amount
+    pub fn edit(&self, scene: &mut Scene, amount: f32) {
+        let state = self.state.borrow();
+        let transform = match state.selected {
+            Some(ref id) => scene.transform_mut(id),
+            None => None,
+        };
+        let transform = match transform {
+            Some(t) => t,
+            None => return,
+        };
+        match state.field {
+            TransformField::Position => transform.position_mut()[state.axis] += amount,
+            TransformField::Scale => transform.scale_mut()[state.axis] += amount,
+            TransformField::Rotation => {
+                let mut axis = glm::Vec3::zeros();
+                axis[state.axis] = 1.0;
+                transform.rotate(axis, amount, crate::components::transform::Space::World);
+            }
+        }
+    }
+
+    /// Handles keyboard input, called by Game every frame after running the scene
+    pub(crate) fn update(&self, ctx: &Context, scene: &mut Scene) {
+        let kb = &ctx.input.kb;
+        {
+            let mut state = self.state.borrow_mut();
+            if !state.enabled || ctx.console.is_open() {
+                return;
+            }
+            if kb.get_key_down(state.toggle_key) {
+                state.open = !state.open;
+                return;
+            }
+            if !state.open {
+                return;
+            }
+            if kb.get_key_down(KeyCode::KeyTab) {
+                state.field = state.field.next();
+            }
+            for (axis, key) in [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3]
+                .into_iter()
+                .enumerate()
+            {
+                if kb.get_key_down(key) {
+                    state.axis = axis;
+                }
+            }
+        }
+        if kb.get_key_down(KeyCode::KeyP) {
+            ctx.time.set_paused(!ctx.time.is_paused());
+        }
+        if kb.get_key_down(KeyCode::KeyN) {
+            ctx.time.step();
+        }
+        if kb.get_key_down(KeyCode::KeyDown) {
+            self.select_next(scene, false);
+        }
+        if kb.get_key_down(KeyCode::KeyUp) {
+            self.select_next(scene, true);
+        }
+        let step = self.state.borrow().edit_step;
+        if kb.get_key(KeyCode::KeyRight) {
+            self.edit(scene, step);
+        }
+        if kb.get_key(KeyCode::KeyLeft) {
+            self.edit(scene, -step);
+        }
+    }
+
+    /// Returns text lines describing the scene
+    fn describe(&self, ctx: &Context, scene: &Scene) -> Vec<String> {
+        let state = self.state.borrow();
+        let mut lines = vec![format!(
+            "Scene '{}'{}",
+            scene.name(),
+            if ctx.time.is_paused() {
+                " [PAUSED]"
+            } else {
+                ""
+            }
+        )];
+        let mut selected_info = None;
+        let mut layer = None;
+        for info in scene.inspect() {
+            if layer != Some(info.id.layer()) {
+                layer = Some(info.id.layer());
+                lines.push(format!("Layer {}", info.id.layer()));
+            }
+            let marker = if state.selected == Some(info.id) {
+                selected_info = Some(info.clone());
+                ">"
+            } else {
+                " "
+            };
+            let status = match info.state {
+                GameObjectState::Pending => " (pending)",
+                GameObjectState::Active => "",
+                GameObjectState::Dead => " (dead)",
+            };
+            lines.push(format!("{marker} {}: {}{status}", info.id.id(), info.name));
+        }
+        if let Some(info) = selected_info {
+            lines.push(String::new());
+            match info.transform {
+                Some(t) => {
+                    let rotation = glm::degrees(&t.euler_angles());
+                    let fields = [
+                        (TransformField::Position, "position", *t.position()),
+                        (TransformField::Rotation, "rotation", rotation),
+                        (TransformField::Scale, "scale", *t.scale()),
+                    ];
+                    for (field, name, v) in fields {
+                        let marker = if field == state.field { "*" } else { " " };
+                        lines.push(format!(
+                            "{marker}{name:<9}{:>9.3}{:>9.3}{:>9.3}",
+                            v.x, v.y, v.z
+                        ));
+                    }
+                    lines.push(format!(
+                        "editing {:?}.{}",
+                        state.field,
+                        ["x", "y", "z"][state.axis]
+                    ));
+                }
+                None => lines.push("no transform".into()),
+            }
+        }
+        lines
+    }
+
+    /// Draws inspector on the right side of the screen, called by Game every frame
+    pub(crate) fn draw(&self, ctx: &Context, scene: &Scene) {
+        let renderer = match self.renderer {
+            Some(ref r) if self.is_open() => r,
+            _ => return,
+        };
+        let lines = self.describe(ctx, scene);
+        let size = self.state.borrow().font_size;
+        let padding = size / 2.0;
+        let screen = (ctx.window.width() as f32, ctx.window.height() as f32);
+        let width = lines
+            .iter()
+            .map(|l| renderer.text_width(l, size))
+            .fold(0.0, f32::max)
+            + padding * 2.0;
+        let height = lines.len() as f32 * size + padding * 2.0;
+        let pos = (screen.0 - width, 0.0);
+        renderer.draw_rect(
+            pos,
+            (width, height),
+            &glm::vec4(0.0, 0.0, 0.0, 0.75),
+            screen,
+        );
+        renderer.draw_text(
+            &lines.join("\n"),
+            (pos.0 + padding, padding),
+            size,
+            &glm::vec4(0.9, 0.9, 0.9, 1.0),
+            screen,
+        );
+    }
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Inspector::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::transform::Transform, GameObject};
+
+    struct Movable {
+        transform: Transform,
+    }
+
+    impl GameObject for Movable {
+        fn name(&self) -> &str {
+            "movable"
+        }
+        fn transform(&self) -> Option<&Transform> {
+            Some(&self.transform)
+        }
+        fn transform_mut(&mut self) -> Option<&mut Transform> {
+            Some(&mut self.transform)
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn scene_with_movable() -> (Context, Scene, GameObjectId) {
+        let mut ctx = Context::default();
+        let mut scene = Scene::default();
+        let id = scene
+            .add_gameobject(
+                Movable {
+                    transform: Transform::default(),
+                },
+                1,
+            )
+            .unwrap();
+        scene.run_loop(&mut ctx).unwrap();
+        (ctx, scene, id)
+    }
+
+    #[test]
+    fn edits_selected_transform() {
+        let (_, mut scene, id) = scene_with_movable();
+        let inspector = Inspector::default();
+        inspector.select(Some(id));
+        inspector.set_edited(TransformField::Position, 1);
+        inspector.edit(&mut scene, 2.0);
+        inspector.set_edited(TransformField::Scale, 0);
+        inspector.edit(&mut scene, 1.0);
+        let go = scene.gameobject_by_id::<Movable>(&id).unwrap();
+        assert_eq!(*go.transform.position(), glm::vec3(0.0, 2.0, 0.0));
+        assert_eq!(*go.transform.scale(), glm::vec3(2.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn describes_scene_and_selection() {
+        let (ctx, scene, id) = scene_with_movable();
+        let inspector = Inspector::default();
+        inspector.select_next(&scene, false);
+        assert_eq!(inspector.selected(), Some(id));
+        let lines = inspector.describe(&ctx, &scene);
+        assert!(lines.contains(&String::from("Layer 1")));
+        assert!(lines.contains(&format!("> {}: movable", id.id())));
+        assert!(lines.iter().any(|l| l.starts_with("*position")));
+    }
+}
@@ -5,28 +5,47 @@
 
 // TODO make these optional as they are not used in core engine
 extern crate gl;
+extern crate gl_utils;
 extern crate glfw;
+extern crate image;
 extern crate nalgebra_glm as glm;
 
+pub mod camera_controllers;
+pub mod collision;
+pub mod collision2d;
 pub mod components;
+pub mod console;
 pub mod context;
+pub mod ecs;
 pub mod error;
 mod event_handler;
 pub mod game;
 pub mod gameobject;
+pub mod geometry;
+pub mod inspector;
 mod input;
+pub mod logger;
+pub mod parallel;
+pub mod physics;
 pub mod scene;
+#[cfg(test)]
+mod test_utils;
 mod timer;
+pub mod tween;
 mod window;
 pub mod prelude;
+pub mod profiler;
+pub mod renderer;
 
 pub use event_handler::Backend;
+pub use console::ConsoleConfig;
 pub use game::{Game, GameConfig};
 pub use gameobject::{GameObject, GameObjectId};
+pub use inspector::InspectorConfig;
 pub use input::keyboard::keys::KeyCode;
 pub use input::mouse::MouseButton;
-pub use scene::Scene;
-pub use window::WindowConfig;
+pub use scene::{ErrorPolicy, Scene, SortMode};
+pub use window::{MonitorInfo, RenderConfig, VideoMode, WindowConfig};
 
 // TODO: better document each module
 // TODO: add fps upper limit
@@ -0,0 +1,204 @@
+//! Module providing Logger struct which is passed with Context to gameobjects
+//! Engine uses it to report scene changes, gameobject lifecycle and backend events
+//! (at debug level) and errors handled by ErrorPolicy.
+//! With "log" feature enabled every message is also forwarded to the log crate facade
+//! (so it can be consumed by any log or tracing subscriber).
+
+use crate::error::{GameError, GameResult};
+use std::{
+    cell::RefCell,
+    fmt,
+    fs::{File, OpenOptions},
+    io::Write,
+    time::Instant,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        };
+        f.pad(name)
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<LogLevel> for log::Level {
+    fn from(value: LogLevel) -> Self {
+        match value {
+            LogLevel::Error => log::Level::Error,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Trace => log::Level::Trace,
+        }
+    }
+}
+
+/// Initial logger configuration
+pub struct LoggerConfig {
+    /// messages less important than this level are skipped
+    pub level: LogLevel,
+    /// overrides level for targets starting with given prefix
+    /// e.g. ("microengine::scene", LogLevel::Debug) or ("scene::Level1", LogLevel::Trace)
+    pub targets: Vec<(String, LogLevel)>,
+    /// messages are appended to this file if set
+    pub file: Option<String>,
+    pub stderr: bool,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        LoggerConfig {
+            level: LogLevel::Info,
+            targets: Vec::new(),
+            file: None,
+            stderr: true,
+        }
+    }
+}
+
+pub struct Logger {
+    level: LogLevel,
+    targets: Vec<(String, LogLevel)>,
+    file: Option<RefCell<File>>,
+    stderr: bool,
+    start: Instant,
+}
+
+impl Logger {
+    pub fn new(cfg: LoggerConfig) -> GameResult<Self> {
+        let file = match cfg.file {
+            Some(ref path) => Some(RefCell::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| {
+                        GameError::EngineError(format!("Couldn't open log file '{path}': {e}"))
+                    })?,
+            )),
+            None => None,
+        };
+        let mut targets = cfg.targets;
+        // the longest (most specific) prefix is checked first
+        targets.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Ok(Logger {
+            level: cfg.level,
+            targets,
+            file,
+            stderr: cfg.stderr,
+            start: Instant::now(),
+        })
+    }
+
+    /// Checks if message with given level and target would be logged
+    pub fn enabled(&self, level: LogLevel, target: &str) -> bool {
+        let max_level = self
+            .targets
+            .iter()
+            .find(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .map_or(self.level, |(_, l)| *l);
+        level <= max_level
+    }
+
+    pub fn log(&self, level: LogLevel, target: &str, msg: impl fmt::Display) {
+        #[cfg(feature = "log")]
+        log::log!(target: target, level.into(), "{msg}");
+        if !self.enabled(level, target) {
+            return;
+        }
+        let line = format!(
+            "[{:>10.3}] {level:<5} {target}: {msg}",
+            self.start.elapsed().as_secs_f64()
+        );
+        if self.stderr {
+            eprintln!("{line}");
+        }
+        if let Some(ref file) = self.file {
+            // there is nowhere to report failed logging
+            let _ = writeln!(file.borrow_mut(), "{line}");
+        }
+    }
+
+    pub fn error(&self, target: &str, msg: impl fmt::Display) {
+        self.log(LogLevel::Error, target, msg);
+    }
+
+    pub fn warn(&self, target: &str, msg: impl fmt::Display) {
+        self.log(LogLevel::Warn, target, msg);
+    }
+
+    pub fn info(&self, target: &str, msg: impl fmt::Display) {
+        self.log(LogLevel::Info, target, msg);
+    }
+
+    pub fn debug(&self, target: &str, msg: impl fmt::Display) {
+        self.log(LogLevel::Debug, target, msg);
+    }
+
+    pub fn trace(&self, target: &str, msg: impl fmt::Display) {
+        self.log(LogLevel::Trace, target, msg);
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Logger::new(LoggerConfig::default()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_target_decides_level() {
+        let log = Logger::new(LoggerConfig {
+            level: LogLevel::Warn,
+            targets: vec![
+                ("microengine".into(), LogLevel::Info),
+                ("microengine::scene".into(), LogLevel::Trace),
+            ],
+            file: None,
+            stderr: false,
+        })
+        .unwrap();
+        assert!(log.enabled(LogLevel::Warn, "game"));
+        assert!(!log.enabled(LogLevel::Info, "game"));
+        assert!(log.enabled(LogLevel::Info, "microengine::game"));
+        assert!(!log.enabled(LogLevel::Debug, "microengine::game"));
+        assert!(log.enabled(LogLevel::Trace, "microengine::scene"));
+    }
+
+    #[test]
+    fn messages_are_written_to_file() {
+        let path = std::env::temp_dir().join("microengine_logger_test.log");
+        let _ = std::fs::remove_file(&path);
+        let log = Logger::new(LoggerConfig {
+            level: LogLevel::Info,
+            targets: Vec::new(),
+            file: Some(path.to_string_lossy().into()),
+            stderr: false,
+        })
+        .unwrap();
+        log.info("test", "hello");
+        log.debug("test", "skipped");
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("INFO  test: hello"));
+        assert!(!content.contains("skipped"));
+    }
+}
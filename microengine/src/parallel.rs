@@ -0,0 +1,79 @@
+//! Provides ParallelGameObject trait used by opt-in multithreaded update.
+//! Gameobjects implementing it and returning Some from GameObject::as_parallel
+//! are updated on rayon's thread pool if their layer is marked parallel
+//! with scene.set_layer_parallel(layer, true). Draw always runs on the main thread.
+//! Parallel gameobjects only get read-only snapshots of the context and the scene,
+//! so they can't add gameobjects or use anything from Context directly.
+
+use crate::{
+    components::transform::Transform,
+    error::GameResult,
+    gameobject::GameObjectId,
+    scene::{GameObjectInfo, GameObjectState},
+    timer::Timer,
+};
+
+pub trait ParallelGameObject: Send {
+    /// called every frame on a worker thread instead of GameObject::update
+    fn par_update(&mut self, _ctx: &ParallelContext, _scene: &SceneView) -> GameResult {
+        Ok(())
+    }
+
+    /// called on a worker thread instead of GameObject::fixed_update
+    fn par_fixed_update(&mut self, _ctx: &ParallelContext, _scene: &SceneView) -> GameResult {
+        Ok(())
+    }
+}
+
+/// Frame data available to parallel gameobjects
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelContext {
+    pub delta_time: f64,
+    pub fixed_time_step: f64,
+    /// frame start timestamp
+    pub timestamp: f64,
+}
+
+impl From<&Timer> for ParallelContext {
+    fn from(value: &Timer) -> Self {
+        ParallelContext {
+            delta_time: value.delta_time(),
+            fixed_time_step: value.fixed_time_step,
+            timestamp: value.get_timestamp(),
+        }
+    }
+}
+
+/// Read-only snapshot of the scene taken before running parallel gameobjects
+/// Transforms are available only for gameobjects exposing them (see GameObject::transform)
+pub struct SceneView {
+    pub(crate) name: String,
+    pub(crate) gameobjects: Vec<GameObjectInfo>,
+}
+
+impl SceneView {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns all gameobjects in the scene ordered by layer
+    pub fn gameobjects(&self) -> &[GameObjectInfo] {
+        &self.gameobjects
+    }
+
+    /// Returns id of the first active gameobject with given name
+    pub fn get_gameobject_id(&self, name: &str) -> Option<GameObjectId> {
+        self.gameobjects
+            .iter()
+            .find(|info| info.state == GameObjectState::Active && info.name == name)
+            .map(|info| info.id)
+    }
+
+    /// Returns transform of gameobject with given id, as it was before this phase started
+    pub fn transform(&self, id: &GameObjectId) -> Option<&Transform> {
+        self.gameobjects
+            .iter()
+            .find(|info| info.id == *id)
+            .and_then(|info| info.transform.as_ref())
+    }
+}
@@ -0,0 +1,615 @@
+//! Rigid body dynamics. Gameobjects returning a rigid body from GameObject::rigid_body
+//! are moved by the scene in every fixed step, after collisions are detected and before
+//! fixed_update. Colliders of gameobjects without a rigid body are static.
+//! Contacts are solved with sequential impulses, bodies which stay still for a while
+//! fall asleep and are woken up by forces, impulses or moving bodies touching them.
+
+use crate::collision::{shapes::WorldShape, Contact};
+
+/// Bodies slower than this (in m/s and rad/s) are considered to be at rest
+pub const SLEEP_VELOCITY: f32 = 0.05;
+/// Time in seconds a body has to be at rest before it falls asleep
+pub const SLEEP_TIME: f32 = 0.5;
+/// Default number of velocity solver iterations, see scene.set_solver_iterations
+pub const DEFAULT_ITERATIONS: usize = 8;
+
+/// Penetration allowed without positional correction, keeps resting contacts stable
+const SLOP: f32 = 0.005;
+/// Part of penetration removed each step
+const CORRECTION: f32 = 0.6;
+/// Contacts approaching slower than this don't bounce, so resting bodies don't jitter
+const BOUNCE_VELOCITY: f32 = 0.5;
+const MIN_MASS: f32 = 1e-4;
+
+pub fn default_gravity() -> glm::Vec3 {
+    glm::vec3(0.0, -9.81, 0.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyType {
+    /// moved by forces, gravity and collisions
+    #[default]
+    Dynamic,
+    /// moved only by its velocity, pushes dynamic bodies like an infinitely heavy one
+    Kinematic,
+}
+
+/// Rigid body returned by GameObject::rigid_body, its center of mass
+/// is the gameobject's position
+#[derive(Debug, Clone, PartialEq)]
+pub struct RigidBody {
+    pub body_type: BodyType,
+    mass: f32,
+    velocity: glm::Vec3,
+    angular_velocity: glm::Vec3,
+    force: glm::Vec3,
+    torque: glm::Vec3,
+    /// multiplies scene's gravity
+    pub gravity_scale: f32,
+    /// bounciness from 0 to 1, the bouncier body of a pair is used
+    pub restitution: f32,
+    /// friction coefficient, pairs use geometric mean of both bodies
+    pub friction: f32,
+    /// part of velocity lost each second
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// collisions don't rotate bodies with locked rotation
+    pub lock_rotation: bool,
+    pub can_sleep: bool,
+    sleeping: bool,
+    rest_time: f32,
+}
+
+impl RigidBody {
+    pub fn new(mass: f32) -> Self {
+        RigidBody {
+            body_type: BodyType::Dynamic,
+            mass: mass.max(MIN_MASS),
+            velocity: glm::Vec3::zeros(),
+            angular_velocity: glm::Vec3::zeros(),
+            force: glm::Vec3::zeros(),
+            torque: glm::Vec3::zeros(),
+            gravity_scale: 1.0,
+            restitution: 0.0,
+            friction: 0.5,
+            linear_damping: 0.0,
+            angular_damping: 0.05,
+            lock_rotation: false,
+            can_sleep: true,
+            sleeping: false,
+            rest_time: 0.0,
+        }
+    }
+
+    pub fn kinematic() -> Self {
+        RigidBody {
+            body_type: BodyType::Kinematic,
+            ..RigidBody::new(1.0)
+        }
+    }
+
+    pub fn restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn friction(mut self, friction: f32) -> Self {
+        self.friction = friction.max(0.0);
+        self
+    }
+
+    pub fn damping(mut self, linear: f32, angular: f32) -> Self {
+        self.linear_damping = linear.max(0.0);
+        self.angular_damping = angular.max(0.0);
+        self
+    }
+
+    pub fn gravity_scale(mut self, scale: f32) -> Self {
+        self.gravity_scale = scale;
+        self
+    }
+
+    pub fn lock_rotation(mut self, lock: bool) -> Self {
+        self.lock_rotation = lock;
+        self
+    }
+
+    pub fn can_sleep(mut self, can_sleep: bool) -> Self {
+        self.can_sleep = can_sleep;
+        self
+    }
+
+    pub fn mass(&self) -> f32 {
+        self.mass
+    }
+
+    pub fn set_mass(&mut self, mass: f32) {
+        self.mass = mass.max(MIN_MASS);
+    }
+
+    /// Returns 0 for kinematic bodies
+    pub fn inverse_mass(&self) -> f32 {
+        match self.body_type {
+            BodyType::Dynamic => 1.0 / self.mass,
+            BodyType::Kinematic => 0.0,
+        }
+    }
+
+    pub fn velocity(&self) -> &glm::Vec3 {
+        &self.velocity
+    }
+
+    pub fn set_velocity(&mut self, velocity: glm::Vec3) {
+        self.velocity = velocity;
+        self.wake_up();
+    }
+
+    /// Returns angular velocity, its direction is the rotation axis and length is speed in rad/s
+    pub fn angular_velocity(&self) -> &glm::Vec3 {
+        &self.angular_velocity
+    }
+
+    pub fn set_angular_velocity(&mut self, angular_velocity: glm::Vec3) {
+        self.angular_velocity = angular_velocity;
+        self.wake_up();
+    }
+
+    /// Adds force applied during the next physics step
+    pub fn add_force(&mut self, force: glm::Vec3) {
+        self.force += force;
+        self.wake_up();
+    }
+
+    pub fn add_torque(&mut self, torque: glm::Vec3) {
+        self.torque += torque;
+        self.wake_up();
+    }
+
+    /// Changes velocity immediately, like a hit would
+    pub fn add_impulse(&mut self, impulse: glm::Vec3) {
+        self.velocity += impulse * self.inverse_mass();
+        self.wake_up();
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    pub fn wake_up(&mut self) {
+        self.sleeping = false;
+        self.rest_time = 0.0;
+    }
+
+    /// Stops the body until something wakes it up
+    pub fn sleep(&mut self) {
+        self.sleeping = true;
+        self.velocity = glm::Vec3::zeros();
+        self.angular_velocity = glm::Vec3::zeros();
+    }
+
+    fn is_moving(&self) -> bool {
+        let threshold = SLEEP_VELOCITY * SLEEP_VELOCITY;
+        !self.sleeping
+            && (self.velocity.magnitude_squared() > threshold
+                || self.angular_velocity.magnitude_squared() > threshold)
+    }
+}
+
+/// Returns moment of inertia of a solid shape, approximated with a single number
+/// (average of the principal moments), shapes other than spheres and boxes
+/// are treated as their bounding box
+pub(crate) fn inertia(shape: Option<&WorldShape>, mass: f32) -> f32 {
+    let half_extents = match shape {
+        Some(WorldShape::Sphere { radius, .. }) => return 0.4 * mass * radius * radius,
+        Some(WorldShape::Box(b)) => b.half_extents,
+        Some(shape) => shape.bounds().extents(),
+        None => return 0.4 * mass,
+    };
+    2.0 / 9.0 * mass * half_extents.magnitude_squared()
+}
+
+/// Rigid body of a gameobject during a physics step, bodies without rigid body are static
+pub(crate) struct BodyState {
+    pub body: Option<RigidBody>,
+    pub position: glm::Vec3,
+    pub rotation: glm::Quat,
+    pub inverse_inertia: f32,
+    /// body with a 2D collider, it moves in the XY plane and rotates around the z axis
+    pub planar: bool,
+}
+
+impl BodyState {
+    pub fn new(
+        body: Option<RigidBody>,
+        position: glm::Vec3,
+        rotation: glm::Quat,
+        shape: Option<&WorldShape>,
+    ) -> Self {
+        let inverse_inertia = match body {
+            Some(ref b) if b.body_type == BodyType::Dynamic && !b.lock_rotation => {
+                1.0 / inertia(shape, b.mass).max(MIN_MASS)
+            }
+            _ => 0.0,
+        };
+        BodyState {
+            body,
+            position,
+            rotation,
+            inverse_inertia,
+            planar: false,
+        }
+    }
+
+    /// Makes the body planar, inertia around the z axis is computed
+    /// from half extents of its 2D collider's bounds
+    pub fn planar(mut self, half_extents: glm::Vec2) -> Self {
+        self.planar = true;
+        if let Some(b) = self.body.as_ref().filter(|_| self.inverse_inertia > 0.0) {
+            let inertia = b.mass * half_extents.magnitude_squared() / 3.0;
+            self.inverse_inertia = 1.0 / inertia.max(MIN_MASS);
+        }
+        self
+    }
+
+    /// Returns body moved by the solver, dynamic and awake
+    fn dynamic(&mut self) -> Option<&mut RigidBody> {
+        self.body
+            .as_mut()
+            .filter(|b| b.body_type == BodyType::Dynamic && !b.sleeping)
+    }
+
+    fn inverse_mass(&self) -> f32 {
+        match self.body {
+            Some(ref b) if !b.sleeping => b.inverse_mass(),
+            _ => 0.0,
+        }
+    }
+
+    fn inverse_inertia(&self) -> f32 {
+        match self.body {
+            Some(ref b) if !b.sleeping => self.inverse_inertia,
+            _ => 0.0,
+        }
+    }
+
+    fn velocity_at(&self, r: &glm::Vec3) -> glm::Vec3 {
+        match self.body {
+            Some(ref b) if !b.sleeping => b.velocity + glm::cross(&b.angular_velocity, r),
+            _ => glm::Vec3::zeros(),
+        }
+    }
+
+    fn apply_impulse(&mut self, impulse: &glm::Vec3, r: &glm::Vec3) {
+        let inverse_inertia = self.inverse_inertia;
+        if let Some(body) = self.dynamic() {
+            body.velocity += impulse * body.inverse_mass();
+            body.angular_velocity += glm::cross(r, impulse) * inverse_inertia;
+        }
+    }
+}
+
+/// Contact between bodies a and b prepared for the velocity solver
+struct Constraint {
+    a: usize,
+    b: usize,
+    contact: Contact,
+    ra: glm::Vec3,
+    rb: glm::Vec3,
+    friction: f32,
+    /// separating velocity the solver aims for
+    bounce: f32,
+    normal_impulse: f32,
+    tangent_impulse: glm::Vec3,
+}
+
+pub(crate) struct PhysicsWorld {
+    pub gravity: glm::Vec3,
+    pub iterations: usize,
+}
+
+impl PhysicsWorld {
+    pub fn new() -> Self {
+        PhysicsWorld {
+            gravity: default_gravity(),
+            iterations: DEFAULT_ITERATIONS,
+        }
+    }
+
+    /// Advances bodies by dt, contacts are (index of a, index of b, contact from a to b)
+    pub fn step(&self, states: &mut [BodyState], contacts: &[(usize, usize, Contact)], dt: f32) {
+        self.wake_touched(states, contacts);
+        // bounce is computed from velocities before gravity is applied, so it keeps energy
+        let mut constraints = contacts
+            .iter()
+            .filter_map(|&(a, b, contact)| Self::constraint(states, a, b, contact))
+            .collect::<Vec<_>>();
+        for state in states.iter_mut() {
+            let gravity = self.gravity;
+            let inverse_inertia = state.inverse_inertia;
+            let Some(body) = state.dynamic() else {
+                continue;
+            };
+            body.velocity += (gravity * body.gravity_scale + body.force * body.inverse_mass()) * dt;
+            body.angular_velocity += body.torque * inverse_inertia * dt;
+            body.velocity /= 1.0 + dt * body.linear_damping;
+            body.angular_velocity /= 1.0 + dt * body.angular_damping;
+        }
+        for body in states.iter_mut().filter_map(|s| s.body.as_mut()) {
+            body.force = glm::Vec3::zeros();
+            body.torque = glm::Vec3::zeros();
+        }
+
+        for _ in 0..self.iterations {
+            for c in constraints.iter_mut() {
+                Self::solve(states, c);
+            }
+        }
+
+        for state in states.iter_mut() {
+            let planar = state.planar;
+            let Some(body) = state.body.as_mut().filter(|b| !b.sleeping) else {
+                continue;
+            };
+            if planar {
+                body.velocity.z = 0.0;
+                body.angular_velocity.x = 0.0;
+                body.angular_velocity.y = 0.0;
+            }
+            state.position += body.velocity * dt;
+            let (angle, axis) = (
+                body.angular_velocity.magnitude() * dt,
+                body.angular_velocity,
+            );
+            if angle > f32::EPSILON && !body.lock_rotation {
+                let spin = glm::quat_angle_axis(angle, &axis.normalize());
+                state.rotation = (spin * state.rotation).normalize();
+            }
+        }
+        for c in constraints.iter() {
+            let (ma, mb) = (states[c.a].inverse_mass(), states[c.b].inverse_mass());
+            let correction = (c.contact.depth - SLOP).max(0.0) * CORRECTION / (ma + mb);
+            states[c.a].position -= c.contact.normal * correction * ma;
+            states[c.b].position += c.contact.normal * correction * mb;
+        }
+        Self::update_sleep(states, dt);
+    }
+
+    /// Wakes sleeping bodies touched by moving ones
+    fn wake_touched(&self, states: &mut [BodyState], contacts: &[(usize, usize, Contact)]) {
+        for &(a, b, _) in contacts {
+            let moving = |s: &BodyState| s.body.as_ref().is_some_and(RigidBody::is_moving);
+            let (wake_a, wake_b) = (moving(&states[b]), moving(&states[a]));
+            for (i, wake) in [(a, wake_a), (b, wake_b)] {
+                if let Some(body) = states[i].body.as_mut().filter(|b| wake && b.sleeping) {
+                    body.wake_up();
+                }
+            }
+        }
+    }
+
+    fn constraint(
+        states: &[BodyState],
+        a: usize,
+        b: usize,
+        contact: Contact,
+    ) -> Option<Constraint> {
+        let (sa, sb) = (&states[a], &states[b]);
+        if sa.inverse_mass() + sb.inverse_mass() == 0.0 {
+            return None;
+        }
+        let ra = contact.point - sa.position;
+        let rb = contact.point - sb.position;
+        let (restitution, friction) = match (&sa.body, &sb.body) {
+            (Some(a), Some(b)) => (
+                a.restitution.max(b.restitution),
+                (a.friction * b.friction).sqrt(),
+            ),
+            (Some(body), None) | (None, Some(body)) => (body.restitution, body.friction),
+            (None, None) => return None,
+        };
+        let approach = glm::dot(
+            &(sb.velocity_at(&rb) - sa.velocity_at(&ra)),
+            &contact.normal,
+        );
+        let bounce = match approach < -BOUNCE_VELOCITY {
+            true => -approach * restitution,
+            false => 0.0,
+        };
+        Some(Constraint {
+            a,
+            b,
+            contact,
+            ra,
+            rb,
+            friction,
+            bounce,
+            normal_impulse: 0.0,
+            tangent_impulse: glm::Vec3::zeros(),
+        })
+    }
+
+    /// Returns inverse of the effective mass of the pair along direction
+    fn effective_mass(states: &[BodyState], c: &Constraint, direction: &glm::Vec3) -> f32 {
+        let (sa, sb) = (&states[c.a], &states[c.b]);
+        let k = sa.inverse_mass()
+            + sb.inverse_mass()
+            + sa.inverse_inertia() * glm::cross(&c.ra, direction).magnitude_squared()
+            + sb.inverse_inertia() * glm::cross(&c.rb, direction).magnitude_squared();
+        match k > f32::EPSILON {
+            true => 1.0 / k,
+            false => 0.0,
+        }
+    }
+
+    fn apply(states: &mut [BodyState], c: &Constraint, impulse: &glm::Vec3) {
+        states[c.a].apply_impulse(&-impulse, &c.ra);
+        states[c.b].apply_impulse(impulse, &c.rb);
+    }
+
+    fn solve(states: &mut [BodyState], c: &mut Constraint) {
+        let n = c.contact.normal;
+        let relative = |states: &[BodyState], c: &Constraint| {
+            states[c.b].velocity_at(&c.rb) - states[c.a].velocity_at(&c.ra)
+        };
+
+        // normal impulse, accumulated impulse can't pull the bodies together
+        let vn = glm::dot(&relative(states, c), &n);
+        let lambda = (c.bounce - vn) * Self::effective_mass(states, c, &n);
+        let total = (c.normal_impulse + lambda).max(0.0);
+        let lambda = total - c.normal_impulse;
+        c.normal_impulse = total;
+        Self::apply(states, c, &(n * lambda));
+
+        // friction impulse, limited by the normal impulse
+        let v = relative(states, c);
+        let tangent_velocity = v - n * glm::dot(&v, &n);
+        let speed = tangent_velocity.magnitude();
+        if speed < f32::EPSILON {
+            return;
+        }
+        let t = tangent_velocity / speed;
+        let lambda = -speed * Self::effective_mass(states, c, &t);
+        let mut total = c.tangent_impulse + t * lambda;
+        let limit = c.friction * c.normal_impulse;
+        if total.magnitude() > limit {
+            total = total.normalize() * limit;
+        }
+        let impulse = total - c.tangent_impulse;
+        c.tangent_impulse = total;
+        Self::apply(states, c, &impulse);
+    }
+
+    fn update_sleep(states: &mut [BodyState], dt: f32) {
+        for body in states.iter_mut().filter_map(BodyState::dynamic) {
+            if !body.can_sleep || body.is_moving() {
+                body.rest_time = 0.0;
+                continue;
+            }
+            body.rest_time += dt;
+            if body.rest_time >= SLEEP_TIME {
+                body.sleep();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.02;
+
+    fn ball(y: f32, body: RigidBody) -> BodyState {
+        let center = glm::vec3(0.0, y, 0.0);
+        let shape = WorldShape::Sphere {
+            center,
+            radius: 0.5,
+        };
+        BodyState::new(Some(body), center, glm::quat_identity(), Some(&shape))
+    }
+
+    fn floor() -> BodyState {
+        BodyState::new(None, glm::vec3(0.0, -0.5, 0.0), glm::quat_identity(), None)
+    }
+
+    /// Contact of a ball with the floor whose top is at y = 0
+    fn floor_contact(states: &[BodyState]) -> Vec<(usize, usize, Contact)> {
+        let y = states[1].position.y;
+        let depth = 0.5 - y;
+        match depth >= 0.0 {
+            true => vec![(
+                0,
+                1,
+                Contact {
+                    point: glm::vec3(states[1].position.x, y - 0.5 + depth / 2.0, 0.0),
+                    normal: glm::Vec3::y(),
+                    depth,
+                },
+            )],
+            false => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn bodies_fall_with_gravity_and_forces() {
+        let world = PhysicsWorld::new();
+        let mut states = [
+            ball(10.0, RigidBody::new(2.0)),
+            ball(10.0, RigidBody::kinematic()),
+        ];
+        states[1]
+            .body
+            .as_mut()
+            .unwrap()
+            .set_velocity(glm::vec3(1.0, 0.0, 0.0));
+        for _ in 0..50 {
+            states[0]
+                .body
+                .as_mut()
+                .unwrap()
+                .add_force(glm::vec3(4.0, 0.0, 0.0));
+            world.step(&mut states, &[], DT);
+        }
+        let falling = states[0].body.as_ref().unwrap();
+        assert!((falling.velocity().y + 9.81).abs() < 1e-3);
+        assert!((falling.velocity().x - 2.0).abs() < 1e-3);
+        assert!((states[0].position.y - (10.0 - 9.81 * 1.02 / 2.0)).abs() < 1e-2);
+        assert!((states[1].position - glm::vec3(1.0, 10.0, 0.0)).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn restitution_bounces_and_friction_slows_down() {
+        let world = PhysicsWorld::new();
+        let body = RigidBody::new(1.0).restitution(1.0).lock_rotation(true);
+        let mut states = [floor(), ball(0.5, body)];
+        states[1]
+            .body
+            .as_mut()
+            .unwrap()
+            .set_velocity(glm::vec3(0.0, -5.0, 0.0));
+        let contacts = floor_contact(&states);
+        world.step(&mut states, &contacts, DT);
+        let v = states[1].body.as_ref().unwrap().velocity().y;
+        assert!((v - 5.0).abs() < 1e-3, "{v}");
+
+        let body = RigidBody::new(1.0).friction(0.5).lock_rotation(true);
+        let mut states = [floor(), ball(0.5, body)];
+        states[1]
+            .body
+            .as_mut()
+            .unwrap()
+            .set_velocity(glm::vec3(2.0, 0.0, 0.0));
+        for _ in 0..10 {
+            let contacts = floor_contact(&states);
+            world.step(&mut states, &contacts, DT);
+        }
+        // friction decelerates by mu * g
+        let v = states[1].body.as_ref().unwrap().velocity();
+        assert!((v.x - (2.0 - 0.5 * 9.81 * 10.0 * DT)).abs() < 0.05, "{v}");
+        assert!(states[1].position.y > 0.45);
+    }
+
+    #[test]
+    fn resting_bodies_fall_asleep_and_wake_up() {
+        let world = PhysicsWorld::new();
+        let mut states = [floor(), ball(0.5, RigidBody::new(1.0))];
+        for _ in 0..60 {
+            let contacts = floor_contact(&states);
+            world.step(&mut states, &contacts, DT);
+        }
+        assert!(states[1].body.as_ref().unwrap().is_sleeping());
+        assert!((states[1].position.y - 0.5).abs() < 0.02);
+        // sleeping bodies ignore gravity
+        let y = states[1].position.y;
+        world.step(&mut states, &[], DT);
+        assert_eq!(states[1].position.y, y);
+        states[1]
+            .body
+            .as_mut()
+            .unwrap()
+            .add_impulse(glm::vec3(0.0, 1.0, 0.0));
+        world.step(&mut states, &[], DT);
+        assert!(states[1].position.y > y);
+        assert!(!states[1].body.as_ref().unwrap().is_sleeping());
+    }
+}
@@ -1,8 +1,15 @@
 /// Most of the stuff you need to implement gameobjects
 
 pub use crate::gameobject::{GameObject, GameObjectId};
+pub use crate::parallel::{ParallelContext, ParallelGameObject, SceneView};
 pub use crate::scene::Scene;
 pub use crate::context::Context;
-pub use crate::error::GameResult;
+pub use crate::logger::LogLevel;
+pub use crate::ecs::{Entity, World};
+pub use crate::error::{GameResult, Phase};
 pub use crate::input::keyboard::keys::KeyCode;
 pub use crate::input::mouse::MouseButton;
+pub use crate::tween::{Animation, Ease, Loop, Tween, TweenValue};
+pub use crate::collision::{Collider, Collision, RaycastHit, Shape};
+pub use crate::collision2d::{character::CharacterController2D, Collider2D, RaycastHit2D, Shape2D};
+pub use crate::physics::RigidBody;
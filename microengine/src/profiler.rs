@@ -0,0 +1,254 @@
+//! Module providing Profiler struct which is passed with Context to gameobjects
+//! When enabled it measures time spent in each phase of the game loop, in each layer
+//! and in each gameobject (keyed by its name).
+//! Measurements can be also recorded and saved as a Chrome trace file
+//! (open it with chrome://tracing or https://ui.perfetto.dev).
+
+use crate::error::{GameResult, Phase};
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    time::Instant,
+};
+
+/// Timings (in seconds) measured during a single frame
+#[derive(Debug, Clone, Default)]
+pub struct FrameStats {
+    pub frame_time: f64,
+    /// time spent in each game loop phase (backend events, fixed_update, update, draw ...)
+    pub phases: HashMap<String, f64>,
+    /// time spent in gameobjects on each layer
+    pub layers: Vec<f64>,
+    /// time spent in gameobjects with given name
+    pub gameobjects: HashMap<String, f64>,
+}
+
+struct TraceEvent {
+    name: String,
+    category: &'static str,
+    start: f64,
+    duration: f64,
+}
+
+struct ProfilerState {
+    enabled: bool,
+    recording: bool,
+    frame_start: Instant,
+    current: FrameStats,
+    last: FrameStats,
+    trace: Vec<TraceEvent>,
+}
+
+pub struct Profiler {
+    start: Instant,
+    state: RefCell<ProfilerState>,
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        let start = Instant::now();
+        Profiler {
+            start,
+            state: RefCell::new(ProfilerState {
+                enabled,
+                recording: false,
+                frame_start: start,
+                current: Default::default(),
+                last: Default::default(),
+                trace: Vec::new(),
+            }),
+        }
+    }
+
+    /// Enable or disable measurements, disabled profiler has almost no overhead
+    pub fn set_enabled(&self, enabled: bool) {
+        self.state.borrow_mut().enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.borrow().enabled
+    }
+
+    /// Returns timings of the last finished frame
+    pub fn last_frame(&self) -> Ref<'_, FrameStats> {
+        Ref::map(self.state.borrow(), |s| &s.last)
+    }
+
+    /// Starts recording measurements for Chrome trace export, enables profiler
+    pub fn start_trace(&self) {
+        let mut state = self.state.borrow_mut();
+        state.enabled = true;
+        state.recording = true;
+        state.trace.clear();
+    }
+
+    /// Stops recording measurements, recorded events are kept until next start_trace
+    pub fn stop_trace(&self) {
+        self.state.borrow_mut().recording = false;
+    }
+
+    /// Saves recorded measurements as a Chrome trace JSON file
+    pub fn save_trace(&self, path: &str) -> GameResult {
+        fs::write(path, self.trace_json())?;
+        Ok(())
+    }
+
+    /// Returns recorded measurements in Chrome trace JSON format
+    pub fn trace_json(&self) -> String {
+        let state = self.state.borrow();
+        let mut json = String::from("{\"traceEvents\":[");
+        for (i, e) in state.trace.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            // timestamps are in microseconds
+            let _ = write!(
+                json,
+                "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":0,\"tid\":0}}",
+                escape_json(&e.name),
+                e.category,
+                e.start * 1e6,
+                e.duration * 1e6
+            );
+        }
+        json.push_str("]}");
+        json
+    }
+
+    /// Returns current time if profiler is enabled
+    pub(crate) fn now(&self) -> Option<Instant> {
+        if self.state.borrow().enabled {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn begin_frame(&self) {
+        self.state.borrow_mut().frame_start = Instant::now();
+    }
+
+    pub(crate) fn end_frame(&self) {
+        let mut state = self.state.borrow_mut();
+        if !state.enabled {
+            return;
+        }
+        let frame_start = state.frame_start;
+        state.current.frame_time = frame_start.elapsed().as_secs_f64();
+        state.last = std::mem::take(&mut state.current);
+        self.push_trace(&mut state, "frame".into(), "frame", frame_start);
+    }
+
+    /// Records time spent in a game loop phase which started at given instant
+    pub(crate) fn record_phase(&self, phase: &str, start: Option<Instant>) {
+        let Some(start) = start else {
+            return;
+        };
+        let mut state = self.state.borrow_mut();
+        *state.current.phases.entry(phase.into()).or_default() += start.elapsed().as_secs_f64();
+        self.push_trace(&mut state, phase.into(), "phase", start);
+    }
+
+    /// Records time spent in a single gameobject method which started at given instant
+    pub(crate) fn record_gameobject(
+        &self,
+        name: &str,
+        layer: usize,
+        phase: Phase,
+        start: Option<Instant>,
+    ) {
+        let Some(start) = start else {
+            return;
+        };
+        let mut state = self.state.borrow_mut();
+        let duration = start.elapsed().as_secs_f64();
+        if state.current.layers.len() <= layer {
+            state.current.layers.resize(layer + 1, 0.0);
+        }
+        state.current.layers[layer] += duration;
+        *state.current.gameobjects.entry(name.into()).or_default() += duration;
+        self.push_trace(&mut state, name.into(), phase.as_str(), start);
+    }
+
+    fn push_trace(
+        &self,
+        state: &mut ProfilerState,
+        name: String,
+        category: &'static str,
+        start: Instant,
+    ) {
+        if state.recording {
+            state.trace.push(TraceEvent {
+                name,
+                category,
+                start: start.duration_since(self.start).as_secs_f64(),
+                duration: start.elapsed().as_secs_f64(),
+            });
+        }
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler::new(false)
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_profiler_records_nothing() {
+        let p = Profiler::default();
+        p.begin_frame();
+        p.record_gameobject("go", 0, Phase::Update, p.now());
+        p.end_frame();
+        assert!(p.last_frame().gameobjects.is_empty());
+    }
+
+    #[test]
+    fn records_gameobjects_layers_and_phases() {
+        let p = Profiler::new(true);
+        p.begin_frame();
+        p.record_gameobject("go", 2, Phase::Update, p.now());
+        p.record_gameobject("go", 2, Phase::Draw, p.now());
+        p.record_phase("update", p.now());
+        p.end_frame();
+        let frame = p.last_frame();
+        assert!(frame.gameobjects.contains_key("go"));
+        assert_eq!(frame.layers.len(), 3);
+        assert!(frame.phases.contains_key("update"));
+    }
+
+    #[test]
+    fn trace_is_exported_as_chrome_trace_json() {
+        let p = Profiler::new(false);
+        p.start_trace();
+        p.begin_frame();
+        p.record_gameobject("\"quoted\"", 0, Phase::Update, p.now());
+        p.end_frame();
+        p.stop_trace();
+        let json = p.trace_json();
+        assert!(json.starts_with("{\"traceEvents\":["));
+        assert!(json.contains("\"name\":\"\\\"quoted\\\"\",\"cat\":\"update\",\"ph\":\"X\""));
+        assert!(json.contains("\"name\":\"frame\""));
+    }
+}
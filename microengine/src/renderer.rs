@@ -0,0 +1,384 @@
+//! Renderer subsystem drawing queued draw commands.
+//! Instead of issuing GL calls in GameObject::draw, gameobjects can submit DrawCommands
+//! (mesh, material and model matrix) with ctx.renderer.submit() and cameras with
+//! ctx.renderer.add_camera(). At the end of each frame (after all gameobjects were drawn)
+//! the queue is drawn once for each camera, sorted to minimize state changes:
+//! opaque commands front-to-back grouped by program, material and mesh,
+//! transparent commands back-to-front. Commands whose bounds are outside camera's frustum
+//! are skipped. The old draw hook still works for custom rendering.
+//!
+//! Shaders used by materials get these uniforms from the renderer:
+//! `mat4 projection` (view-projection matrix), `vec3 viewer_pos` and `mat4 model`.
+
+use crate::{
+    components::camera::{layer_visible, Camera, ClearFlags},
+    geometry::{Aabb, Frustum},
+    window::Window,
+};
+use gl_utils::{CompiledProgram, Mesh, Texture, Uniform};
+use std::{cell::RefCell, rc::Rc};
+
+/// Shader program with its uniform values and textures
+pub struct Material {
+    pub program: Rc<CompiledProgram>,
+    pub uniforms: Vec<(String, Uniform)>,
+    /// (sampler uniform name, texture), texture units are assigned in order
+    pub textures: Vec<(String, Rc<Texture>)>,
+    /// transparent materials are drawn after opaque ones, back-to-front
+    pub transparent: bool,
+}
+
+impl Material {
+    pub fn new(program: Rc<CompiledProgram>) -> Self {
+        Material {
+            program,
+            uniforms: Vec::new(),
+            textures: Vec::new(),
+            transparent: false,
+        }
+    }
+
+    /// Sets uniform value, replacing the previous one with the same name
+    pub fn set_uniform(&mut self, name: &str, value: Uniform) {
+        match self.uniforms.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.uniforms.push((name.into(), value)),
+        }
+    }
+
+    fn apply(&self) {
+        for (name, value) in self.uniforms.iter() {
+            self.program.set_uniform(name, value);
+        }
+        for (unit, (name, texture)) in self.textures.iter().enumerate() {
+            texture.bind_texture_unit(unit as u32);
+            self.program.set_uniform(name, &Uniform::Int(unit as i32));
+        }
+    }
+}
+
+/// Single mesh drawn with given material
+pub struct DrawCommand {
+    pub mesh: Rc<Mesh>,
+    pub material: Rc<Material>,
+    /// local to world matrix
+    pub model: glm::Mat4,
+    /// lower keys are drawn first, opaque commands are still drawn before transparent ones
+    pub sort_key: i32,
+    /// layer used for camera culling, None is replaced on submit with scene layer
+    /// of the gameobject drawing it (layer 0 outside of draw)
+    pub layer: Option<usize>,
+    /// model space bounds used for frustum culling, None means command is never culled
+    pub bounds: Option<Aabb>,
+}
+
+impl DrawCommand {
+    pub fn new(mesh: Rc<Mesh>, material: Rc<Material>, model: glm::Mat4) -> Self {
+        DrawCommand {
+            bounds: Some(mesh.bounds().into()),
+            mesh,
+            material,
+            model,
+            sort_key: 0,
+            layer: None,
+        }
+    }
+}
+
+/// Camera data the queue is rendered with
+#[derive(Debug, Clone)]
+pub struct RenderView {
+    pub view_projection: glm::Mat4,
+    pub position: glm::Vec3,
+    /// normalized (x, y, width, height) of the window, (0, 0) is bottom-left corner
+    pub viewport: glm::Vec4,
+    /// views with lower priority are rendered first
+    pub priority: i32,
+    pub clear: ClearFlags,
+    /// bit n set means commands from layer n are rendered
+    pub culling_mask: u32,
+}
+
+impl From<&Camera> for RenderView {
+    fn from(value: &Camera) -> Self {
+        RenderView {
+            view_projection: value.world_to_projection_matrix(),
+            position: *value.transform.position(),
+            viewport: value.viewport,
+            priority: value.priority,
+            clear: value.clear,
+            culling_mask: value.culling_mask,
+        }
+    }
+}
+
+/// Statistics of the last rendered frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub views: usize,
+    pub draw_calls: usize,
+    /// number of program or material changes
+    pub batches: usize,
+    /// number of commands skipped because they were outside of view's frustum
+    pub culled: usize,
+}
+
+/// Data of a command needed to sort it
+#[derive(Debug, Clone, Copy)]
+struct SortItem {
+    transparent: bool,
+    sort_key: i32,
+    program: usize,
+    material: usize,
+    mesh: usize,
+    distance: f32,
+}
+
+impl SortItem {
+    fn new(cmd: &DrawCommand, view_position: &glm::Vec3) -> Self {
+        let position = glm::vec4_to_vec3(&cmd.model.column(3).into());
+        SortItem {
+            transparent: cmd.material.transparent,
+            sort_key: cmd.sort_key,
+            program: Rc::as_ptr(&cmd.material.program) as usize,
+            material: Rc::as_ptr(&cmd.material) as usize,
+            mesh: Rc::as_ptr(&cmd.mesh) as usize,
+            distance: glm::distance2(&position, view_position),
+        }
+    }
+}
+
+/// Converts normalized viewport to (x, y, width, height) in pixels
+fn viewport_rect(viewport: &glm::Vec4, width: f32, height: f32) -> (i32, i32, i32, i32) {
+    (
+        (viewport.x * width).round() as i32,
+        (viewport.y * height).round() as i32,
+        (viewport.z * width).round() as i32,
+        (viewport.w * height).round() as i32,
+    )
+}
+
+/// Clears the part of the window covered by view
+fn clear_view(clear: ClearFlags, rect: (i32, i32, i32, i32), window: &Window) {
+    let mask = match clear {
+        ClearFlags::Nothing => return,
+        ClearFlags::DepthOnly => gl::DEPTH_BUFFER_BIT,
+        ClearFlags::Color(_) => gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
+    };
+    unsafe {
+        gl::Enable(gl::SCISSOR_TEST);
+        gl::Scissor(rect.0, rect.1, rect.2, rect.3);
+        if let ClearFlags::Color(c) = clear {
+            gl::ClearColor(c.x, c.y, c.z, c.w);
+        }
+        gl::Clear(mask);
+        let (r, g, b, a) = window.clear_color;
+        gl::ClearColor(r, g, b, a);
+        gl::Disable(gl::SCISSOR_TEST);
+    }
+}
+
+/// Returns order in which items should be drawn
+fn draw_order(items: &[SortItem]) -> Vec<usize> {
+    let mut order = (0..items.len()).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&items[a], &items[b]);
+        let state = |i: &SortItem| (i.program, i.material, i.mesh);
+        a.transparent
+            .cmp(&b.transparent)
+            .then(a.sort_key.cmp(&b.sort_key))
+            .then_with(|| match a.transparent {
+                true => b.distance.total_cmp(&a.distance),
+                // opaque geometry is batched first and only then sorted front-to-back
+                false => state(a)
+                    .cmp(&state(b))
+                    .then(a.distance.total_cmp(&b.distance)),
+            })
+    });
+    order
+}
+
+#[derive(Default)]
+struct RendererState {
+    views: Vec<RenderView>,
+    /// views rendered in the previous frame
+    last_views: Vec<RenderView>,
+    commands: Vec<DrawCommand>,
+    stats: RenderStats,
+    /// scene layer whose gameobjects are being drawn
+    draw_layer: usize,
+}
+
+#[derive(Default)]
+pub struct Renderer {
+    state: RefCell<RendererState>,
+}
+
+impl Renderer {
+    /// Queues given command to be drawn at the end of this frame
+    pub fn submit(&self, mut cmd: DrawCommand) {
+        let mut state = self.state.borrow_mut();
+        cmd.layer = cmd.layer.or(Some(state.draw_layer));
+        state.commands.push(cmd);
+    }
+
+    /// Sets layer of commands submitted without one, called by scene while drawing layers
+    pub(crate) fn set_draw_layer(&self, layer: usize) {
+        self.state.borrow_mut().draw_layer = layer;
+    }
+
+    /// Renders this frame's queue from given camera's point of view
+    pub fn add_camera(&self, camera: &Camera) {
+        self.add_view(RenderView::from(camera));
+    }
+
+    pub fn add_view(&self, view: RenderView) {
+        self.state.borrow_mut().views.push(view);
+    }
+
+    /// Renders this frame with previous frame's views if no camera was added,
+    /// used while the game is paused and gameobjects' update isn't called
+    pub(crate) fn keep_last_views(&self) {
+        let mut state = self.state.borrow_mut();
+        if state.views.is_empty() {
+            state.views = state.last_views.clone();
+        }
+    }
+
+    pub fn last_stats(&self) -> RenderStats {
+        self.state.borrow().stats
+    }
+
+    /// Draws all queued commands for each view and clears the queue
+    pub(crate) fn render(&self, window: &Window) {
+        let mut state = self.state.borrow_mut();
+        let mut views = std::mem::take(&mut state.views);
+        let commands = std::mem::take(&mut state.commands);
+        state.stats = RenderStats::default();
+        state.last_views = views.clone();
+        if views.is_empty() {
+            return;
+        }
+        views.sort_by_key(|v| v.priority);
+        let bounds = commands
+            .iter()
+            .map(|cmd| cmd.bounds.map(|b| b.transformed(&cmd.model)))
+            .collect::<Vec<Option<Aabb>>>();
+        let (width, height) = (window.width() as f32, window.height() as f32);
+        for view in views.iter() {
+            let rect = viewport_rect(&view.viewport, width, height);
+            unsafe {
+                gl::Viewport(rect.0, rect.1, rect.2, rect.3);
+            }
+            clear_view(view.clear, rect, window);
+            let frustum = Frustum::from_matrix(&view.view_projection);
+            let items = commands
+                .iter()
+                .map(|cmd| SortItem::new(cmd, &view.position))
+                .collect::<Vec<SortItem>>();
+            let order = draw_order(&items)
+                .into_iter()
+                .filter(|&i| layer_visible(view.culling_mask, commands[i].layer.unwrap_or(0)))
+                .filter(|&i| match &bounds[i] {
+                    Some(b) if !frustum.intersects_aabb(b) => {
+                        state.stats.culled += 1;
+                        false
+                    }
+                    _ => true,
+                })
+                .collect::<Vec<usize>>();
+            let mut bound: Option<(usize, usize, usize)> = None;
+            let mut depth_write = true;
+            for i in order {
+                let (cmd, item) = (&commands[i], &items[i]);
+                let program = &cmd.material.program;
+                if bound.map(|b| b.0) != Some(item.program) {
+                    program.bind_program();
+                    program.set_uniform("projection", &Uniform::Mat4(view.view_projection));
+                    program.set_uniform("viewer_pos", &Uniform::Vec3(view.position));
+                }
+                if bound.map(|b| (b.0, b.1)) != Some((item.program, item.material)) {
+                    cmd.material.apply();
+                    state.stats.batches += 1;
+                }
+                if bound.map(|b| b.2) != Some(item.mesh) {
+                    cmd.mesh.bind();
+                }
+                // transparent geometry doesn't write depth so it can't hide itself
+                if item.transparent == depth_write {
+                    depth_write = !item.transparent;
+                    unsafe {
+                        gl::DepthMask(if depth_write { gl::TRUE } else { gl::FALSE });
+                    }
+                }
+                bound = Some((item.program, item.material, item.mesh));
+                program.set_uniform("model", &Uniform::Mat4(cmd.model));
+                cmd.mesh.draw();
+                state.stats.draw_calls += 1;
+            }
+        }
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+            gl::BindVertexArray(0);
+            gl::Viewport(0, 0, width as i32, height as i32);
+        }
+        state.stats.views = views.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(transparent: bool, program: usize, mesh: usize, distance: f32) -> SortItem {
+        SortItem {
+            transparent,
+            sort_key: 0,
+            program,
+            material: program,
+            mesh,
+            distance,
+        }
+    }
+
+    #[test]
+    fn viewport_is_converted_to_pixels() {
+        let left_half = glm::vec4(0.0, 0.0, 0.5, 1.0);
+        assert_eq!(viewport_rect(&left_half, 800.0, 600.0), (0, 0, 400, 600));
+        let minimap = glm::vec4(0.75, 0.75, 0.25, 0.25);
+        assert_eq!(viewport_rect(&minimap, 800.0, 600.0), (600, 450, 200, 150));
+    }
+
+    #[test]
+    fn culling_mask_hides_layers() {
+        let mask = 0b101;
+        assert!(layer_visible(mask, 0));
+        assert!(!layer_visible(mask, 1));
+        assert!(layer_visible(mask, 2));
+        assert!(layer_visible(0, 40));
+    }
+
+    #[test]
+    fn opaque_commands_are_grouped_by_state() {
+        let items = [
+            item(false, 2, 1, 1.0),
+            item(false, 1, 1, 5.0),
+            item(false, 2, 1, 0.5),
+            item(false, 1, 2, 2.0),
+        ];
+        assert_eq!(draw_order(&items), vec![1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn transparent_commands_are_drawn_last_back_to_front() {
+        let mut items = [
+            item(true, 1, 1, 1.0),
+            item(false, 1, 1, 3.0),
+            item(true, 2, 1, 9.0),
+            item(false, 1, 1, 2.0),
+        ];
+        assert_eq!(draw_order(&items), vec![3, 1, 2, 0]);
+        items[0].sort_key = -1;
+        assert_eq!(draw_order(&items), vec![3, 1, 0, 2]);
+    }
+}
@@ -5,12 +5,83 @@ pub(crate) mod idmanager;
 mod tests;
 
 use crate::{
+    collision::{
+        narrowphase, shapes::WorldShape, Body, CollisionEvent, CollisionWorld, RaycastHit,
+    },
+    collision2d::{self, shapes::{ConvexShape2D, WorldShape2D}, Body2D, RaycastHit2D},
+    components::transform::Transform,
     context::Context,
-    error::{GameError, GameResult},
+    ecs::World,
+    error::{GameError, GameResult, Phase},
     gameobject::{GameObject, GameObjectId},
+    geometry::Ray,
+    parallel::{ParallelContext, ParallelGameObject, SceneView},
+    physics::{BodyState, PhysicsWorld},
 };
 use idmanager::IdManager;
-use std::{cell::RefCell, collections::HashMap, iter};
+use rayon::prelude::*;
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    collections::{hash_map::Entry, HashMap},
+    iter,
+};
+
+/// Decides what happens when a gameobject returns an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Stop the game and return the error from Game::run
+    #[default]
+    Abort,
+    /// Log the error and keep the gameobject alive
+    LogAndContinue,
+    /// Log the error and remove the offending gameobject from scene
+    Kill,
+}
+
+/// State of a gameobject reported by scene.inspect()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameObjectState {
+    /// added to scene, but on_add wasn't called yet
+    Pending,
+    Active,
+    /// will be removed from scene in the next frame
+    Dead,
+}
+
+/// Snapshot of a single gameobject, used by the scene inspector
+#[derive(Debug, Clone)]
+pub struct GameObjectInfo {
+    pub id: GameObjectId,
+    pub name: String,
+    pub state: GameObjectState,
+    pub transform: Option<Transform>,
+}
+
+/// Decides in which order gameobjects of a layer are drawn
+/// All modes except Insertion sort by GameObject::sort_key first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// gameobjects are drawn in order they were added
+    #[default]
+    Insertion,
+    /// only by sort_key, lower keys are drawn first
+    SortKey,
+    /// closest to view position first, use it for opaque geometry
+    FrontToBack,
+    /// farthest from view position first, use it for transparent geometry
+    BackToFront,
+}
+
+/// Preallocated gameobjects of a single type, see scene.create_pool
+struct Pool {
+    free: Vec<Box<dyn GameObject>>,
+    factory: Box<dyn FnMut() -> Box<dyn GameObject>>,
+}
+
+/// Systems are closures iterating over scene.world() queries
+pub type System = dyn FnMut(&Context, &Scene) -> GameResult;
 
 pub struct Scene {
     /// Each scene name must be unique!
@@ -18,12 +89,30 @@ pub struct Scene {
     /// if disposable is set to false Game will keep the scene after it is changed
     pub(crate) disposable: bool,
     pub(crate) layers: usize,
+    /// if not set Game will use policy from GameConfig
+    pub(crate) error_policy: Option<ErrorPolicy>,
+    /// layers which update their parallel gameobjects on the thread pool
+    parallel_layers: Vec<bool>,
+    sort_modes: Vec<SortMode>,
+    /// position distance sorting is relative to, usually camera position
+    view_position: Cell<glm::Vec3>,
+
+    world: World,
+    systems: Vec<(Phase, Box<System>)>,
+    pools: RefCell<HashMap<TypeId, Pool>>,
+    collisions: CollisionWorld,
+    physics: PhysicsWorld,
+    /// colliders used by scene queries, refreshed before fixed_update and update
+    query_bodies: Vec<Body>,
+    query_bodies_2d: Vec<Body2D>,
 
     first_loop: bool,
     gameobjects: Vec<HashMap<usize, Option<Box<dyn GameObject>>>>,
     gameobject_ids: Vec<Vec<GameObjectId>>,
     new_gameobjects: RefCell<Vec<(GameObjectId, Box<dyn GameObject>)>>,
     id_manager: RefCell<IdManager>,
+    errors: Vec<GameError>,
+    log_target: String,
     // TODO? Disable/Enable gameobjects
 }
 
@@ -36,10 +125,136 @@ impl Scene {
             gameobjects: iter::repeat_with(HashMap::new).take(layers).collect(),
             gameobject_ids: iter::repeat_with(Vec::new).take(layers).collect(),
             new_gameobjects: RefCell::new(Vec::new()),
+            error_policy: None,
+            parallel_layers: vec![false; layers],
+            sort_modes: vec![SortMode::Insertion; layers],
+            view_position: Cell::new(glm::Vec3::zeros()),
+            world: World::new(),
+            systems: Vec::new(),
+            pools: RefCell::new(HashMap::new()),
+            collisions: CollisionWorld::new(),
+            physics: PhysicsWorld::new(),
+            query_bodies: Vec::new(),
+            query_bodies_2d: Vec::new(),
+            errors: Vec::new(),
+            log_target: format!("microengine::scene::{name}"),
             disposable,
         }
     }
 
+    /// Returns scene name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets what should happen when one of scene's gameobjects returns an error
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.error_policy = Some(policy);
+    }
+
+    /// Marks layer as parallel, its gameobjects implementing ParallelGameObject
+    /// will be updated on the thread pool (see parallel module)
+    pub fn set_layer_parallel(&mut self, layer: usize, parallel: bool) -> GameResult {
+        match self.parallel_layers.get_mut(layer) {
+            Some(p) => {
+                *p = parallel;
+                Ok(())
+            }
+            None => Err(GameError::SceneError(
+                self.name.clone(),
+                format!("Layer does not exist: {}", layer),
+            )),
+        }
+    }
+
+    /// Sets how gameobjects in given layer are ordered when drawing
+    pub fn set_layer_sort_mode(&mut self, layer: usize, mode: SortMode) -> GameResult {
+        match self.sort_modes.get_mut(layer) {
+            Some(m) => {
+                *m = mode;
+                Ok(())
+            }
+            None => Err(GameError::SceneError(
+                self.name.clone(),
+                format!("Layer does not exist: {}", layer),
+            )),
+        }
+    }
+
+    /// Sets position used by FrontToBack and BackToFront sort modes,
+    /// camera gameobjects should set it every frame
+    pub fn set_view_position(&self, position: glm::Vec3) {
+        self.view_position.set(position);
+    }
+
+    pub fn view_position(&self) -> glm::Vec3 {
+        self.view_position.get()
+    }
+
+    /// Returns scene's ECS world, see ecs module
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Adds a system which runs after gameobjects in given phase,
+    /// only FixedUpdate, Update and Draw phases are supported
+    pub fn add_system<F>(&mut self, phase: Phase, system: F) -> GameResult
+    where
+        F: FnMut(&Context, &Scene) -> GameResult + 'static,
+    {
+        match phase {
+            Phase::FixedUpdate | Phase::Update | Phase::Draw => {
+                self.systems.push((phase, Box::new(system)));
+                Ok(())
+            }
+            _ => Err(GameError::SceneError(
+                self.name.clone(),
+                format!("Systems can't run in {phase} phase"),
+            )),
+        }
+    }
+
+    /// Runs all systems added for given phase in order they were added
+    fn run_systems(&mut self, ctx: &Context, phase: Phase) -> GameResult {
+        let mut systems = std::mem::take(&mut self.systems);
+        let mut result = Ok(());
+        for (_, system) in systems.iter_mut().filter(|(p, _)| *p == phase) {
+            if let Err(e) = system(ctx, self) {
+                result = self.handle_callback_error(ctx, e);
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+        // systems added while running are kept as well
+        systems.append(&mut self.systems);
+        self.systems = systems;
+        result
+    }
+
+    /// Applies error policy to error returned by a callback not owned by any gameobject
+    /// (systems, tweens), it fails only if the policy is Abort
+    pub(crate) fn handle_callback_error(&mut self, ctx: &Context, e: GameError) -> GameResult {
+        match self.error_policy.unwrap_or_default() {
+            ErrorPolicy::Abort => Err(e),
+            _ => {
+                ctx.log.error(&self.log_target, &e);
+                self.errors.push(e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns errors handled by the error policy (not aborting the game)
+    pub fn errors(&self) -> &[GameError] {
+        &self.errors
+    }
+
+    /// Removes and returns all errors handled by the error policy
+    pub fn take_errors(&mut self) -> Vec<GameError> {
+        std::mem::take(&mut self.errors)
+    }
+
     /// Returns a reference to gameobject with given id
     /// Reference can't be saved because GameObject could be dropped later,
     /// so use it each time you need to reference another gameobject
@@ -72,6 +287,305 @@ impl Scene {
         None
     }
 
+    /// Returns number of layers in scene
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
+    /// Returns a snapshot of all gameobjects in scene ordered by layer
+    /// This is slow and meant for debugging tools only
+    pub fn inspect(&self) -> Vec<GameObjectInfo> {
+        let mut infos = Vec::new();
+        for layer in 0..self.layers {
+            for id in self.gameobject_ids[layer].iter() {
+                if let Some(Some(go)) = self.gameobjects[layer].get(&id.id) {
+                    infos.push(GameObjectInfo {
+                        id: *id,
+                        name: go.name().into(),
+                        state: if id.is_dead {
+                            GameObjectState::Dead
+                        } else {
+                            GameObjectState::Active
+                        },
+                        transform: go.transform().cloned(),
+                    });
+                }
+            }
+            for (id, go) in self.new_gameobjects.borrow().iter() {
+                if id.layer == layer {
+                    infos.push(GameObjectInfo {
+                        id: *id,
+                        name: go.name().into(),
+                        state: GameObjectState::Pending,
+                        transform: go.transform().cloned(),
+                    });
+                }
+            }
+        }
+        infos
+    }
+
+    /// Returns a reference to transform of gameobject with given id
+    /// if the gameobject exposes it (see GameObject::transform)
+    pub fn transform(&self, id: &GameObjectId) -> Option<&Transform> {
+        match self.gameobjects.get(id.layer)?.get(&id.id) {
+            Some(Some(go)) => go.transform(),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to transform of gameobject with given id
+    /// if the gameobject exposes it (see GameObject::transform_mut)
+    pub fn transform_mut(&mut self, id: &GameObjectId) -> Option<&mut Transform> {
+        match self.gameobjects.get_mut(id.layer)?.get_mut(&id.id) {
+            Some(Some(go)) => go.transform_mut(),
+            _ => None,
+        }
+    }
+
+    /// Sets size of cells of the grid used to find colliders close to each other,
+    /// it should be close to the size of a typical collider (default is 4)
+    pub fn set_collision_cell_size(&mut self, cell_size: f32) {
+        self.collisions.set_cell_size(cell_size);
+    }
+
+    /// Returns colliders of all active gameobjects placed in world
+    fn collision_bodies(&self) -> Vec<Body> {
+        let mut bodies = Vec::new();
+        for layer in 0..self.layers {
+            for id in self.gameobject_ids[layer].iter().filter(|id| !id.is_dead) {
+                let Some(Some(go)) = self.gameobjects[layer].get(&id.id) else {
+                    continue;
+                };
+                if let Some(collider) = go.collider() {
+                    let transform = go.transform().cloned().unwrap_or_default();
+                    bodies.push(Body {
+                        id: *id,
+                        shape: WorldShape::new(&collider.shape, &collider.offset, &transform),
+                        collider: collider.clone(),
+                    });
+                }
+            }
+        }
+        bodies
+    }
+
+    /// Returns 2D colliders of all active gameobjects placed in world
+    fn collision_bodies_2d(&self) -> Vec<Body2D> {
+        let mut bodies = Vec::new();
+        for layer in 0..self.layers {
+            for id in self.gameobject_ids[layer].iter().filter(|id| !id.is_dead) {
+                let Some(Some(go)) = self.gameobjects[layer].get(&id.id) else {
+                    continue;
+                };
+                if let Some(collider) = go.collider_2d() {
+                    let transform = go.transform().cloned().unwrap_or_default();
+                    bodies.push(Body2D {
+                        id: *id,
+                        shape: WorldShape2D::new(&collider.shape, &collider.offset, &transform),
+                        collider: collider.clone(),
+                        z: transform.position().z,
+                    });
+                }
+            }
+        }
+        bodies
+    }
+
+    /// Places colliders used by queries in this phase, so queries don't rebuild them
+    fn refresh_query_bodies(&mut self) {
+        self.query_bodies = self.collision_bodies();
+        self.query_bodies_2d = self.collision_bodies_2d();
+    }
+
+    /// Returns 2D colliders used by queries, without the gameobject calling it
+    pub(crate) fn query_bodies_2d(&self) -> impl Iterator<Item = &Body2D> {
+        self.query_bodies_2d.iter().filter(|b| self.is_queryable(&b.id))
+    }
+
+    /// Checks if gameobject is in scene and isn't running at the moment
+    fn is_queryable(&self, id: &GameObjectId) -> bool {
+        matches!(self.gameobjects[id.layer].get(&id.id), Some(Some(_)))
+    }
+
+    /// Returns the closest collider hit by the ray, colliders whose layer is not in mask
+    /// and triggers are ignored. Gameobject calling it is not part of the scene
+    /// at that moment, so it never hits itself.
+    /// Queries see colliders placed as they were at the beginning of fixed_update or update.
+    pub fn raycast(&self, ray: &Ray, max_distance: f32, mask: u32) -> Option<RaycastHit> {
+        self.raycast_all(ray, max_distance, mask).into_iter().next()
+    }
+
+    /// Returns all colliders hit by the ray sorted by distance
+    pub fn raycast_all(&self, ray: &Ray, max_distance: f32, mask: u32) -> Vec<RaycastHit> {
+        CollisionWorld::cast(&self.query_bodies, ray, 0.0, max_distance, mask)
+            .into_iter()
+            .filter(|hit| self.is_queryable(&hit.id))
+            .collect()
+    }
+
+    /// Returns the first collider touched by a sphere moving along the ray,
+    /// hit point lies on collider's surface
+    pub fn sphere_cast(
+        &self,
+        ray: &Ray,
+        radius: f32,
+        max_distance: f32,
+        mask: u32,
+    ) -> Option<RaycastHit> {
+        CollisionWorld::cast(&self.query_bodies, ray, radius, max_distance, mask)
+            .into_iter()
+            .find(|hit| self.is_queryable(&hit.id))
+    }
+
+    /// Returns ids of gameobjects whose colliders overlap given sphere, triggers are ignored
+    pub fn overlap_sphere(&self, center: glm::Vec3, radius: f32, mask: u32) -> Vec<GameObjectId> {
+        let sphere = WorldShape::Sphere { center, radius };
+        self.query_bodies
+            .iter()
+            .filter(|b| !b.collider.is_trigger && mask & b.collider.layer_bit() != 0)
+            .filter(|b| self.is_queryable(&b.id))
+            .filter(|b| narrowphase::contact(&sphere, &b.shape).is_some())
+            .map(|b| b.id)
+            .collect()
+    }
+
+    /// Returns the closest 2D collider hit by the ray in the XY plane, see raycast.
+    /// One way platforms are hit only from above.
+    pub fn raycast_2d(
+        &self,
+        origin: glm::Vec2,
+        direction: glm::Vec2,
+        max_distance: f32,
+        mask: u32,
+    ) -> Option<RaycastHit2D> {
+        collision2d::raycast(&self.query_bodies_2d, &origin, &direction, max_distance, mask)
+            .into_iter()
+            .find(|hit| self.is_queryable(&hit.id))
+    }
+
+    /// Returns ids of gameobjects whose 2D colliders overlap given circle, triggers are ignored
+    pub fn overlap_circle(&self, center: glm::Vec2, radius: f32, mask: u32) -> Vec<GameObjectId> {
+        let circle = ConvexShape2D::Circle { center, radius };
+        let reach = glm::vec2(radius, radius);
+        self.query_bodies_2d()
+            .filter(|b| !b.collider.is_trigger && mask & b.collider.layer_bit() != 0)
+            .filter(|b| {
+                b.shape
+                    .parts(&(center - reach), &(center + reach), b.collider.one_way)
+                    .iter()
+                    .any(|(part, _)| collision2d::shapes::contact(&circle, part).is_some())
+            })
+            .map(|b| b.id)
+            .collect()
+    }
+
+    /// Sets acceleration applied to all rigid bodies, default is 9.81 m/s^2 down the y axis
+    pub fn set_gravity(&mut self, gravity: glm::Vec3) {
+        self.physics.gravity = gravity;
+    }
+
+    pub fn gravity(&self) -> glm::Vec3 {
+        self.physics.gravity
+    }
+
+    /// Sets how many times contacts are solved each physics step,
+    /// more iterations make stacked bodies more stable (default is 8)
+    pub fn set_solver_iterations(&mut self, iterations: usize) {
+        self.physics.iterations = iterations.max(1);
+    }
+
+    /// Moves rigid bodies of gameobjects by forces and contacts found in this step
+    fn step_rigid_bodies(&mut self, dt: f32, bodies: &[Body], bodies_2d: &[Body2D]) {
+        let shapes = bodies
+            .iter()
+            .map(|b| (b.id.id, &b.shape))
+            .collect::<HashMap<_, _>>();
+        let half_extents_2d = bodies_2d
+            .iter()
+            .map(|b| (b.id.id, b.shape.bounds().extents().xy()))
+            .collect::<HashMap<_, _>>();
+        let mut ids = Vec::new();
+        let mut states = Vec::new();
+        for layer in 0..self.layers {
+            for id in self.gameobject_ids[layer].iter().filter(|id| !id.is_dead) {
+                let Some(Some(go)) = self.gameobjects[layer].get(&id.id) else {
+                    continue;
+                };
+                let body = go.rigid_body().cloned();
+                let shape = shapes.get(&id.id).copied();
+                let half_extents_2d = half_extents_2d.get(&id.id);
+                if body.is_none() && shape.is_none() && half_extents_2d.is_none() {
+                    continue;
+                }
+                let transform = go.transform().cloned().unwrap_or_default();
+                let state = BodyState::new(body, *transform.position(), *transform.rotation(), shape);
+                ids.push(*id);
+                states.push(match half_extents_2d {
+                    Some(half_extents) => state.planar(*half_extents),
+                    None => state,
+                });
+            }
+        }
+        if states.iter().all(|s| s.body.is_none()) {
+            return;
+        }
+        let indices = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.id, i))
+            .collect::<HashMap<_, _>>();
+        let contacts = self
+            .collisions
+            .contacts()
+            .into_iter()
+            .filter_map(|(a, b, contact)| Some((*indices.get(&a.id)?, *indices.get(&b.id)?, contact)))
+            .collect::<Vec<_>>();
+        self.physics.step(&mut states, &contacts, dt);
+        for (id, state) in ids.into_iter().zip(states) {
+            let (Some(body), Some(Some(go))) = (state.body, self.gameobjects[id.layer].get_mut(&id.id))
+            else {
+                continue;
+            };
+            if let Some(rigid_body) = go.rigid_body_mut() {
+                *rigid_body = body;
+            }
+            if let Some(transform) = go.transform_mut() {
+                *transform.position_mut() = state.position;
+                *transform.rotation_mut() = state.rotation;
+            }
+        }
+    }
+
+    /// Finds touching colliders, moves rigid bodies and calls on_collision_*
+    /// and on_trigger_* methods
+    fn step_physics(&mut self, ctx: &Context) -> GameResult {
+        let bodies = self.collision_bodies();
+        let bodies_2d = self.collision_bodies_2d();
+        let events = self.collisions.step(&bodies, &bodies_2d);
+        self.step_rigid_bodies(ctx.time.fixed_time_step() as f32, &bodies, &bodies_2d);
+        if events.is_empty() {
+            return Ok(());
+        }
+        let mut by_gameobject: HashMap<usize, Vec<CollisionEvent>> = HashMap::new();
+        for (id, event) in events {
+            by_gameobject.entry(id.id).or_default().push(event);
+        }
+        self.for_all_gameobjects(ctx, Phase::Collision, |id, go, ctx, scene| {
+            for event in by_gameobject.remove(&id.id).unwrap_or_default() {
+                match event {
+                    CollisionEvent::Enter(c) => go.on_collision_enter(ctx, scene, &c)?,
+                    CollisionEvent::Stay(c) => go.on_collision_stay(ctx, scene, &c)?,
+                    CollisionEvent::Exit(c) => go.on_collision_exit(ctx, scene, &c)?,
+                    CollisionEvent::TriggerEnter(other) => go.on_trigger_enter(ctx, scene, other)?,
+                    CollisionEvent::TriggerStay(other) => go.on_trigger_stay(ctx, scene, other)?,
+                    CollisionEvent::TriggerExit(other) => go.on_trigger_exit(ctx, scene, other)?,
+                }
+            }
+            Ok(())
+        })
+    }
+
     /// Adds given gameobject to scene and returns its Id.
     pub fn add_gameobject<T: GameObject + 'static>(
         &self,
@@ -92,31 +606,350 @@ impl Scene {
         }
     }
 
+    /// If set to true scene creates new GameObjectIds when max_gameobject_count
+    /// is reached instead of returning an error
+    pub fn set_growable_ids(&mut self, growable: bool) {
+        self.id_manager.get_mut().set_growable(growable);
+    }
+
+    /// Preallocates count gameobjects of type T created by factory
+    /// Dead gameobjects of this type are reset (see GameObject::reset) and returned to the pool
+    /// instead of being dropped, use spawn_pooled to add them to scene.
+    /// Creating pool for the same type again adds more gameobjects to it
+    /// and replaces its factory, which is used from then on
+    pub fn create_pool<T, F>(&self, count: usize, mut factory: F)
+    where
+        T: GameObject + 'static,
+        F: FnMut() -> T + 'static,
+    {
+        let mut pools = self.pools.borrow_mut();
+        let factory: Box<dyn FnMut() -> Box<dyn GameObject>> = Box::new(move || Box::new(factory()));
+        let pool = match pools.entry(TypeId::of::<T>()) {
+            Entry::Occupied(entry) => {
+                let pool = entry.into_mut();
+                pool.factory = factory;
+                pool
+            }
+            Entry::Vacant(entry) => entry.insert(Pool {
+                free: Vec::new(),
+                factory,
+            }),
+        };
+        pool.free.reserve(count);
+        for _ in 0..count {
+            let go = (pool.factory)();
+            pool.free.push(go);
+        }
+    }
+
+    /// Adds gameobject from pool of type T to scene, init is called before adding it
+    /// (T has to implement GameObject::as_any_mut). If the pool is empty a new
+    /// gameobject is created by pool's factory.
+    pub fn spawn_pooled<T, F>(&self, layer: usize, init: F) -> GameResult<GameObjectId>
+    where
+        T: GameObject + 'static,
+        F: FnOnce(&mut T),
+    {
+        let mut go = {
+            let mut pools = self.pools.borrow_mut();
+            let pool = pools.get_mut(&TypeId::of::<T>()).ok_or_else(|| {
+                GameError::SceneError(
+                    self.name.clone(),
+                    format!("There is no pool for {}", std::any::type_name::<T>()),
+                )
+            })?;
+            match pool.free.pop() {
+                Some(go) => go,
+                None => (pool.factory)(),
+            }
+        };
+        match go.as_any_mut().and_then(|go| go.downcast_mut::<T>()) {
+            Some(go) => init(go),
+            None => {
+                self.recycle(go);
+                return Err(GameError::SceneError(
+                    self.name.clone(),
+                    format!(
+                        "Pooled gameobject {} doesn't implement as_any_mut",
+                        std::any::type_name::<T>()
+                    ),
+                ));
+            }
+        }
+        if layer >= self.layers {
+            self.recycle(go);
+            return Err(GameError::SceneError(
+                self.name.clone(),
+                format!("Layer does not exist: {}", layer),
+            ));
+        }
+        let new_id = match self.id_manager.borrow_mut().get(layer) {
+            Ok(id) => id,
+            Err(e) => {
+                self.recycle(go);
+                return Err(e);
+            }
+        };
+        self.new_gameobjects.borrow_mut().push((new_id, go));
+        Ok(new_id)
+    }
+
+    /// Returns number of gameobjects waiting in the pool of type T
+    pub fn pooled_count<T: GameObject + 'static>(&self) -> usize {
+        self.pools
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .map_or(0, |pool| pool.free.len())
+    }
+
+    /// Wraps error returned by gameobject with its name, id and phase it failed in
+    fn annotate_error(
+        go: &dyn GameObject,
+        id: GameObjectId,
+        phase: Phase,
+        e: GameError,
+    ) -> GameError {
+        GameError::GameObjectError {
+            name: go.name().into(),
+            id,
+            phase,
+            source: Box::new(e),
+        }
+    }
+
+    /// Annotates gameobject error, calls its on_error hook and handles it according to
+    /// scene's error policy. Returns Ok(true) if gameobject should be killed.
+    fn handle_error(
+        &mut self,
+        go: &mut Box<dyn GameObject>,
+        id: GameObjectId,
+        phase: Phase,
+        e: GameError,
+        ctx: &Context,
+    ) -> GameResult<bool> {
+        let e = Self::annotate_error(go.as_ref(), id, phase, e);
+        go.on_error(ctx, self, &e);
+        match self.error_policy.unwrap_or_default() {
+            ErrorPolicy::Abort => Err(e),
+            ErrorPolicy::LogAndContinue => {
+                ctx.log.error(&self.log_target, &e);
+                self.errors.push(e);
+                Ok(false)
+            }
+            ErrorPolicy::Kill => {
+                ctx.log.error(&self.log_target, &e);
+                self.errors.push(e);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Removes gameobject from scene, freeing its id
+    fn destroy_gameobject(
+        &mut self,
+        go: Box<dyn GameObject>,
+        layer: usize,
+        it: usize,
+        ctx: &Context,
+    ) {
+        let id = self.gameobject_ids[layer][it];
+        ctx.log.debug(
+            &self.log_target,
+            format_args!(
+                "Destroying gameobject '{}' (id: {}, layer: {})",
+                go.name(),
+                id.id,
+                id.layer
+            ),
+        );
+        self.id_manager.borrow_mut().free(id);
+        self.gameobject_ids[layer][it].is_dead = true;
+        self.recycle(go);
+    }
+
+    /// Returns pooled gameobject to its pool, other gameobjects are dropped
+    fn recycle(&self, mut go: Box<dyn GameObject>) {
+        let type_id = Any::type_id(go.as_any());
+        if let Some(pool) = self.pools.borrow_mut().get_mut(&type_id) {
+            go.reset();
+            pool.free.push(go);
+        }
+    }
+
     /// Runs given closure on all gameobjects in scene.
-    fn for_all_gameobjects<T>(&mut self, mut f: T) -> GameResult
+    /// Errors are annotated with given phase and handled according to error policy.
+    fn for_all_gameobjects<T>(&mut self, ctx: &Context, phase: Phase, mut f: T) -> GameResult
     where
-        T: FnMut(GameObjectId, &mut Box<dyn GameObject>, &Scene) -> GameResult,
+        T: FnMut(GameObjectId, &mut Box<dyn GameObject>, &Context, &Scene) -> GameResult,
+    {
+        for layer in 0..self.layers {
+            self.for_layer_gameobjects(layer, None, ctx, phase, &mut f)?;
+        }
+        Ok(())
+    }
+
+    /// Runs given closure on all gameobjects in given layer.
+    /// order contains indices into layer's gameobject_ids, insertion order is used if None
+    fn for_layer_gameobjects<T>(
+        &mut self,
+        layer: usize,
+        order: Option<&[usize]>,
+        ctx: &Context,
+        phase: Phase,
+        f: &mut T,
+    ) -> GameResult
+    where
+        T: FnMut(GameObjectId, &mut Box<dyn GameObject>, &Context, &Scene) -> GameResult,
     {
         // using for loops instead of mutable iterators
         // so there's no unnecessary mutable reference to scene
-        for layer in 0..self.layers {
-            for it in 0..self.gameobject_ids[layer].len() {
-                let id = self.gameobject_ids[layer][it];
-                let go = self.gameobjects[layer].remove(&id.id).unwrap();
-                let mut go = match go {
-                    Some(g) => g,
-                    None => {
-                        return Err(GameError::EngineError(
-                            "Trying to process a missing gameobject".into(),
-                        ))
+        for n in 0..self.gameobject_ids[layer].len() {
+            let it = order.map_or(n, |order| order[n]);
+            let id = self.gameobject_ids[layer][it];
+            if id.is_dead {
+                continue;
+            }
+            let go = self.gameobjects[layer].remove(&id.id).flatten();
+            let mut go = match go {
+                Some(g) => g,
+                None => {
+                    return Err(GameError::EngineError(
+                        "Trying to process a missing gameobject".into(),
+                    ))
+                }
+            };
+            let start = ctx.profiler.now();
+            let result = f(id, &mut go, ctx, self);
+            ctx.profiler.record_gameobject(go.name(), layer, phase, start);
+            let kill = match result {
+                Ok(_) => false,
+                Err(e) => match self.handle_error(&mut go, id, phase, e, ctx) {
+                    Ok(kill) => kill,
+                    Err(e) => {
+                        self.gameobjects[layer].insert(id.id, Some(go));
+                        return Err(e);
                     }
+                },
+            };
+            if !kill && !go.is_dead() {
+                self.gameobjects[layer].insert(id.id, Some(go));
+            } else {
+                self.destroy_gameobject(go, layer, it, ctx);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns order in which gameobjects of given layer are drawn
+    /// or None if they are drawn in insertion order
+    fn draw_order(&self, layer: usize) -> Option<Vec<usize>> {
+        let mode = self.sort_modes[layer];
+        if mode == SortMode::Insertion {
+            return None;
+        }
+        let view = self.view_position.get();
+        let keys = self.gameobject_ids[layer]
+            .iter()
+            .map(|id| match self.gameobjects[layer].get(&id.id) {
+                Some(Some(go)) => {
+                    let distance = go
+                        .transform()
+                        .map(|t| glm::distance2(t.position(), &view));
+                    (go.sort_key(), distance)
+                }
+                _ => (0, None),
+            })
+            .collect::<Vec<(i32, Option<f32>)>>();
+        let mut order = (0..keys.len()).collect::<Vec<usize>>();
+        // sort is stable so gameobjects with equal keys keep insertion order
+        order.sort_by(|&a, &b| {
+            let (key_a, dist_a) = keys[a];
+            let (key_b, dist_b) = keys[b];
+            let by_distance = match (mode, dist_a, dist_b) {
+                (SortMode::FrontToBack, Some(a), Some(b)) => a.total_cmp(&b),
+                (SortMode::BackToFront, Some(a), Some(b)) => b.total_cmp(&a),
+                (SortMode::SortKey, _, _) => Ordering::Equal,
+                // gameobjects without transform are drawn first
+                _ => dist_a.is_some().cmp(&dist_b.is_some()),
+            };
+            key_a.cmp(&key_b).then(by_distance)
+        });
+        Some(order)
+    }
+
+    /// Runs update or fixed_update of all gameobjects, layers marked parallel
+    /// run their parallel gameobjects on the thread pool first.
+    /// All parallel layers share one snapshot of the scene, taken before the first of them runs
+    fn update_gameobjects(&mut self, ctx: &Context, phase: Phase) -> GameResult {
+        let mut view = None;
+        for layer in 0..self.layers {
+            let parallel = self.parallel_layers[layer];
+            if parallel {
+                let view = view.get_or_insert_with(|| SceneView {
+                    name: self.name.clone(),
+                    gameobjects: self.inspect(),
+                });
+                self.par_update_layer(layer, ctx, phase, view)?;
+            }
+            self.for_layer_gameobjects(layer, None, ctx, phase, &mut |_, go, ctx, scene| {
+                if parallel && go.as_parallel().is_some() {
+                    return Ok(());
+                }
+                match phase {
+                    Phase::FixedUpdate => go.fixed_update(ctx, scene),
+                    _ => go.update(ctx, scene),
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Runs par_update or par_fixed_update of parallel gameobjects in given layer
+    /// on rayon's thread pool, the scene is only available as a read-only snapshot
+    fn par_update_layer(
+        &mut self,
+        layer: usize,
+        ctx: &Context,
+        phase: Phase,
+        view: &SceneView,
+    ) -> GameResult {
+        let start = ctx.profiler.now();
+        let pctx = ParallelContext::from(&ctx.time);
+        let mut errors = self.gameobjects[layer]
+            .iter_mut()
+            .filter_map(|(id, go)| go.as_deref_mut()?.as_parallel().map(|go| (*id, go)))
+            .collect::<Vec<(usize, &mut dyn ParallelGameObject)>>()
+            .into_par_iter()
+            .filter_map(|(id, go)| {
+                let result = match phase {
+                    Phase::FixedUpdate => go.par_fixed_update(&pctx, view),
+                    _ => go.par_update(&pctx, view),
                 };
-                f(id, &mut go, self)?;
-                if !go.is_dead() {
-                    self.gameobjects[layer].insert(id.id, Some(go));
-                } else {
-                    self.id_manager.borrow_mut().free(id);
-                    self.gameobject_ids[layer][it].is_dead = true;
+                result.err().map(|e| (id, e))
+            })
+            .collect::<Vec<(usize, GameError)>>();
+        ctx.profiler
+            .record_phase(&format!("parallel_{}", phase.as_str()), start);
+
+        // errors are handled on the main thread, in the same order gameobjects were added
+        let ids = &self.gameobject_ids[layer];
+        errors.sort_by_key(|(id, _)| ids.iter().position(|i| i.id == *id));
+        for (key, e) in errors {
+            let Some(it) = self.gameobject_ids[layer].iter().position(|i| i.id == key) else {
+                continue;
+            };
+            let id = self.gameobject_ids[layer][it];
+            let Some(mut go) = self.gameobjects[layer].remove(&key).flatten() else {
+                continue;
+            };
+            match self.handle_error(&mut go, id, phase, e, ctx) {
+                Ok(false) => {
+                    self.gameobjects[layer].insert(key, Some(go));
+                }
+                Ok(true) => self.destroy_gameobject(go, layer, it, ctx),
+                Err(e) => {
+                    self.gameobjects[layer].insert(key, Some(go));
+                    return Err(e);
                 }
             }
         }
@@ -126,8 +959,16 @@ impl Scene {
     /// All gameobject methods are being run here in this very method
     /// returns true if all gameobjects are finished.
     pub fn run_loop(&mut self, ctx: &mut Context) -> GameResult {
+        self.run_update(ctx)?;
+        self.run_draw(ctx)
+    }
+
+    /// Adds new gameobjects and runs start, physics, fixed_update and update
+    pub(crate) fn run_update(&mut self, ctx: &mut Context) -> GameResult {
         // add newly created gameobjects
-        for (id, mut go) in self.new_gameobjects.borrow_mut().drain(..) {
+        let start = ctx.profiler.now();
+        let new_gameobjects = self.new_gameobjects.take();
+        for (id, mut go) in new_gameobjects {
             if id.layer >= self.layers {
                 return Err(
                     GameError::SceneError(self.name.clone(),
@@ -139,34 +980,79 @@ impl Scene {
                     )
                 );
             }
-            go.on_add(ctx, self, id)?;
+            if let Err(e) = go.on_add(ctx, self, id) {
+                if self.handle_error(&mut go, id, Phase::OnAdd, e, ctx)? {
+                    self.id_manager.borrow_mut().free(id);
+                    self.recycle(go);
+                    continue;
+                }
+            }
+            ctx.log.debug(
+                &self.log_target,
+                format_args!(
+                    "Added gameobject '{}' (id: {}, layer: {})",
+                    go.name(),
+                    id.id,
+                    id.layer
+                ),
+            );
             self.gameobject_ids[id.layer].push(id);
             self.gameobjects[id.layer].insert(id.id, Some(go));
         }
+        ctx.profiler.record_phase(Phase::OnAdd.as_str(), start);
         // run start
         if self.first_loop {
             self.first_loop = false;
-            self.for_all_gameobjects(|_, go, scene| go.start(ctx, scene))?;
+            let start = ctx.profiler.now();
+            self.for_all_gameobjects(ctx, Phase::Start, |_, go, ctx, scene| go.start(ctx, scene))?;
+            ctx.profiler.record_phase(Phase::Start.as_str(), start);
         }
 
         // run fixed_update
+        let start = ctx.profiler.now();
         for _ in 0..ctx.time.get_fixed_steps() {
-            self.for_all_gameobjects(|_, go, scene| go.fixed_update(ctx, scene))?;
+            let physics_start = ctx.profiler.now();
+            self.step_physics(ctx)?;
+            ctx.profiler.record_phase(Phase::Physics.as_str(), physics_start);
+            self.refresh_query_bodies();
+            self.update_gameobjects(ctx, Phase::FixedUpdate)?;
+            self.run_systems(ctx, Phase::FixedUpdate)?;
         }
+        ctx.profiler.record_phase(Phase::FixedUpdate.as_str(), start);
 
         // run update
-        self.for_all_gameobjects(|_, go, scene| {
-            go.update(ctx, scene)?;
-            Ok(())
-        })?;
+        let start = ctx.profiler.now();
+        self.refresh_query_bodies();
+        self.update_gameobjects(ctx, Phase::Update)?;
+        self.run_systems(ctx, Phase::Update)?;
+        ctx.profiler.record_phase(Phase::Update.as_str(), start);
 
         // delete all dead gameobjects
         self.gameobject_ids.iter_mut().for_each(|v| {
             *v = v.iter().filter(|&id| !id.is_dead ).copied().collect::<Vec<GameObjectId>>();
         });
-        
+        Ok(())
+    }
+
+    /// Draws all gameobjects, the only part of the loop running while game is paused
+    pub(crate) fn run_draw(&mut self, ctx: &mut Context) -> GameResult {
         // draw gameobjects
-        self.for_all_gameobjects(|_, go, scene| go.draw(ctx, scene))?;
+        let start = ctx.profiler.now();
+        for layer in 0..self.layers {
+            let order = self.draw_order(layer);
+            ctx.renderer.set_draw_layer(layer);
+            let result = self.for_layer_gameobjects(
+                layer,
+                order.as_deref(),
+                ctx,
+                Phase::Draw,
+                &mut |_, go, ctx, scene| go.draw(ctx, scene),
+            );
+            ctx.renderer.set_draw_layer(0);
+            result?;
+        }
+        self.run_systems(ctx, Phase::Draw)?;
+        ctx.profiler.record_phase(Phase::Draw.as_str(), start);
 
         Ok(())
     }
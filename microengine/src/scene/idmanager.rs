@@ -10,6 +10,9 @@ use std::iter;
 pub(crate) struct IdManager {
     taken: usize,
     id_pool: Vec<usize>,
+    /// next id created when pool is empty and growing is enabled
+    next_id: usize,
+    growable: bool,
 }
 
 impl IdManager {
@@ -17,6 +20,8 @@ impl IdManager {
         let mut cnt = 0;
         IdManager {
             taken: 0,
+            next_id: max_ids + 1,
+            growable: false,
             id_pool: iter::repeat_with(|| {
                 cnt += 1;
                 cnt
@@ -36,10 +41,24 @@ impl IdManager {
                     is_dead: false,
                 })
             }
+            None if self.growable => {
+                self.taken += 1;
+                self.next_id += 1;
+                Ok(GameObjectId {
+                    layer,
+                    id: self.next_id - 1,
+                    is_dead: false,
+                })
+            }
             None => Err(GameError::GameLogicError("run out of GameObjectIds".into())),
         }
     }
 
+    /// if growable is set new ids are created instead of returning an error
+    pub fn set_growable(&mut self, growable: bool) {
+        self.growable = growable;
+    }
+
     /// return GameObjectIds to the pool
     /// if scene frees Ids properly it should never panic
     pub fn free(&mut self, id: GameObjectId) {
@@ -91,6 +110,17 @@ mod tests {
         assert_eq!(true, im.get(0).is_err());
     }
 
+    #[test]
+    fn growable_pool_creates_unique_ids() {
+        let mut im = IdManager::new(1);
+        im.set_growable(true);
+        let a = im.get(0).unwrap();
+        let b = im.get(0).unwrap();
+        assert_ne!(a.id, b.id);
+        im.free(b);
+        assert_eq!(im.get(0).unwrap().id, b.id);
+    }
+
     #[test]
     fn can_take_returned_ids() {
         let max_ids = 1;
@@ -128,17 +128,28 @@ impl GetTime for MockGetTime {
     }
 }
 
-#[test]
-fn scene_runs_fixed_update_n_times() {
-    let mut scene = empty_scene(1);
+/// Returns timer after a loop which took one second (50 fixed steps)
+fn one_second_timer() -> Timer {
     let mut t = Timer::new(50);
     let gt = MockGetTime {
         time: RefCell::new(0.0),
     };
-    let mut ctx = Context::default();
     t.loop_start(&gt);
     t.loop_end(&gt);
-    ctx.time = t;
+    t
+}
+
+fn one_second_context() -> Context {
+    Context {
+        time: one_second_timer(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn scene_runs_fixed_update_n_times() {
+    let mut scene = empty_scene(1);
+    let mut ctx = one_second_context();
     let go = FixedUpdateCheck(0);
     let id = scene.add_gameobject(go, 0).unwrap();
     _ = scene.run_loop(&mut ctx);
@@ -199,3 +210,557 @@ fn scene_gameobjects_find_others_by_name() {
     let mut ctx = Context::default();
     _ = scene.run_loop(&mut ctx);
 }
+
+struct FailingGO;
+impl GameObject for FailingGO {
+    fn update(&mut self, _ctx: &Context, _scene: &Scene) -> GameResult {
+        Err(GameError::GameLogicError("failed".into()))
+    }
+    fn name(&self) -> &str {
+        "failing"
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[test]
+fn scene_annotates_gameobject_errors() {
+    let mut scene = empty_scene(10);
+    let id = scene.add_gameobject(FailingGO, 2).unwrap();
+    let mut ctx = Context::default();
+    let err = scene.run_loop(&mut ctx).unwrap_err();
+    match err {
+        GameError::GameObjectError {
+            ref name,
+            id: err_id,
+            phase,
+            ..
+        } => {
+            assert_eq!(name, "failing");
+            assert_eq!(err_id, id);
+            assert_eq!(phase, Phase::Update);
+        }
+        _ => panic!("error was not annotated: {err}"),
+    }
+    assert!(matches!(err.root(), GameError::GameLogicError(_)));
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn scene_log_and_continue_keeps_failing_gameobject() {
+    let mut scene = empty_scene(10);
+    scene.set_error_policy(ErrorPolicy::LogAndContinue);
+    let id = scene.add_gameobject(FailingGO, 0).unwrap();
+    let mut ctx = Context::default();
+    assert!(scene.run_loop(&mut ctx).is_ok());
+    assert!(scene.run_loop(&mut ctx).is_ok());
+    assert!(scene.gameobject_by_id::<FailingGO>(&id).is_some());
+    assert_eq!(scene.errors().len(), 2);
+}
+
+#[test]
+fn scene_kill_policy_removes_failing_gameobject() {
+    let mut scene = empty_scene(1);
+    scene.set_error_policy(ErrorPolicy::Kill);
+    let id = scene.add_gameobject(FailingGO, 0).unwrap();
+    let mut ctx = Context::default();
+    assert!(scene.run_loop(&mut ctx).is_ok());
+    assert!(scene.gameobject_by_id::<FailingGO>(&id).is_none());
+    assert_eq!(scene.take_errors().len(), 1);
+    // id was returned to the pool
+    assert!(scene.add_gameobject(FailingGO, 0).is_ok());
+}
+
+struct ParallelGO {
+    sequential_updates: u32,
+    parallel_updates: u32,
+    fail: bool,
+}
+
+impl ParallelGO {
+    fn new(fail: bool) -> Self {
+        ParallelGO {
+            sequential_updates: 0,
+            parallel_updates: 0,
+            fail,
+        }
+    }
+}
+
+impl GameObject for ParallelGO {
+    fn update(&mut self, _ctx: &Context, _scene: &Scene) -> GameResult {
+        self.sequential_updates += 1;
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "parallel"
+    }
+    fn as_parallel(&mut self) -> Option<&mut dyn ParallelGameObject> {
+        Some(self)
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ParallelGameObject for ParallelGO {
+    fn par_update(&mut self, _ctx: &ParallelContext, scene: &SceneView) -> GameResult {
+        assert!(scene.get_gameobject_id("parallel").is_some());
+        self.parallel_updates += 1;
+        if self.fail {
+            return Err(GameError::GameLogicError("parallel failure".into()));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn scene_runs_parallel_gameobjects_only_in_parallel_layers() {
+    let mut scene = empty_scene(10);
+    scene.set_layer_parallel(1, true).unwrap();
+    assert!(scene.set_layer_parallel(3, true).is_err());
+    let sequential = scene.add_gameobject(ParallelGO::new(false), 0).unwrap();
+    let parallel: Vec<GameObjectId> = (0..4)
+        .map(|_| scene.add_gameobject(ParallelGO::new(false), 1).unwrap())
+        .collect();
+    let mut ctx = Context::default();
+    scene.run_loop(&mut ctx).unwrap();
+    scene.run_loop(&mut ctx).unwrap();
+    let go = scene.gameobject_by_id::<ParallelGO>(&sequential).unwrap();
+    assert_eq!((go.sequential_updates, go.parallel_updates), (2, 0));
+    for id in parallel {
+        let go = scene.gameobject_by_id::<ParallelGO>(&id).unwrap();
+        assert_eq!((go.sequential_updates, go.parallel_updates), (0, 2));
+    }
+}
+
+#[test]
+fn scene_handles_parallel_gameobject_errors() {
+    let mut scene = empty_scene(10);
+    scene.set_layer_parallel(0, true).unwrap();
+    scene.set_error_policy(ErrorPolicy::Kill);
+    let failing = scene.add_gameobject(ParallelGO::new(true), 0).unwrap();
+    let ok = scene.add_gameobject(ParallelGO::new(false), 0).unwrap();
+    let mut ctx = Context::default();
+    scene.run_loop(&mut ctx).unwrap();
+    assert!(scene.gameobject_by_id::<ParallelGO>(&failing).is_none());
+    assert!(scene.gameobject_by_id::<ParallelGO>(&ok).is_some());
+    match scene.take_errors().as_slice() {
+        [GameError::GameObjectError { id, phase, .. }] => {
+            assert_eq!(*id, failing);
+            assert_eq!(*phase, Phase::Update);
+        }
+        errors => panic!("unexpected errors: {errors:?}"),
+    }
+}
+
+#[test]
+fn scene_runs_systems_over_world_queries() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Counter(u32);
+
+    let mut scene = empty_scene(1);
+    let e = scene.world().spawn();
+    scene.world().insert(e, Counter(0));
+    scene
+        .add_system(Phase::Update, |_, scene| {
+            scene.world().query::<Counter>(|_, c| c.0 += 1);
+            Ok(())
+        })
+        .unwrap();
+    assert!(scene.add_system(Phase::Start, |_, _| Ok(())).is_err());
+    let mut ctx = Context::default();
+    scene.run_loop(&mut ctx).unwrap();
+    scene.run_loop(&mut ctx).unwrap();
+    assert_eq!(scene.world().get::<Counter>(e), Some(Counter(2)));
+}
+
+struct Bullet {
+    speed: u32,
+    lifetime: u32,
+    resets: u32,
+}
+
+impl GameObject for Bullet {
+    fn update(&mut self, _ctx: &Context, _scene: &Scene) -> GameResult {
+        self.lifetime = self.lifetime.saturating_sub(1);
+        Ok(())
+    }
+    fn reset(&mut self) {
+        self.resets += 1;
+        self.speed = 0;
+    }
+    fn is_dead(&mut self) -> bool {
+        self.lifetime == 0
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
+}
+
+#[test]
+fn scene_reuses_pooled_gameobjects() {
+    let mut scene = empty_scene(2);
+    scene.create_pool(2, || Bullet {
+        speed: 0,
+        lifetime: 0,
+        resets: 0,
+    });
+    assert_eq!(scene.pooled_count::<Bullet>(), 2);
+    let id = scene
+        .spawn_pooled(0, |b: &mut Bullet| {
+            b.speed = 10;
+            b.lifetime = 1;
+        })
+        .unwrap();
+    assert_eq!(scene.pooled_count::<Bullet>(), 1);
+    let mut ctx = Context::default();
+    scene.run_loop(&mut ctx).unwrap();
+    assert!(scene.gameobject_by_id::<Bullet>(&id).is_none());
+    assert_eq!(scene.pooled_count::<Bullet>(), 2);
+    // the pool grows when empty, but the third gameobject runs out of ids
+    for _ in 0..2 {
+        scene.spawn_pooled(0, |b: &mut Bullet| b.lifetime = 5).unwrap();
+    }
+    assert!(scene.spawn_pooled(0, |b: &mut Bullet| b.lifetime = 5).is_err());
+    assert!(scene.spawn_pooled(0, |_: &mut TestGO| ()).is_err());
+}
+
+#[test]
+fn scene_with_growable_ids_is_not_limited() {
+    let mut scene = empty_scene(1);
+    scene.set_growable_ids(true);
+    for _ in 0..5 {
+        assert!(scene.add_gameobject(TestGO::new(0, 0), 0).is_ok());
+    }
+    let mut ctx = Context::default();
+    scene.run_loop(&mut ctx).unwrap();
+}
+
+struct Sorted {
+    key: i32,
+    transform: Option<crate::components::transform::Transform>,
+    draws: Rc<RefCell<Vec<i32>>>,
+    tag: i32,
+}
+
+impl GameObject for Sorted {
+    fn draw(&mut self, _ctx: &Context, _scene: &Scene) -> GameResult {
+        self.draws.borrow_mut().push(self.tag);
+        Ok(())
+    }
+    fn sort_key(&self) -> i32 {
+        self.key
+    }
+    fn transform(&self) -> Option<&crate::components::transform::Transform> {
+        self.transform.as_ref()
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+fn sorted_scene(mode: SortMode, draws: &Rc<RefCell<Vec<i32>>>) -> Scene {
+    use crate::components::transform::Transform;
+    let mut scene = empty_scene(10);
+    scene.set_layer_sort_mode(0, mode).unwrap();
+    let at = |z: f32| {
+        Some(Transform::new(
+            glm::vec3(0.0, 0.0, z),
+            glm::Vec3::zeros(),
+            glm::vec3(1.0, 1.0, 1.0),
+        ))
+    };
+    // (tag, key, transform)
+    for (tag, key, transform) in [(0, 0, at(1.0)), (1, 0, at(5.0)), (2, -1, at(3.0)), (3, 0, None)] {
+        let go = Sorted {
+            key,
+            transform,
+            draws: draws.clone(),
+            tag,
+        };
+        scene.add_gameobject(go, 0).unwrap();
+    }
+    scene
+}
+
+#[test]
+fn scene_sorts_draw_order_per_layer() {
+    let cases = [
+        (SortMode::Insertion, vec![0, 1, 2, 3]),
+        (SortMode::SortKey, vec![2, 0, 1, 3]),
+        (SortMode::FrontToBack, vec![2, 3, 0, 1]),
+        (SortMode::BackToFront, vec![2, 3, 1, 0]),
+    ];
+    for (mode, expected) in cases {
+        let draws = Rc::new(RefCell::new(Vec::new()));
+        let mut scene = sorted_scene(mode, &draws);
+        scene.run_loop(&mut Context::default()).unwrap();
+        assert_eq!(*draws.borrow(), expected, "{mode:?}");
+    }
+}
+
+struct Body {
+    transform: Transform,
+    collider: crate::collision::Collider,
+    speed: f32,
+    events: Rc<RefCell<Vec<String>>>,
+}
+
+impl Body {
+    fn new(x: f32, shape: crate::collision::Shape, events: &Rc<RefCell<Vec<String>>>) -> Self {
+        Body {
+            transform: Transform::new(
+                glm::vec3(x, 0.0, 0.0),
+                glm::Vec3::zeros(),
+                glm::vec3(1.0, 1.0, 1.0),
+            ),
+            collider: crate::collision::Collider::new(shape),
+            speed: 0.0,
+            events: events.clone(),
+        }
+    }
+
+    fn log(&self, event: &str, other: GameObjectId, scene: &Scene) {
+        let x = self.transform.position().x;
+        let other = scene.transform(&other).unwrap().position().x;
+        self.events.borrow_mut().push(format!("{x} {event} {other}"));
+    }
+}
+
+impl GameObject for Body {
+    fn fixed_update(&mut self, _ctx: &Context, _scene: &Scene) -> GameResult {
+        self.transform.position_mut().x += self.speed;
+        Ok(())
+    }
+    fn collider(&self) -> Option<&crate::collision::Collider> {
+        Some(&self.collider)
+    }
+    fn on_collision_enter(&mut self, _ctx: &Context, scene: &Scene, c: &crate::collision::Collision) -> GameResult {
+        self.log("enter", c.other, scene);
+        Ok(())
+    }
+    fn on_collision_stay(&mut self, _ctx: &Context, scene: &Scene, c: &crate::collision::Collision) -> GameResult {
+        self.log("stay", c.other, scene);
+        Ok(())
+    }
+    fn on_collision_exit(&mut self, _ctx: &Context, scene: &Scene, c: &crate::collision::Collision) -> GameResult {
+        self.log("exit", c.other, scene);
+        Ok(())
+    }
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.transform)
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[test]
+fn scene_reports_collisions_before_fixed_update() {
+    use crate::collision::{Shape, ALL_LAYERS};
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let mut scene = empty_scene(2);
+    let mut ball = Body::new(0.0, Shape::Sphere { radius: 0.5 }, &events);
+    ball.speed = 1.0;
+    ball.collider = ball.collider.layer(1);
+    let ball = scene.add_gameobject(ball, 0).unwrap();
+    let half_extents = glm::vec3(1.0, 1.0, 1.0);
+    let wall = Body::new(10.0, Shape::Obb { half_extents }, &events);
+    scene.add_gameobject(wall, 1).unwrap();
+
+    let mut ctx = one_second_context();
+    scene.run_loop(&mut ctx).unwrap();
+    // each gameobject gets its events, the ball is touching the wall from 8.5 to 11.5
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            "9 enter 10", "10 enter 9", "10 stay 10", "10 stay 10", "11 stay 10", "10 stay 11",
+            "12 exit 10", "10 exit 12",
+        ]
+    );
+
+    let ray = Ray::new(glm::Vec3::zeros(), glm::Vec3::x());
+    let hit = scene.raycast(&ray, 100.0, ALL_LAYERS).unwrap();
+    assert_eq!(hit.distance, 9.0);
+    let hit = scene.raycast(&ray, 100.0, 1 << 1).unwrap();
+    assert_eq!(hit.id, ball);
+    assert_eq!(hit.distance, 49.5);
+    assert!(scene.raycast(&ray, 40.0, 1 << 1).is_none());
+    assert_eq!(scene.raycast_all(&ray, 100.0, ALL_LAYERS).len(), 2);
+    let hit = scene.sphere_cast(&ray, 1.0, 100.0, ALL_LAYERS).unwrap();
+    assert_eq!(hit.distance, 8.0);
+    assert_eq!(scene.overlap_sphere(glm::vec3(50.0, 1.0, 0.0), 0.6, ALL_LAYERS), vec![ball]);
+}
+
+struct Falling {
+    transform: Transform,
+    collider: crate::collision::Collider,
+    body: crate::physics::RigidBody,
+    /// position seen by fixed_update in each step
+    heights: Vec<f32>,
+}
+
+impl GameObject for Falling {
+    fn fixed_update(&mut self, _ctx: &Context, _scene: &Scene) -> GameResult {
+        self.heights.push(self.transform.position().y);
+        Ok(())
+    }
+    fn collider(&self) -> Option<&crate::collision::Collider> {
+        Some(&self.collider)
+    }
+    fn rigid_body(&self) -> Option<&crate::physics::RigidBody> {
+        Some(&self.body)
+    }
+    fn rigid_body_mut(&mut self) -> Option<&mut crate::physics::RigidBody> {
+        Some(&mut self.body)
+    }
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.transform)
+    }
+    fn transform_mut(&mut self) -> Option<&mut Transform> {
+        Some(&mut self.transform)
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[test]
+fn scene_moves_rigid_bodies_before_fixed_update() {
+    use crate::collision::{Collider, Shape};
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let mut scene = empty_scene(3);
+    let half_extents = glm::vec3(10.0, 0.5, 10.0);
+    let floor = Body::new(0.0, Shape::Aabb { half_extents }, &events);
+    scene.add_gameobject(floor, 0).unwrap();
+    let falling = |x: f32, layer: u8| Falling {
+        transform: Transform::new(glm::vec3(x, 3.0, 0.0), glm::Vec3::zeros(), glm::vec3(1.0, 1.0, 1.0)),
+        collider: Collider::new(Shape::Sphere { radius: 0.5 }).layer(layer),
+        body: crate::physics::RigidBody::new(1.0),
+        heights: Vec::new(),
+    };
+    let ball = scene.add_gameobject(falling(0.0, 0), 1).unwrap();
+    // the floor doesn't collide with layer 1, so this ball falls through it
+    let ghost = falling(2.0, 1);
+    let mut ghost_collider = ghost.collider.clone();
+    ghost_collider.mask = !(1 << 0);
+    let ghost = scene.add_gameobject(Falling { collider: ghost_collider, ..ghost }, 1).unwrap();
+
+    let mut ctx = Context::default();
+    for _ in 0..3 {
+        ctx.time = one_second_timer();
+        scene.run_loop(&mut ctx).unwrap();
+    }
+    let ball_id = ball;
+    let ball = scene.gameobject_by_id::<Falling>(&ball).unwrap();
+    assert!(ball.heights[0] < 3.0);
+    assert!((ball.transform.position().y - 1.0).abs() < 0.02);
+    assert!(ball.body.is_sleeping());
+    let ghost = scene.gameobject_by_id::<Falling>(&ghost).unwrap();
+    assert!(ghost.transform.position().y < -10.0);
+    // the floor gets collision events of the ball only
+    assert!(events.borrow()[0].starts_with("0 enter"));
+    assert!(events.borrow().iter().all(|e| !e.ends_with(" 2")));
+    // queries see colliders placed by the last physics step
+    let down = crate::geometry::Ray::new(glm::vec3(0.0, 5.0, 0.0), glm::vec3(0.0, -1.0, 0.0));
+    let hit = scene.raycast(&down, 10.0, u32::MAX).unwrap();
+    assert_eq!(hit.id, ball_id);
+    assert!((hit.distance - 3.5).abs() < 0.02);
+}
+
+struct Platformer {
+    transform: Transform,
+    collider: Option<crate::collision2d::Collider2D>,
+    controller: Option<crate::collision2d::character::CharacterController2D>,
+    body: Option<crate::physics::RigidBody>,
+    /// distance to the ground below seen by fixed_update in each step
+    ground: Vec<Option<f32>>,
+}
+
+impl Platformer {
+    fn new(x: f32, y: f32) -> Self {
+        Platformer {
+            transform: Transform::new(glm::vec3(x, y, 0.0), glm::Vec3::zeros(), glm::vec3(1.0, 1.0, 1.0)),
+            collider: None,
+            controller: None,
+            body: None,
+            ground: Vec::new(),
+        }
+    }
+}
+
+impl GameObject for Platformer {
+    fn fixed_update(&mut self, ctx: &Context, scene: &Scene) -> GameResult {
+        let position = self.transform.position().xy();
+        let hit = scene.raycast_2d(position, glm::vec2(0.0, -1.0), 10.0, u32::MAX);
+        self.ground.push(hit.map(|h| h.distance));
+        if let Some(controller) = self.controller.as_mut() {
+            let delta = glm::vec2(1.0, -9.81) * ctx.time.fixed_time_step() as f32;
+            controller.move_by(scene, &mut self.transform, delta);
+        }
+        Ok(())
+    }
+    fn collider_2d(&self) -> Option<&crate::collision2d::Collider2D> {
+        self.collider.as_ref()
+    }
+    fn rigid_body(&self) -> Option<&crate::physics::RigidBody> {
+        self.body.as_ref()
+    }
+    fn rigid_body_mut(&mut self) -> Option<&mut crate::physics::RigidBody> {
+        self.body.as_mut()
+    }
+    fn transform(&self) -> Option<&Transform> {
+        Some(&self.transform)
+    }
+    fn transform_mut(&mut self) -> Option<&mut Transform> {
+        Some(&mut self.transform)
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[test]
+fn scene_moves_2d_characters_and_rigid_bodies() {
+    use crate::collision2d::{character::CharacterController2D, tilemap::Tilemap, Collider2D, Shape2D};
+    let mut scene = empty_scene(3);
+    let map = Tilemap::from_rows(&["........", "########"], glm::vec2(1.0, 1.0)).unwrap();
+    let level = Platformer {
+        collider: Some(Collider2D::new(Shape2D::Tilemap(Rc::new(map)))),
+        ..Platformer::new(0.0, 0.0)
+    };
+    let level = scene.add_gameobject(level, 0).unwrap();
+    let box_collider = Collider2D::new(Shape2D::Box { half_extents: glm::vec2(0.25, 0.5) });
+    let player = Platformer {
+        controller: Some(CharacterController2D::new(box_collider.clone())),
+        ..Platformer::new(1.5, 1.6)
+    };
+    let player = scene.add_gameobject(player, 1).unwrap();
+    let crate_ = Platformer {
+        collider: Some(box_collider),
+        body: Some(crate::physics::RigidBody::new(1.0)),
+        ..Platformer::new(5.5, 3.0)
+    };
+    let crate_id = scene.add_gameobject(crate_, 1).unwrap();
+
+    let mut ctx = one_second_context();
+    scene.run_loop(&mut ctx).unwrap();
+    let player = scene.gameobject_by_id::<Platformer>(&player).unwrap();
+    // walks 1 unit per second on the floor
+    let x = 1.5 + player.ground.len() as f32 / 50.0;
+    assert!((player.transform.position() - glm::vec3(x, 1.5, 0.0)).magnitude() < 1e-3);
+    assert!(player.controller.as_ref().unwrap().is_grounded());
+    assert!((player.ground[0].unwrap() - 0.6).abs() < 1e-3);
+    // the rigid body with 2D collider stays in the XY plane
+    let crate_ = scene.gameobject_by_id::<Platformer>(&crate_id).unwrap();
+    let position = *crate_.transform.position();
+    assert!((position.y - 1.5).abs() < 0.02 && position.z == 0.0, "{position}");
+    assert!((crate_.ground.last().unwrap().unwrap() - 0.5).abs() < 0.02);
+    assert_eq!(scene.overlap_circle(glm::vec2(5.5, 1.5), 0.1, u32::MAX), vec![crate_id]);
+    assert_eq!(scene.overlap_circle(glm::vec2(0.5, 0.5), 0.1, u32::MAX), vec![level]);
+}
@@ -0,0 +1,6 @@
+//! Helpers shared by unit tests
+
+/// Asserts that two vectors are equal up to floating point errors
+pub(crate) fn assert_close(a: &glm::Vec3, b: &glm::Vec3) {
+    assert!(glm::distance(a, b) < 1e-4, "{a:?} != {b:?}");
+}
@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 /// Struct responsible for calculating and providing
 /// all data related to loop timing
 pub struct Timer {
@@ -6,6 +8,11 @@ pub struct Timer {
     pub(crate) fps: u32,
     loop_start_time: f64,
     time_since_last_fixed_update: f64,
+    time_scale: Cell<f64>,
+    paused: Cell<bool>,
+    step_requested: Cell<bool>,
+    /// true if scene should only be drawn this frame
+    pub(crate) frame_paused: bool,
 }
 
 pub(crate) trait GetTime {
@@ -21,6 +28,10 @@ impl Timer {
             /// default is 50 times per second
             fixed_time_step: 1.0 / (fixed_fps as f64),
             time_since_last_fixed_update: 0.0,
+            time_scale: Cell::new(1.0),
+            paused: Cell::new(false),
+            step_requested: Cell::new(false),
+            frame_paused: false,
         }
     }
 
@@ -30,15 +41,23 @@ impl Timer {
     }
 
     /// Marks end of loop and calculates delta_time
+    /// while paused delta_time is 0 unless a single step was requested
     pub(crate) fn loop_end(&mut self, t: &dyn GetTime) {
-        self.delta_time = t.get_timestamp() - self.loop_start_time;
+        self.frame_paused = self.paused.get() && !self.step_requested.get();
+        self.delta_time = if !self.paused.get() {
+            (t.get_timestamp() - self.loop_start_time) * self.time_scale.get()
+        } else if self.step_requested.replace(false) {
+            self.fixed_time_step
+        } else {
+            0.0
+        };
         self.time_since_last_fixed_update += self.delta_time;
     }
 
     /// Calculates how many fixed_update steps should be performed during next loop
     /// and changes it's internal state accordingly
     pub(crate) fn get_fixed_steps(&mut self) -> usize {
-        if self.time_since_last_fixed_update > self.fixed_time_step {
+        if self.time_since_last_fixed_update >= self.fixed_time_step {
             let x = self.time_since_last_fixed_update / self.fixed_time_step;
             self.time_since_last_fixed_update -= self.fixed_time_step * x.floor();
             x.floor() as usize
@@ -57,9 +76,40 @@ impl Timer {
         self.delta_time
     }
 
+    /// Returns duration of a single fixed_update step in seconds
+    pub fn fixed_time_step(&self) -> f64 {
+        self.fixed_time_step
+    }
+
     pub fn fps(&self) -> u32 {
         self.fps
     }
+
+    /// Scales delta_time and fixed_update frequency, starting from next frame
+    /// e.g. 0.5 runs the game in slow motion and 0.0 pauses it
+    pub fn set_time_scale(&self, scale: f64) {
+        self.time_scale.set(scale.max(0.0));
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale.get()
+    }
+
+    /// Pauses the game starting from next frame, scene is still drawn but not updated
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.set(paused);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    /// Runs a single frame with one fixed step while the game is paused
+    pub fn step(&self) {
+        if self.paused.get() {
+            self.step_requested.set(true);
+        }
+    }
 }
 
 impl Default for Timer {
@@ -71,32 +121,24 @@ impl Default for Timer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
 
-    static mut COUNTER: usize = 0;
-
-    fn reset_counter() {
-        unsafe {
-            COUNTER = 0;
-        }
-    }
-
-    fn count_up() -> f64 {
-        unsafe {
-            COUNTER += 1;
-            (COUNTER - 1) as f64
-        }
+    /// Returns timestamps 0, 1, 2... each test has its own counter as tests run in parallel
+    struct FakeTimer {
+        counter: Cell<usize>,
     }
-
-    struct FakeTimer;
     impl FakeTimer {
         pub fn new() -> Self {
-            reset_counter();
-            FakeTimer {}
+            FakeTimer {
+                counter: Cell::new(0),
+            }
         }
     }
     impl GetTime for FakeTimer {
         fn get_timestamp(&self) -> f64 {
-            count_up()
+            let count = self.counter.get();
+            self.counter.set(count + 1);
+            count as f64
         }
     }
 
@@ -120,6 +162,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn time_scale_changes_delta_time() {
+        let mut t = Timer::default();
+        let get_time = FakeTimer::new();
+        t.set_time_scale(0.5);
+        t.loop_start(&get_time);
+        t.loop_end(&get_time);
+        assert_eq!(t.delta_time(), 0.5);
+    }
+
+    #[test]
+    fn paused_timer_runs_single_steps() {
+        let mut t = Timer::default();
+        let get_time = FakeTimer::new();
+        t.set_paused(true);
+        t.loop_start(&get_time);
+        t.loop_end(&get_time);
+        assert!(t.frame_paused);
+        assert_eq!(t.delta_time(), 0.0);
+        assert_eq!(t.get_fixed_steps(), 0);
+        t.step();
+        t.loop_start(&get_time);
+        t.loop_end(&get_time);
+        assert!(!t.frame_paused);
+        assert_eq!(t.delta_time(), t.fixed_time_step);
+        assert_eq!(t.get_fixed_steps(), 1);
+        t.loop_start(&get_time);
+        t.loop_end(&get_time);
+        assert!(t.frame_paused);
+    }
+
     #[test]
     fn calculates_fixed_update_count() {
         let mut t = Timer::default();
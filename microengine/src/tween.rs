@@ -0,0 +1,768 @@
+//! Tween subsystem animating values over time.
+//! Tweens describe how a value changes (from, to, duration, easing, delay, looping)
+//! and Animations bind them to targets: position, rotation or scale of a gameobject
+//! exposing its transform (see GameObject::transform_mut) or a shared TweenValue.
+//! Animations are added with ctx.tweens and advanced by the game after update,
+//! using scaled delta_time, so they stop while the game is paused.
+//! Animations belong to the scene active when they were added and are suspended
+//! while other scenes run (or dropped when their disposable scene is left).
+//!
+//! ```ignore
+//! let alpha = TweenValue::new(0.0f32);
+//! ctx.tweens.add(Animation::sequence(vec![
+//!     Animation::position(id, Tween::to(glm::vec3(0.0, 2.0, 0.0), 1.0).ease(Ease::BackOut)),
+//!     Animation::value(&alpha, Tween::new(0.0, 1.0, 0.5).looping(Loop::PingPong(None))),
+//! ]));
+//! ```
+
+use crate::{context::Context, error::GameResult, gameobject::GameObjectId, scene::Scene};
+use std::{
+    cell::{Cell, RefCell},
+    f32::consts::PI,
+    rc::Rc,
+};
+
+/// Standard easing curves mapping normalized time to animation progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    /// overshoots backwards before moving to target
+    BackIn,
+    /// overshoots target before settling
+    BackOut,
+    BackInOut,
+    ElasticOut,
+    BounceOut,
+}
+
+impl Ease {
+    /// Returns progress for time t in range [0, 1], result is 0 for t = 0 and 1 for t = 1
+    pub fn apply(&self, t: f32) -> f32 {
+        const BACK: f32 = 1.70158;
+        let t = t.clamp(0.0, 1.0);
+        // maps ease-in curve to in-out by mirroring its second half
+        let in_out = |f: fn(f32) -> f32| match t < 0.5 {
+            true => f(t * 2.0) / 2.0,
+            false => 1.0 - f((1.0 - t) * 2.0) / 2.0,
+        };
+        let out = |f: fn(f32) -> f32| 1.0 - f(1.0 - t);
+        let quad = |t: f32| t * t;
+        let cubic = |t: f32| t * t * t;
+        let sine = |t: f32| 1.0 - (t * PI / 2.0).cos();
+        let expo = |t: f32| {
+            if t == 0.0 {
+                0.0
+            } else {
+                2f32.powf(10.0 * t - 10.0)
+            }
+        };
+        let back = |t: f32| t * t * ((BACK + 1.0) * t - BACK);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => quad(t),
+            Ease::QuadOut => out(quad),
+            Ease::QuadInOut => in_out(quad),
+            Ease::CubicIn => cubic(t),
+            Ease::CubicOut => out(cubic),
+            Ease::CubicInOut => in_out(cubic),
+            Ease::SineIn => sine(t),
+            Ease::SineOut => out(sine),
+            Ease::SineInOut => in_out(sine),
+            Ease::ExpoIn => expo(t),
+            Ease::ExpoOut => out(expo),
+            Ease::ExpoInOut => in_out(expo),
+            Ease::BackIn => back(t),
+            Ease::BackOut => out(back),
+            Ease::BackInOut => in_out(back),
+            Ease::ElasticOut => match t {
+                0.0 | 1.0 => t,
+                _ => 2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * 2.0 * PI / 3.0).sin() + 1.0,
+            },
+            Ease::BounceOut => bounce_out(t),
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// Values that can be animated
+pub trait Tweenable: Copy + 'static {
+    /// Returns value between from and to, t may leave [0, 1] range for overshooting easings
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        from + (to - from) * t
+    }
+}
+
+impl Tweenable for glm::Vec2 {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        glm::lerp(from, to, t)
+    }
+}
+
+impl Tweenable for glm::Vec3 {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        glm::lerp(from, to, t)
+    }
+}
+
+impl Tweenable for glm::Vec4 {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        glm::lerp(from, to, t)
+    }
+}
+
+impl Tweenable for glm::Quat {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        glm::quat_slerp(from, to, t)
+    }
+}
+
+/// How many times tween is played and in which direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Loop {
+    #[default]
+    Once,
+    /// plays from start to end given number of times, None means forever
+    Restart(Option<u32>),
+    /// plays forwards and then backwards, each direction counts as one play,
+    /// None means forever
+    PingPong(Option<u32>),
+}
+
+impl Loop {
+    fn plays(&self) -> Option<u32> {
+        match self {
+            Loop::Once => Some(1),
+            Loop::Restart(n) | Loop::PingPong(n) => *n,
+        }
+    }
+}
+
+pub type TweenCallback = Box<dyn FnOnce(&Context, &Scene) -> GameResult>;
+
+/// Description of a value change over time, bind it to a target with Animation
+pub struct Tween<T: Tweenable> {
+    /// None means animation starts from target's value at the moment it starts
+    pub from: Option<T>,
+    pub to: T,
+    /// length of a single play in seconds
+    pub duration: f32,
+    pub ease: Ease,
+    /// seconds before the first play starts
+    pub delay: f32,
+    pub looping: Loop,
+    on_complete: Option<TweenCallback>,
+}
+
+impl<T: Tweenable> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32) -> Self {
+        Tween {
+            from: Some(from),
+            ..Tween::to(to, duration)
+        }
+    }
+
+    /// Creates tween starting from the current value of its target
+    pub fn to(to: T, duration: f32) -> Self {
+        Tween {
+            from: None,
+            to,
+            duration,
+            ease: Ease::Linear,
+            delay: 0.0,
+            looping: Loop::Once,
+            on_complete: None,
+        }
+    }
+
+    pub fn ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    pub fn delay(mut self, seconds: f32) -> Self {
+        self.delay = seconds;
+        self
+    }
+
+    pub fn looping(mut self, looping: Loop) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Sets function called after the last play finishes, never called for endless loops
+    /// or cancelled tweens
+    pub fn on_complete<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&Context, &Scene) -> GameResult + 'static,
+    {
+        self.on_complete = Some(Box::new(f));
+        self
+    }
+
+    /// Returns (value progress, finished) after given time since tween started
+    /// (excluding delay)
+    fn progress(&self, time: f32) -> (f32, bool) {
+        let plays = self.looping.plays();
+        if self.duration <= 0.0 || plays == Some(0) {
+            return (1.0, true);
+        }
+        let play = (time / self.duration).floor();
+        let (play, t, finished) = match plays {
+            Some(n) if play >= n as f32 => (n as f32 - 1.0, 1.0, true),
+            _ => (play, time / self.duration - play, false),
+        };
+        let backwards = matches!(self.looping, Loop::PingPong(_)) && play % 2.0 == 1.0;
+        let t = if backwards { 1.0 - t } else { t };
+        (self.ease.apply(t), finished)
+    }
+
+    /// Returns total length including delay, None for endless loops
+    fn total_duration(&self) -> Option<f32> {
+        self.looping
+            .plays()
+            .map(|n| self.delay + self.duration.max(0.0) * n as f32)
+    }
+}
+
+/// Value shared between tween subsystem and gameobject reading it
+#[derive(Debug, Default)]
+pub struct TweenValue<T: Tweenable>(Rc<Cell<T>>);
+
+impl<T: Tweenable> Clone for TweenValue<T> {
+    fn clone(&self) -> Self {
+        TweenValue(self.0.clone())
+    }
+}
+
+impl<T: Tweenable> TweenValue<T> {
+    pub fn new(value: T) -> Self {
+        TweenValue(Rc::new(Cell::new(value)))
+    }
+
+    pub fn get(&self) -> T {
+        self.0.get()
+    }
+
+    pub fn set(&self, value: T) {
+        self.0.set(value)
+    }
+}
+
+/// Running animation, implemented by tweens bound to a target and by groups
+trait Animate {
+    /// Advances animation by given time, returns time left unused if it finished
+    fn advance(&mut self, dt: f32, scene: &mut Scene, done: &mut Vec<TweenCallback>)
+        -> Option<f32>;
+
+    /// Returns true if animation changes given gameobject
+    fn animates(&self, id: &GameObjectId) -> bool;
+}
+
+type Getter<T> = Box<dyn Fn(&Scene) -> Option<T>>;
+type Setter<T> = Box<dyn Fn(&mut Scene, T)>;
+
+struct BoundTween<T: Tweenable> {
+    tween: Tween<T>,
+    elapsed: f32,
+    from: Option<T>,
+    get: Getter<T>,
+    set: Setter<T>,
+    target: Option<GameObjectId>,
+}
+
+impl<T: Tweenable> Animate for BoundTween<T> {
+    fn advance(
+        &mut self,
+        dt: f32,
+        scene: &mut Scene,
+        done: &mut Vec<TweenCallback>,
+    ) -> Option<f32> {
+        self.elapsed += dt;
+        let time = self.elapsed - self.tween.delay;
+        if time < 0.0 {
+            return None;
+        }
+        let from = match (self.from, self.tween.from) {
+            (Some(from), _) | (None, Some(from)) => Some(from),
+            (None, None) => (self.get)(scene),
+        };
+        // target is gone, there is nothing to animate
+        let Some(from) = from else {
+            return Some(0.0);
+        };
+        self.from = Some(from);
+        let (t, finished) = self.tween.progress(time);
+        (self.set)(scene, T::interpolate(&from, &self.tween.to, t));
+        if !finished {
+            return None;
+        }
+        done.extend(self.tween.on_complete.take());
+        Some(self.elapsed - self.tween.total_duration().unwrap_or(self.elapsed))
+    }
+
+    fn animates(&self, id: &GameObjectId) -> bool {
+        self.target
+            .is_some_and(|t| t.id == id.id && t.layer == id.layer)
+    }
+}
+
+enum Group {
+    Sequence(usize),
+    Parallel(Vec<Option<f32>>),
+}
+
+struct AnimationGroup {
+    animations: Vec<Box<dyn Animate>>,
+    group: Group,
+}
+
+impl Animate for AnimationGroup {
+    fn advance(
+        &mut self,
+        dt: f32,
+        scene: &mut Scene,
+        done: &mut Vec<TweenCallback>,
+    ) -> Option<f32> {
+        match &mut self.group {
+            Group::Sequence(current) => {
+                let mut dt = dt;
+                while let Some(animation) = self.animations.get_mut(*current) {
+                    dt = animation.advance(dt, scene, done)?;
+                    *current += 1;
+                }
+                Some(dt)
+            }
+            Group::Parallel(left) => {
+                for (animation, left) in self.animations.iter_mut().zip(left.iter_mut()) {
+                    if left.is_none() {
+                        *left = animation.advance(dt, scene, done);
+                    }
+                }
+                left.iter()
+                    .try_fold(dt, |min, left| left.map(|l| min.min(l)))
+            }
+        }
+    }
+
+    fn animates(&self, id: &GameObjectId) -> bool {
+        self.animations.iter().any(|a| a.animates(id))
+    }
+}
+
+/// Delay or callback step of a sequence
+struct Step {
+    wait: f32,
+    callback: Option<TweenCallback>,
+}
+
+impl Animate for Step {
+    fn advance(
+        &mut self,
+        dt: f32,
+        _scene: &mut Scene,
+        done: &mut Vec<TweenCallback>,
+    ) -> Option<f32> {
+        self.wait -= dt;
+        if self.wait > 0.0 {
+            return None;
+        }
+        done.extend(self.callback.take());
+        Some(-self.wait)
+    }
+
+    fn animates(&self, _id: &GameObjectId) -> bool {
+        false
+    }
+}
+
+/// Tween bound to its target or a group of animations, add it with ctx.tweens.add
+pub struct Animation(Box<dyn Animate>);
+
+impl Animation {
+    fn bind<T, G, S>(tween: Tween<T>, target: Option<GameObjectId>, get: G, set: S) -> Self
+    where
+        T: Tweenable,
+        G: Fn(&Scene) -> Option<T> + 'static,
+        S: Fn(&mut Scene, T) + 'static,
+    {
+        Animation(Box::new(BoundTween {
+            tween,
+            elapsed: 0.0,
+            from: None,
+            get: Box::new(get),
+            set: Box::new(set),
+            target,
+        }))
+    }
+
+    /// Animates position of gameobject with given id
+    pub fn position(id: GameObjectId, tween: Tween<glm::Vec3>) -> Self {
+        Animation::bind(
+            tween,
+            Some(id),
+            move |scene| scene.transform(&id).map(|t| *t.position()),
+            move |scene, v| {
+                if let Some(t) = scene.transform_mut(&id) {
+                    *t.position_mut() = v;
+                }
+            },
+        )
+    }
+
+    /// Animates rotation of gameobject with given id
+    pub fn rotation(id: GameObjectId, tween: Tween<glm::Quat>) -> Self {
+        Animation::bind(
+            tween,
+            Some(id),
+            move |scene| scene.transform(&id).map(|t| *t.rotation()),
+            move |scene, v| {
+                if let Some(t) = scene.transform_mut(&id) {
+                    *t.rotation_mut() = v;
+                }
+            },
+        )
+    }
+
+    /// Animates scale of gameobject with given id
+    pub fn scale(id: GameObjectId, tween: Tween<glm::Vec3>) -> Self {
+        Animation::bind(
+            tween,
+            Some(id),
+            move |scene| scene.transform(&id).map(|t| *t.scale()),
+            move |scene, v| {
+                if let Some(t) = scene.transform_mut(&id) {
+                    *t.scale_mut() = v;
+                }
+            },
+        )
+    }
+
+    /// Animates shared value
+    pub fn value<T: Tweenable>(value: &TweenValue<T>, tween: Tween<T>) -> Self {
+        let (get, set) = (value.clone(), value.clone());
+        Animation::bind(
+            tween,
+            None,
+            move |_| Some(get.get()),
+            move |_, v| set.set(v),
+        )
+    }
+
+    /// Plays animations one after another
+    pub fn sequence(animations: Vec<Animation>) -> Self {
+        Animation(Box::new(AnimationGroup {
+            animations: animations.into_iter().map(|a| a.0).collect(),
+            group: Group::Sequence(0),
+        }))
+    }
+
+    /// Plays animations at the same time, finishes when all of them finish
+    pub fn parallel(animations: Vec<Animation>) -> Self {
+        Animation(Box::new(AnimationGroup {
+            group: Group::Parallel(vec![None; animations.len()]),
+            animations: animations.into_iter().map(|a| a.0).collect(),
+        }))
+    }
+
+    /// Does nothing for given time, useful in sequences
+    pub fn wait(seconds: f32) -> Self {
+        Animation(Box::new(Step {
+            wait: seconds,
+            callback: None,
+        }))
+    }
+
+    /// Calls given function when reached in a sequence
+    pub fn call<F>(f: F) -> Self
+    where
+        F: FnOnce(&Context, &Scene) -> GameResult + 'static,
+    {
+        Animation(Box::new(Step {
+            wait: 0.0,
+            callback: Some(Box::new(f)),
+        }))
+    }
+}
+
+/// Handle used to cancel running animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TweenId(u64);
+
+#[derive(Default)]
+struct TweensState {
+    next_id: u64,
+    /// name of the active scene, new animations belong to it
+    scene: String,
+    animations: Vec<(TweenId, String, Animation)>,
+}
+
+/// Tween subsystem, available as ctx.tweens
+#[derive(Default)]
+pub struct Tweens {
+    state: RefCell<TweensState>,
+}
+
+impl Tweens {
+    /// Starts given animation in this frame's tween update
+    pub fn add(&self, animation: Animation) -> TweenId {
+        let mut state = self.state.borrow_mut();
+        state.next_id += 1;
+        let id = TweenId(state.next_id);
+        let scene = state.scene.clone();
+        state.animations.push((id, scene, animation));
+        id
+    }
+
+    /// Shorthand for add(Animation::position(id, tween))
+    pub fn position(&self, id: GameObjectId, tween: Tween<glm::Vec3>) -> TweenId {
+        self.add(Animation::position(id, tween))
+    }
+
+    /// Shorthand for add(Animation::rotation(id, tween))
+    pub fn rotation(&self, id: GameObjectId, tween: Tween<glm::Quat>) -> TweenId {
+        self.add(Animation::rotation(id, tween))
+    }
+
+    /// Shorthand for add(Animation::scale(id, tween))
+    pub fn scale(&self, id: GameObjectId, tween: Tween<glm::Vec3>) -> TweenId {
+        self.add(Animation::scale(id, tween))
+    }
+
+    /// Shorthand for add(Animation::value(value, tween))
+    pub fn value<T: Tweenable>(&self, value: &TweenValue<T>, tween: Tween<T>) -> TweenId {
+        self.add(Animation::value(value, tween))
+    }
+
+    /// Stops animation leaving animated values as they are, its callbacks are not called
+    pub fn cancel(&self, id: TweenId) {
+        self.state
+            .borrow_mut()
+            .animations
+            .retain(|(i, _, _)| *i != id);
+    }
+
+    /// Stops all animations of the active scene changing given gameobject,
+    /// e.g. before it is destroyed
+    pub fn cancel_gameobject(&self, id: &GameObjectId) {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        state
+            .animations
+            .retain(|(_, scene, a)| *scene != state.scene || !a.0.animates(id));
+    }
+
+    pub fn is_running(&self, id: TweenId) -> bool {
+        self.state
+            .borrow()
+            .animations
+            .iter()
+            .any(|(i, _, _)| *i == id)
+    }
+
+    /// Returns number of running animations
+    pub fn len(&self) -> usize {
+        self.state.borrow().animations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Advances all animations by delta_time and calls completion callbacks,
+    /// their errors are handled by the scene's error policy
+    pub(crate) fn update(&self, ctx: &Context, scene: &mut Scene) -> GameResult {
+        for callback in self.advance(ctx.time.delta_time() as f32, scene) {
+            if let Err(e) = callback(ctx, scene) {
+                scene.handle_callback_error(ctx, e)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns callbacks of animations finished in this step,
+    /// they are called after releasing the state so they can add new animations
+    fn advance(&self, dt: f32, scene: &mut Scene) -> Vec<TweenCallback> {
+        let mut done = Vec::new();
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        state.animations.retain_mut(|(_, tween_scene, a)| {
+            *tween_scene != state.scene || a.0.advance(dt, scene, &mut done).is_none()
+        });
+        done
+    }
+
+    /// Makes animations of given scene run, called by the game when scene changes
+    pub(crate) fn set_scene(&self, name: &str) {
+        self.state.borrow_mut().scene = name.into();
+    }
+
+    /// Drops animations of given scene, called when disposable scene is left
+    pub(crate) fn remove_scene(&self, name: &str) {
+        self.state
+            .borrow_mut()
+            .animations
+            .retain(|(_, scene, _)| scene != name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene() -> Scene {
+        Scene::new("tweens", 1, 10, false)
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        use Ease::*;
+        let all = [
+            Linear, QuadIn, QuadOut, QuadInOut, CubicIn, CubicOut, CubicInOut, SineIn, SineOut,
+            SineInOut, ExpoIn, ExpoOut, ExpoInOut, BackIn, BackOut, BackInOut, ElasticOut,
+            BounceOut,
+        ];
+        for ease in all {
+            assert!(close(ease.apply(0.0), 0.0), "{ease:?}");
+            assert!(close(ease.apply(1.0), 1.0), "{ease:?}");
+        }
+        assert!(close(QuadInOut.apply(0.5), 0.5));
+        assert!(QuadIn.apply(0.25) < 0.25 && QuadOut.apply(0.25) > 0.25);
+        assert!(BackOut.apply(0.7) > 1.0);
+    }
+
+    #[test]
+    fn value_tween_waits_for_delay_and_finishes() {
+        let (tweens, mut scene) = (Tweens::default(), scene());
+        let value = TweenValue::new(5.0f32);
+        let id = tweens.value(&value, Tween::new(0.0, 10.0, 1.0).delay(0.5));
+        tweens.advance(0.25, &mut scene);
+        assert_eq!(value.get(), 5.0);
+        tweens.advance(0.75, &mut scene);
+        assert!(close(value.get(), 5.0));
+        assert!(tweens.is_running(id));
+        tweens.advance(1.0, &mut scene);
+        assert_eq!(value.get(), 10.0);
+        assert!(!tweens.is_running(id));
+    }
+
+    #[test]
+    fn ping_pong_plays_backwards() {
+        let (tweens, mut scene) = (Tweens::default(), scene());
+        let value = TweenValue::new(0.0f32);
+        tweens.value(
+            &value,
+            Tween::new(0.0, 1.0, 1.0).looping(Loop::PingPong(Some(2))),
+        );
+        tweens.advance(1.25, &mut scene);
+        assert!(close(value.get(), 0.75));
+        tweens.advance(1.0, &mut scene);
+        assert_eq!(value.get(), 0.0);
+        assert!(tweens.is_empty());
+        let id = tweens.value(&value, Tween::to(2.0, 1.0).looping(Loop::Restart(None)));
+        tweens.advance(10.5, &mut scene);
+        assert!(close(value.get(), 1.0));
+        tweens.cancel(id);
+        assert!(tweens.is_empty());
+    }
+
+    #[test]
+    fn sequences_carry_over_time_and_call_callbacks() {
+        let (ctx, mut scene) = (Context::default(), scene());
+        let value = TweenValue::new(0.0f32);
+        let called = Rc::new(Cell::new(0));
+        let (c1, c2) = (called.clone(), called.clone());
+        ctx.tweens.add(Animation::sequence(vec![
+            Animation::value(
+                &value,
+                Tween::to(1.0, 1.0).on_complete(move |_, _| {
+                    c1.set(c1.get() + 1);
+                    Ok(())
+                }),
+            ),
+            Animation::wait(0.5),
+            Animation::parallel(vec![
+                Animation::value(&value, Tween::to(3.0, 1.0)),
+                Animation::call(move |_, _| {
+                    c2.set(c2.get() + 10);
+                    Ok(())
+                }),
+            ]),
+        ]));
+        let callbacks = ctx.tweens.advance(2.0, &mut scene);
+        assert_eq!(callbacks.len(), 2);
+        assert!(close(value.get(), 2.0));
+        callbacks.into_iter().for_each(|c| c(&ctx, &scene).unwrap());
+        assert_eq!(called.get(), 11);
+        ctx.tweens.advance(1.0, &mut scene);
+        assert_eq!(value.get(), 3.0);
+        assert!(ctx.tweens.is_empty());
+    }
+
+    #[test]
+    fn animations_are_suspended_in_other_scenes() {
+        let (tweens, mut scene) = (Tweens::default(), scene());
+        let value = TweenValue::new(0.0f32);
+        tweens.set_scene("menu");
+        let id = tweens.value(&value, Tween::new(0.0, 1.0, 1.0));
+        tweens.set_scene("level");
+        tweens.advance(0.5, &mut scene);
+        assert_eq!(value.get(), 0.0);
+        tweens.set_scene("menu");
+        tweens.advance(0.5, &mut scene);
+        assert!(close(value.get(), 0.5));
+        tweens.remove_scene("menu");
+        assert!(!tweens.is_running(id));
+    }
+
+    #[test]
+    fn callback_errors_follow_error_policy() {
+        use crate::{error::GameError, scene::ErrorPolicy};
+        let (ctx, mut scene) = (Context::default(), scene());
+        let failing = || Animation::call(|_, _| Err(GameError::GameLogicError("failed".into())));
+        scene.set_error_policy(ErrorPolicy::LogAndContinue);
+        ctx.tweens.add(failing());
+        ctx.tweens.add(failing());
+        assert!(ctx.tweens.update(&ctx, &mut scene).is_ok());
+        assert_eq!(scene.errors().len(), 2);
+        scene.set_error_policy(ErrorPolicy::Abort);
+        ctx.tweens.add(failing());
+        assert!(ctx.tweens.update(&ctx, &mut scene).is_err());
+    }
+}
@@ -13,9 +13,14 @@ pub struct WindowConfig {
     pub posx: isize,
     pub posy: isize,
     pub is_fullscreen: bool,
+    pub is_borderless: bool,
+    /// index of the monitor used for fullscreen and borderless modes
+    pub monitor: usize,
+    pub render_cfg: RenderConfig,
 }
 
 impl WindowConfig {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         name: &str,
         width: usize,
@@ -23,6 +28,9 @@ impl WindowConfig {
         posx: isize,
         posy: isize,
         is_fullscreen: bool,
+        is_borderless: bool,
+        monitor: usize,
+        render_cfg: RenderConfig,
     ) -> Self {
         WindowConfig {
             name: name.into(),
@@ -31,16 +39,82 @@ impl WindowConfig {
             posx,
             posy,
             is_fullscreen,
+            is_borderless,
+            monitor,
+            render_cfg,
         }
     }
 }
 
 impl Default for WindowConfig {
     fn default() -> Self {
-        WindowConfig::new("default", 1280, 720, 0, 0, false)
+        WindowConfig::new(
+            "default",
+            1280,
+            720,
+            0,
+            0,
+            false,
+            false,
+            0,
+            Default::default(),
+        )
     }
 }
 
+/// Graphics context and framebuffer configuration used when creating window
+#[derive(Clone, Copy)]
+pub struct RenderConfig {
+    /// (major, minor) OpenGL version, at least (3, 3)
+    pub gl_version: (u32, u32),
+    /// None disables multisampling
+    pub msaa_samples: Option<u32>,
+    pub depth_bits: Option<u32>,
+    pub stencil_bits: Option<u32>,
+    pub srgb: bool,
+    pub blending: bool,
+    pub depth_test: bool,
+    /// RGBA color, can be changed later with ctx.window.set_clear_color()
+    pub clear_color: (f32, f32, f32, f32),
+    /// creates debug context, OpenGL errors and warnings are forwarded to ctx.log
+    /// (requires OpenGL 4.3 or KHR_debug extension)
+    pub debug_context: bool,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        RenderConfig {
+            gl_version: (3, 3),
+            msaa_samples: Some(4),
+            depth_bits: Some(24),
+            stencil_bits: Some(8),
+            srgb: false,
+            blending: true,
+            depth_test: true,
+            clear_color: (0.1, 0.1, 0.1, 1.0),
+            debug_context: false,
+        }
+    }
+}
+
+/// Resolution and refresh rate supported by a monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoMode {
+    pub width: usize,
+    pub height: usize,
+    pub refresh_rate: usize,
+}
+
+/// Monitor connected to the system, as reported by the backend
+#[derive(Debug, Clone)]
+pub struct MonitorInfo {
+    pub name: String,
+    /// current video mode of the monitor
+    pub video_mode: Option<VideoMode>,
+    /// all video modes supported by the monitor
+    pub video_modes: Vec<VideoMode>,
+}
+
 pub struct Window {
     name: String,
     width: usize,
@@ -51,7 +125,17 @@ pub struct Window {
     // TODO: merge is_fullscreen and fullscreen_requested into single value
     pub(crate) is_fullscreen: bool,
     pub(crate) fullscreen_requested: RefCell<bool>,
+    pub(crate) is_borderless: bool,
+    pub(crate) borderless_requested: RefCell<bool>,
+    pub(crate) monitor: usize,
+    pub(crate) monitor_requested: RefCell<Option<usize>>,
+    pub(crate) size_requested: RefCell<Option<(usize, usize)>>,
+    pub(crate) title_requested: RefCell<Option<String>>,
+    pub(crate) icon_requested: RefCell<Option<String>>,
+    pub(crate) clear_color: (f32, f32, f32, f32),
+    pub(crate) clear_color_requested: RefCell<Option<(f32, f32, f32, f32)>>,
     pub(crate) close_requested: RefCell<bool>,
+    monitors: Vec<MonitorInfo>,
     should_close: bool,
 }
 
@@ -70,6 +154,16 @@ impl Window {
             close_requested: RefCell::new(false),
             fullscreen_requested: RefCell::new(false),
             is_fullscreen,
+            borderless_requested: RefCell::new(false),
+            is_borderless: false,
+            monitor: 0,
+            monitor_requested: RefCell::new(None),
+            size_requested: RefCell::new(None),
+            title_requested: RefCell::new(None),
+            icon_requested: RefCell::new(None),
+            clear_color: RenderConfig::default().clear_color,
+            clear_color_requested: RefCell::new(None),
+            monitors: Vec::new(),
             width,
             height,
             posx,
@@ -96,6 +190,16 @@ impl Window {
         self.posy = posy;
     }
 
+    /// Use it in backend after window title has been changed
+    pub(crate) fn system_set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    /// Use it in backend to provide currently connected monitors
+    pub(crate) fn system_set_monitors(&mut self, monitors: Vec<MonitorInfo>) {
+        self.monitors = monitors;
+    }
+
     /// Function indicates that either system requested to close window
     /// or user called window.close() and active scene has finished
     pub fn should_close(&self) -> bool {
@@ -107,6 +211,39 @@ impl Window {
         *self.fullscreen_requested.borrow_mut() = fullscreen;
     }
 
+    /// Set or unset borderless windowed mode.
+    /// Borderless window covers the whole monitor without changing its video mode.
+    /// Fullscreen takes precedence if both are set.
+    pub fn set_borderless(&self, borderless: bool) {
+        *self.borderless_requested.borrow_mut() = borderless;
+    }
+
+    /// Select monitor (index into `monitors()`) used by fullscreen and borderless modes
+    pub fn set_monitor(&self, monitor: usize) {
+        *self.monitor_requested.borrow_mut() = Some(monitor);
+    }
+
+    /// Resize window, in fullscreen or borderless mode
+    /// the size will be used after going back to windowed mode
+    pub fn set_size(&self, width: usize, height: usize) {
+        *self.size_requested.borrow_mut() = Some((width, height));
+    }
+
+    /// Change window title
+    pub fn set_title(&self, title: &str) {
+        *self.title_requested.borrow_mut() = Some(title.into());
+    }
+
+    /// Set window icon from an image file
+    pub fn set_icon(&self, img_path: &str) {
+        *self.icon_requested.borrow_mut() = Some(img_path.into());
+    }
+
+    /// Change color used to clear the screen each frame
+    pub fn set_clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        *self.clear_color_requested.borrow_mut() = Some((r, g, b, a));
+    }
+
     /// Use it to end the game.
     pub fn close(&self) {
         *self.close_requested.borrow_mut() = true;
@@ -117,6 +254,34 @@ impl Window {
         self.is_fullscreen
     }
 
+    /// Check if window is in borderless windowed mode
+    pub fn is_borderless(&self) -> bool {
+        self.is_borderless
+    }
+
+    /// Index of the monitor used by fullscreen and borderless modes
+    pub fn monitor(&self) -> usize {
+        self.monitor
+    }
+
+    /// Returns all monitors connected to the system
+    pub fn monitors(&self) -> &[MonitorInfo] {
+        &self.monitors
+    }
+
+    /// Returns video modes supported by the selected monitor
+    pub fn video_modes(&self) -> &[VideoMode] {
+        match self.monitors.get(self.monitor) {
+            Some(m) => &m.video_modes,
+            None => &[],
+        }
+    }
+
+    /// Returns RGBA color used to clear the screen
+    pub fn clear_color(&self) -> (f32, f32, f32, f32) {
+        self.clear_color
+    }
+
     pub fn pos(&self) -> (isize, isize) {
         (self.posx, self.posy)
     }
@@ -154,7 +319,53 @@ impl From<WindowConfig> for Window {
             posy: value.posy,
             aspect_ratio: (value.height as f64) / (value.width as f64),
             is_fullscreen: value.is_fullscreen,
+            is_borderless: value.is_borderless,
+            monitor: value.monitor,
+            clear_color: value.render_cfg.clear_color,
             ..Default::default()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn video_modes_come_from_selected_monitor() {
+        let mode = VideoMode {
+            width: 640,
+            height: 480,
+            refresh_rate: 60,
+        };
+        let mut w = Window::default();
+        assert_eq!(w.video_modes().len(), 0);
+        w.system_set_monitors(vec![
+            MonitorInfo {
+                name: "first".into(),
+                video_mode: None,
+                video_modes: vec![],
+            },
+            MonitorInfo {
+                name: "second".into(),
+                video_mode: Some(mode),
+                video_modes: vec![mode],
+            },
+        ]);
+        w.monitor = 1;
+        assert_eq!(w.video_modes(), &[mode]);
+    }
+
+    #[test]
+    fn requests_are_stored_until_backend_takes_them() {
+        let w = Window::default();
+        w.set_size(100, 200);
+        w.set_title("title");
+        assert_eq!(w.size_requested.borrow_mut().take(), Some((100, 200)));
+        assert_eq!(
+            w.title_requested.borrow_mut().take().as_deref(),
+            Some("title")
+        );
+        assert!(w.size_requested.borrow().is_none());
+    }
+}